@@ -0,0 +1,140 @@
+use crate::scene::{NodeId, Scene, Transform};
+
+#[derive(Debug, Clone)]
+pub struct Keyframe {
+    pub time: f64,  // Seconds from the start of the clip
+    pub transform: Transform,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimationClip {
+    pub keyframes: Vec<Keyframe>,
+    pub looping: bool,
+}
+
+impl AnimationClip {
+    // Keyframes are sorted by time
+    pub fn new(mut keyframes: Vec<Keyframe>, looping: bool) -> Self {
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Self { keyframes, looping }
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+    }
+
+    // Transform at `time`, holding the first and last keyframes outside the
+    // clip. None for a clip without keyframes.
+    pub fn sample(&self, time: f64) -> Option<Transform> {
+        let first = self.keyframes.first()?;
+        // Index of the first keyframe after `time`
+        let next = self.keyframes.partition_point(|keyframe| keyframe.time <= time);
+        if next == 0 {
+            return Some(first.transform.clone());
+        }
+        if next == self.keyframes.len() {
+            return Some(self.keyframes[next - 1].transform.clone());
+        }
+
+        let (from, to) = (&self.keyframes[next - 1], &self.keyframes[next]);
+        let t = (time - from.time) / (to.time - from.time);
+        Some(from.transform.interpolate(&to.transform, t))
+    }
+}
+
+// Plays a clip on one scene node, see Scene::add_animator
+#[derive(Debug, Clone)]
+pub struct Animator {
+    pub clip: AnimationClip,
+    pub current_time: f64,
+    pub node_id: NodeId,
+}
+
+impl Animator {
+    pub fn new(clip: AnimationClip, node_id: NodeId) -> Self {
+        Self { clip, current_time: 0.0, node_id }
+    }
+
+    // Advances the clip and writes its transform to the node, if it still exists
+    pub fn update(&mut self, dt: f64, scene: &mut Scene) {
+        self.current_time += dt;
+        let duration = self.clip.duration();
+        if self.clip.looping && duration > 0.0 {
+            self.current_time = self.current_time.rem_euclid(duration);
+        }
+
+        if let (Some(transform), Some(node)) = (self.clip.sample(self.current_time), scene.get_node_mut(self.node_id)) {
+            node.transform.snap_to(&transform);
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !self.clip.looping && self.current_time >= self.clip.duration()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Vec3;
+
+    fn keyframe(time: f64, x: f64) -> Keyframe {
+        let mut transform = Transform::new();
+        transform.set_position(Vec3::new(x, 0.0, 0.0));
+        Keyframe { time, transform }
+    }
+
+    #[test]
+    fn test_animators_follow_keyframes() {
+        let mut scene = Scene::new();
+        let node = scene.create_node("door".to_string());
+        let clip = AnimationClip::new(vec![keyframe(2.0, 4.0), keyframe(0.0, 0.0), keyframe(3.0, 0.0)], false);
+        scene.add_animator(Animator::new(clip.clone(), node));
+
+        let x = |scene: &Scene| scene.get_node(node).unwrap().transform.position.x;
+        scene.update_animators(0.5);
+        assert!((x(&scene) - 1.0).abs() < 1e-10);
+        scene.update_animators(2.0);
+        assert!((x(&scene) - 2.0).abs() < 1e-10);
+        // Holds the last keyframe once the clip is over
+        scene.update_animators(5.0);
+        assert_eq!(x(&scene), 0.0);
+
+        let mut looping = Animator::new(AnimationClip { looping: true, ..clip }, node);
+        looping.update(3.5, &mut scene);
+        assert!((looping.current_time - 0.5).abs() < 1e-10);
+        assert!((x(&scene) - 1.0).abs() < 1e-10);
+        assert!(!looping.is_finished());
+
+        scene.update_transforms();
+        assert!((scene.get_node(node).unwrap().transform.world_matrix.data[0][3] - 1.0).abs() < 1e-10);
+        assert!(AnimationClip::new(Vec::new(), true).sample(1.0).is_none());
+    }
+
+    #[test]
+    fn test_finished_animators_release_their_node() {
+        let mut scene = Scene::new();
+        let node = scene.create_node("door".to_string());
+        let clip = AnimationClip::new(vec![keyframe(0.0, 0.0), keyframe(1.0, 2.0)], false);
+        scene.add_animator(Animator::new(clip.clone(), node));
+        scene.add_animator(Animator::new(AnimationClip { looping: true, ..clip.clone() }, node));
+        assert_eq!(scene.animator_count(), 2);
+
+        // The looping one keeps going, the other is dropped after its last frame
+        scene.update_animators(1.5);
+        assert_eq!(scene.animator_count(), 1);
+        scene.remove_animators(node);
+        assert_eq!(scene.animator_count(), 0);
+
+        scene.add_animator(Animator::new(clip, node));
+        scene.update_animators(2.0);
+        assert_eq!(scene.get_node(node).unwrap().transform.position.x, 2.0);
+        scene.get_node_mut(node).unwrap().transform.set_position(Vec3::new(-3.0, 0.0, 0.0));
+        scene.update_animators(0.1);
+        assert_eq!(scene.get_node(node).unwrap().transform.position.x, -3.0);
+
+        scene.add_animator(Animator::new(AnimationClip::new(vec![keyframe(0.0, 1.0)], true), node));
+        scene.clear_animators();
+        assert_eq!(scene.animator_count(), 0);
+    }
+}
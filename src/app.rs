@@ -1,35 +1,43 @@
-use std::time::Instant;
-use minifb::{Window, WindowOptions, Key};
+use std::path::Path;
+use minifb::{Window, WindowOptions, Key, MouseButton, MouseMode};
 
 use crate::renderer::Renderer;
 use crate::scene::Scene;
 use crate::camera::Camera;
+use crate::config::Config;
+use crate::rasterizer::Color;
 use crate::shape_factory::ShapeFactory;
 use crate::math::Vec3;
+use crate::timing::{FrameHistogram, FrameTimer};
 
+// Looked up in the working directory at startup
+const CONFIG_PATH: &str = "config.toml";
 
-pub struct Application {
-    window: Window,
-    renderer: Renderer,
-    scene: Scene,
-    camera: Camera,
-    last_frame: Instant,
+// Per-frame hooks replacing the built-in demo scene update and scene render
+pub type UpdateCallback = Box<dyn FnMut(&mut Scene, &mut Camera, f64)>;
+pub type RenderCallback = Box<dyn FnMut(&mut Renderer, &Scene, &Camera)>;
+
+// Everything a frame touches apart from the window, so frames can be
+// stepped without one
+pub struct AppState {
+    pub renderer: Renderer,
+    pub scene: Scene,
+    pub camera: Camera,
+    frame_timer: FrameTimer,
     delta_time: f64,
+    update_callback: Option<UpdateCallback>,
+    render_callback: Option<RenderCallback>,
+    frame_histogram: FrameHistogram,
 }
 
-impl Application {
-    pub fn new(width: usize, height: usize, title: &str) -> Self {
-        let window = Window::new(
-            title,
-            width,
-            height,
-            WindowOptions {
-                resize: true,
-                scale: minifb::Scale::X1,
-                ..WindowOptions::default()
-            },
-        ).expect("Failed to create window");
+pub struct Application {
+    window: Window,
+    state: AppState,
+    last_mouse_pos: Option<(f32, f32)>,
+}
 
+impl AppState {
+    pub fn new(width: usize, height: usize) -> Self {
         let renderer = Renderer::new(width, height);
         let scene = Scene::new();
         let mut camera = Camera::new(width as f64, height as f64);
@@ -39,87 +47,51 @@ impl Application {
         camera.look_at(Vec3::new(0.0, 0.0, 0.0));
 
         Self {
-            window,
             renderer,
             scene,
             camera,
-            last_frame: Instant::now(),
+            frame_timer: FrameTimer::new(0),
             delta_time: 0.0,
+            update_callback: None,
+            render_callback: None,
+            frame_histogram: FrameHistogram::default(),
         }
     }
 
-    pub fn run(&mut self) {
-        self.setup_scene();
-
-        while self.window.is_open() && !self.window.is_key_down(Key::Escape) {
-            self.update();
-            self.render();
-
-            // Update window with rendered frame
-            self.window.update_with_buffer(
-                self.renderer.get_buffer(),
-                self.renderer.width(),
-                self.renderer.height(),
-            ).unwrap();
-        }
+    // Called every frame with the frame's delta time instead of the default
+    // cube animation. World transforms are refreshed after it returns.
+    pub fn set_update_callback(&mut self, cb: UpdateCallback) {
+        self.update_callback = Some(cb);
     }
 
-    fn setup_scene(&mut self) {
-        // Create a smaller cube for testing
-        let cube_mesh = ShapeFactory::create_cube(2.0);
-        let cube_id = self.scene.create_mesh_node("cube".to_string(), cube_mesh);
-
-        // Position the cube in view
-        if let Some(node) = self.scene.get_node_mut(cube_id) {
-            node.transform.set_position(Vec3::new(0.0, 0.0, 0.0));
-            node.transform.set_scale(Vec3::new(1.0, 1.0, 1.0));
-        }
+    // Called every frame after the buffer is cleared, instead of drawing
+    // every visible mesh node
+    pub fn set_render_callback(&mut self, cb: RenderCallback) {
+        self.render_callback = Some(cb);
     }
 
-    fn update(&mut self) {
-        // Update delta time
-        let current_time = Instant::now();
-        self.delta_time = (current_time - self.last_frame).as_secs_f64();
-        self.last_frame = current_time;
-
-        self.handle_input();
-        self.update_scene();
+    pub fn delta_time(&self) -> f64 {
+        self.delta_time
     }
 
-    fn handle_input(&mut self) {
-        let movement_speed = 3.0 * self.delta_time;
-        let rotation_speed = 2.0 * self.delta_time;
+    // Runs one frame: ticks the timer, applies input, updates the scene and
+    // renders it into the frame buffer
+    pub fn step(&mut self, handle_input: impl FnOnce(&mut AppState)) {
+        self.delta_time = self.frame_timer.tick();
+        self.frame_histogram.record(self.delta_time * 1000.0);
 
-        // Camera movement
-        if self.window.is_key_down(Key::W) {
-            self.camera.move_forward(movement_speed);
-        }
-        if self.window.is_key_down(Key::S) {
-            self.camera.move_forward(-movement_speed);
-        }
-        if self.window.is_key_down(Key::A) {
-            self.camera.move_right(-movement_speed);
-        }
-        if self.window.is_key_down(Key::D) {
-            self.camera.move_right(movement_speed);
-        }
-        if self.window.is_key_down(Key::Q) {
-            self.camera.rotate_horizontal(-rotation_speed);
-        }
-        if self.window.is_key_down(Key::E) {
-            self.camera.rotate_horizontal(rotation_speed);
-        }
-        if self.window.is_key_down(Key::R){
-            self.camera.rotate_vertical(-rotation_speed*0.6);
-        }
-        if self.window.is_key_down(Key::F){
-            self.camera.rotate_vertical(rotation_speed*0.6);
-        }
+        handle_input(self);
+        self.scene.update_animators(self.delta_time);
 
-        // Toggle wireframe mode
-        if self.window.is_key_pressed(Key::O, minifb::KeyRepeat::No) {
-            self.renderer.toggle_wireframe();
+        match self.update_callback.as_mut() {
+            Some(callback) => {
+                callback(&mut self.scene, &mut self.camera, self.delta_time);
+                self.scene.update_transforms();
+            }
+            None => self.update_scene(),
         }
+
+        self.render();
     }
 
     fn update_scene(&mut self) {
@@ -142,16 +114,218 @@ impl Application {
         self.renderer.clear();
         self.camera.update();
 
+        if let Some(callback) = self.render_callback.as_mut() {
+            callback(&mut self.renderer, &self.scene, &self.camera);
+            return;
+        }
+
         self.scene.traverse_visible(|node| {
             if let Some(mesh) = &node.mesh {
                 self.renderer.render_mesh(mesh, &node.transform.world_matrix, &self.camera);
             }
         });
     }
+}
+
+impl Application {
+    pub fn new(width: usize, height: usize, title: &str) -> Self {
+        let window = Window::new(
+            title,
+            width,
+            height,
+            WindowOptions {
+                resize: true,
+                scale: minifb::Scale::X1,
+                ..WindowOptions::default()
+            },
+        ).expect("Failed to create window");
+
+        Self {
+            window,
+            state: AppState::new(width, height),
+            last_mouse_pos: None,
+        }
+    }
+
+    // Uses ./config.toml when present, otherwise the default settings
+    pub fn from_config_file() -> Self {
+        let path = Path::new(CONFIG_PATH);
+        let config = if path.exists() {
+            Config::from_file(path).unwrap_or_else(|err| {
+                eprintln!("Ignoring {}: {}", CONFIG_PATH, err);
+                Config::default()
+            })
+        } else {
+            Config::default()
+        };
+        Self::with_config(&config)
+    }
+
+    pub fn with_config(config: &Config) -> Self {
+        let mut app = Self::new(config.window_width, config.window_height, &config.window_title);
 
+        let (r, g, b, a) = config.clear_color;
+        app.state.renderer.set_clear_color(Color::new(r, g, b, a));
+
+        app.state.camera.fov = config.fov.to_radians();
+        app.state.camera.near = config.near_plane;
+        app.state.camera.far = config.far_plane;
+        app.state.camera.movement_speed = config.movement_speed;
+        app.state.camera.rotation_speed = config.rotation_speed;
+        app.state.camera.update_matrices();
+        app
+    }
+
+    pub fn set_update_callback(&mut self, cb: UpdateCallback) {
+        self.state.set_update_callback(cb);
+    }
+
+    pub fn set_render_callback(&mut self, cb: RenderCallback) {
+        self.state.set_render_callback(cb);
+    }
+
+    // Frame count, smoothed frame rate and total running time
+    pub fn frame_timer(&self) -> &FrameTimer {
+        &self.state.frame_timer
+    }
+
+    // Frame time distribution over recent frames
+    pub fn frame_histogram(&self) -> &FrameHistogram {
+        &self.state.frame_histogram
+    }
+
+    pub fn run(&mut self) {
+        self.setup_scene();
+
+        while self.window.is_open() && !self.window.is_key_down(Key::Escape) {
+            let (width, height) = self.window.get_size();
+            if (width, height) != (self.state.renderer.width(), self.state.renderer.height()) {
+                self.resize(width, height);
+            }
+
+            let (window, last_mouse_pos) = (&self.window, &mut self.last_mouse_pos);
+            self.state.step(|state| Self::handle_input(window, last_mouse_pos, state));
+
+            // Update window with rendered frame
+            let renderer = &mut self.state.renderer;
+            let (width, height) = (renderer.width(), renderer.height());
+            self.window.update_with_buffer(renderer.get_buffer(), width, height).unwrap();
+        }
+    }
+
+    // Matches the frame buffer and camera aspect ratio to a new window size.
+    // Zero sizes (e.g. a minimized window) are ignored.
+    pub fn resize(&mut self, new_width: usize, new_height: usize) {
+        if new_width == 0 || new_height == 0 {
+            return;
+        }
+        self.state.renderer.resize(new_width, new_height);
+        self.state.camera.aspect_ratio = new_width as f64 / new_height as f64;
+        self.state.camera.update_matrices();
+    }
+
+    fn setup_scene(&mut self) {
+        // Create a smaller cube for testing
+        let cube_mesh = ShapeFactory::create_cube(2.0);
+        let cube_id = self.state.scene.create_mesh_node("cube".to_string(), cube_mesh);
+
+        // Position the cube in view
+        if let Some(node) = self.state.scene.get_node_mut(cube_id) {
+            node.transform.set_position(Vec3::new(0.0, 0.0, 0.0));
+            node.transform.set_scale(Vec3::new(1.0, 1.0, 1.0));
+        }
+    }
+
+    fn handle_input(window: &Window, last_mouse_pos: &mut Option<(f32, f32)>, state: &mut AppState) {
+        // The camera scales amounts by its movement_speed and rotation_speed,
+        // so passing the frame time gives units and radians per second
+        let dt = state.delta_time;
+
+        // Camera movement
+        if window.is_key_down(Key::W) {
+            state.camera.move_forward(dt);
+        }
+        if window.is_key_down(Key::S) {
+            state.camera.move_forward(-dt);
+        }
+        if window.is_key_down(Key::A) {
+            state.camera.move_right(-dt);
+        }
+        if window.is_key_down(Key::D) {
+            state.camera.move_right(dt);
+        }
+        if window.is_key_down(Key::Q) {
+            state.camera.rotate_horizontal(-dt);
+        }
+        if window.is_key_down(Key::E) {
+            state.camera.rotate_horizontal(dt);
+        }
+        if window.is_key_down(Key::R){
+            state.camera.rotate_vertical(-dt * 0.6);
+        }
+        if window.is_key_down(Key::F){
+            state.camera.rotate_vertical(dt * 0.6);
+        }
+
+        // Orbit around the target by dragging with the left mouse button
+        if window.get_mouse_down(MouseButton::Left) {
+            if let Some((x, y)) = window.get_mouse_pos(MouseMode::Discard) {
+                if let Some((last_x, last_y)) = *last_mouse_pos {
+                    let orbit_speed = 0.01;
+                    state.camera.orbit_rotate(
+                        -(x - last_x) as f64 * orbit_speed,
+                        (y - last_y) as f64 * orbit_speed,
+                    );
+                }
+                *last_mouse_pos = Some((x, y));
+            }
+        } else {
+            *last_mouse_pos = None;
+        }
+
+        // Scroll to zoom
+        if let Some((_, scroll_y)) = window.get_scroll_wheel() {
+            state.camera.orbit_zoom(-scroll_y as f64 * 0.1);
+        }
+
+        // Cycle wireframe, flat and Gouraud shading
+        if window.is_key_pressed(Key::O, minifb::KeyRepeat::No) {
+            let mode = state.renderer.shading_mode().next();
+            state.renderer.set_shading_mode(mode);
+        }
+    }
 }
 
 impl Drop for Application {
     fn drop(&mut self) {
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_step_runs_callbacks_once_per_frame() {
+        let mut state = AppState::new(32, 24);
+        let updates = Rc::new(Cell::new(0));
+        let renders = Rc::new(Cell::new(0));
+        let inputs = Cell::new(0);
+
+        let counter = updates.clone();
+        state.set_update_callback(Box::new(move |_, _, _| counter.set(counter.get() + 1)));
+        let counter = renders.clone();
+        state.set_render_callback(Box::new(move |_, _, _| counter.set(counter.get() + 1)));
+
+        for _ in 0..5 {
+            state.step(|_| inputs.set(inputs.get() + 1));
+        }
+        assert_eq!(updates.get(), 5);
+        assert_eq!(renders.get(), 5);
+        assert_eq!(inputs.get(), 5);
+        assert_eq!(state.frame_timer.frame_count(), 5);
+        assert!(state.delta_time() >= 0.0);
+    }
+}
@@ -1,5 +1,17 @@
-use crate::math::{Vec3, Mat4};
-use std::f64::consts::PI;
+use crate::math::{Vec2, Vec3, Mat4};
+use crate::geometry::BoundingBox;
+use crate::scene::Scene;
+use std::f64::consts::{FRAC_PI_2, PI};
+
+// Keeps orbit elevation just short of the poles, where the view flips
+const ORBIT_PHI_EPSILON: f64 = 1e-3;
+const ORBIT_MIN_RADIUS: f64 = 1e-3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectionMode {
+    Perspective,
+    Orthographic,
+}
 
 #[derive(Debug, Clone)]
 pub struct Camera {
@@ -13,6 +25,8 @@ pub struct Camera {
     pub aspect_ratio: f64,
     pub near: f64,       // Near clipping plane
     pub far: f64,        // Far clipping plane
+    pub projection_mode: ProjectionMode,
+    pub ortho_scale: f64, // Half the view height in orthographic mode
 
     // Derived matrices
     view_matrix: Mat4,
@@ -33,6 +47,8 @@ impl Camera {
             aspect_ratio: width / height,
             near: 0.1,
             far: 100.0,
+            projection_mode: ProjectionMode::Perspective,
+            ortho_scale: 5.0,
             view_matrix: Mat4::identity(),
             projection_matrix: Mat4::identity(),
             movement_speed: 5.0,
@@ -49,42 +65,92 @@ impl Camera {
     }
 
     fn update_view_matrix(&mut self) {
-        // Calculate camera basis vectors
-        let forward = (self.target - self.position).normalize();
-        let right = forward.cross(&self.up).normalize();
-        let up = right.cross(&forward).normalize();
-
-        // Create rotation matrix
-        let mut rotation = Mat4::identity();
-        rotation.data[0][0] = right.x;
-        rotation.data[0][1] = right.y;
-        rotation.data[0][2] = right.z;
-        rotation.data[1][0] = up.x;
-        rotation.data[1][1] = up.y;
-        rotation.data[1][2] = up.z;
-        rotation.data[2][0] = -forward.x;
-        rotation.data[2][1] = -forward.y;
-        rotation.data[2][2] = -forward.z;
-
-        // Create translation matrix
-        let translation = Mat4::translation(-self.position.x, -self.position.y, -self.position.z);
-
-        // Combine matrices
-        self.view_matrix = rotation.multiply(&translation);
+        self.view_matrix = Mat4::look_at(self.position, self.target, self.up);
     }
 
     fn update_projection_matrix(&mut self) {
+        if self.projection_mode == ProjectionMode::Orthographic {
+            let top = self.ortho_scale;
+            let right = self.ortho_scale * self.aspect_ratio;
+            let (left, bottom) = (-right, -top);
+            let depth = self.far - self.near;
+
+            self.projection_matrix = Mat4::new([
+                [2.0 / (right - left), 0.0, 0.0, -(right + left) / (right - left)],
+                [0.0, 2.0 / (top - bottom), 0.0, -(top + bottom) / (top - bottom)],
+                [0.0, 0.0, -2.0 / depth, -(self.far + self.near) / depth],
+                [0.0, 0.0, 0.0, 1.0],
+            ]);
+            return;
+        }
+
         let f = 1.0 / (self.fov / 2.0).tan();
         let range_inv = 1.0 / (self.near - self.far);
 
+        // Row-major like the rest of Mat4, so w ends up as -z in view space
         self.projection_matrix = Mat4::new([
             [f / self.aspect_ratio, 0.0, 0.0, 0.0],
             [0.0, f, 0.0, 0.0],
-            [0.0, 0.0, (self.far + self.near) * range_inv, -1.0],
-            [0.0, 0.0, 2.0 * self.far * self.near * range_inv, 0.0],
+            [0.0, 0.0, (self.far + self.near) * range_inv, 2.0 * self.far * self.near * range_inv],
+            [0.0, 0.0, -1.0, 0.0],
         ]);
     }
 
+    // `scale` is half the visible height in world units
+    pub fn set_orthographic(&mut self, scale: f64) {
+        self.projection_mode = ProjectionMode::Orthographic;
+        self.ortho_scale = scale;
+        self.update_projection_matrix();
+    }
+
+    pub fn set_perspective(&mut self, fov: f64) {
+        self.projection_mode = ProjectionMode::Perspective;
+        self.fov = fov;
+        self.update_projection_matrix();
+    }
+
+    // Sets the clip planes from the nearest and farthest scene distances,
+    // with generous margins on both sides
+    pub fn auto_fit_clip_planes(&mut self, scene_near: f64, scene_far: f64) {
+        self.far = scene_far * 10.0;
+        self.near = scene_near * 0.001;
+        if self.near <= 0.0 {
+            // Camera inside the scene bounds; keep the depth range finite
+            self.near = self.far * 1e-6;
+        }
+        self.update_matrices();
+    }
+
+    // Moves the camera back along its current view direction until the whole
+    // scene fits in view, aims at its center and fits the clip planes
+    pub fn fit_to_scene(&mut self, scene: &Scene) {
+        let Some(bounds) = scene.compute_scene_bounds() else {
+            return;
+        };
+        let center = (bounds.min + bounds.max) * 0.5;
+        let radius = ((bounds.max - bounds.min) * 0.5).length();
+
+        let offset = self.position - self.target;
+        let direction = if offset.length() > 0.0 {
+            offset.normalize()
+        } else {
+            Vec3::new(0.0, 0.0, -1.0)
+        };
+
+        let half_fov_y = self.fov / 2.0;
+        let half_fov_x = (half_fov_y.tan() * self.aspect_ratio).atan();
+        let distance = radius / half_fov_y.min(half_fov_x).sin();
+
+        self.target = center;
+        self.position = center + direction * distance;
+
+        if let Some((scene_near, scene_far)) = scene.estimate_clip_planes(self.position) {
+            self.auto_fit_clip_planes(scene_near, scene_far);
+        } else {
+            self.update_matrices();
+        }
+    }
+
     pub fn get_view_matrix(&self) -> Mat4 {
         self.view_matrix.clone()
     }
@@ -100,23 +166,23 @@ impl Camera {
     // Camera movement methods
     pub fn move_forward(&mut self, amount: f64) {
         let forward = (self.target - self.position).normalize();
-        self.position = self.position + forward * (amount * self.movement_speed);
-        self.target = self.target + forward * (amount * self.movement_speed);
+        self.position += forward * (amount * self.movement_speed);
+        self.target += forward * (amount * self.movement_speed);
         self.update_matrices();
     }
 
     pub fn move_right(&mut self, amount: f64) {
         let forward = (self.target - self.position).normalize();
         let right = forward.cross(&self.up).normalize();
-        self.position = self.position + right * (amount * self.movement_speed);
-        self.target = self.target + right * (amount * self.movement_speed);
+        self.position += right * (amount * self.movement_speed);
+        self.target += right * (amount * self.movement_speed);
         self.update_matrices();
     }
 
     pub fn move_up(&mut self, amount: f64) {
         let up = self.up.normalize();
-        self.position = self.position + up * (amount * self.movement_speed);
-        self.target = self.target + up * (amount * self.movement_speed);
+        self.position += up * (amount * self.movement_speed);
+        self.target += up * (amount * self.movement_speed);
         self.update_matrices();
     }
 
@@ -142,6 +208,45 @@ impl Camera {
         self.update_matrices();
     }
 
+    // Places the camera on a sphere around `target`, looking at it. `theta` is
+    // the azimuth around +Y (0 looks down -Z from the +Z side) and `phi` the
+    // elevation above the XZ plane.
+    pub fn orbit(&mut self, target: Vec3, radius: f64, theta: f64, phi: f64) {
+        let radius = radius.max(ORBIT_MIN_RADIUS);
+        let phi = phi.clamp(-FRAC_PI_2 + ORBIT_PHI_EPSILON, FRAC_PI_2 - ORBIT_PHI_EPSILON);
+
+        self.target = target;
+        self.position = target + Vec3::new(
+            radius * phi.cos() * theta.sin(),
+            radius * phi.sin(),
+            radius * phi.cos() * theta.cos(),
+        );
+        self.update_matrices();
+    }
+
+    // Current (radius, theta, phi) of the camera around its target
+    fn orbit_angles(&self) -> (f64, f64, f64) {
+        let offset = self.position - self.target;
+        let radius = offset.length();
+        if radius == 0.0 {
+            return (0.0, 0.0, 0.0);
+        }
+        let theta = offset.x.atan2(offset.z);
+        let phi = (offset.y / radius).clamp(-1.0, 1.0).asin();
+        (radius, theta, phi)
+    }
+
+    pub fn orbit_rotate(&mut self, d_theta: f64, d_phi: f64) {
+        let (radius, theta, phi) = self.orbit_angles();
+        self.orbit(self.target, radius, theta + d_theta, phi + d_phi);
+    }
+
+    // Moves towards (negative delta) or away from the target
+    pub fn orbit_zoom(&mut self, delta: f64) {
+        let (radius, theta, phi) = self.orbit_angles();
+        self.orbit(self.target, radius + delta, theta, phi);
+    }
+
     // Frustum culling methods
     pub fn get_frustum_planes(&self) -> [Vec4; 6] {
         let vp_matrix = self.get_view_projection_matrix();
@@ -152,48 +257,115 @@ impl Camera {
         [
             // Left plane
             Vec4::new(
-                m[0][3] + m[0][0],
-                m[1][3] + m[1][0],
-                m[2][3] + m[2][0],
-                m[3][3] + m[3][0]
+                m[3][0] + m[0][0],
+                m[3][1] + m[0][1],
+                m[3][2] + m[0][2],
+                m[3][3] + m[0][3]
             ).normalize(),
             // Right plane
             Vec4::new(
-                m[0][3] - m[0][0],
-                m[1][3] - m[1][0],
-                m[2][3] - m[2][0],
-                m[3][3] - m[3][0]
+                m[3][0] - m[0][0],
+                m[3][1] - m[0][1],
+                m[3][2] - m[0][2],
+                m[3][3] - m[0][3]
             ).normalize(),
             // Bottom plane
             Vec4::new(
-                m[0][3] + m[0][1],
-                m[1][3] + m[1][1],
-                m[2][3] + m[2][1],
-                m[3][3] + m[3][1]
+                m[3][0] + m[1][0],
+                m[3][1] + m[1][1],
+                m[3][2] + m[1][2],
+                m[3][3] + m[1][3]
             ).normalize(),
             // Top plane
             Vec4::new(
-                m[0][3] - m[0][1],
-                m[1][3] - m[1][1],
-                m[2][3] - m[2][1],
-                m[3][3] - m[3][1]
+                m[3][0] - m[1][0],
+                m[3][1] - m[1][1],
+                m[3][2] - m[1][2],
+                m[3][3] - m[1][3]
             ).normalize(),
             // Near plane
             Vec4::new(
-                m[0][3] + m[0][2],
-                m[1][3] + m[1][2],
-                m[2][3] + m[2][2],
-                m[3][3] + m[3][2]
+                m[3][0] + m[2][0],
+                m[3][1] + m[2][1],
+                m[3][2] + m[2][2],
+                m[3][3] + m[2][3]
             ).normalize(),
             // Far plane
             Vec4::new(
-                m[0][3] - m[0][2],
-                m[1][3] - m[1][2],
-                m[2][3] - m[2][2],
-                m[3][3] - m[3][2]
+                m[3][0] - m[2][0],
+                m[3][1] - m[2][1],
+                m[3][2] - m[2][2],
+                m[3][3] - m[2][3]
             ).normalize(),
         ]
     }
+
+    // True when the sphere is at least partly inside the frustum
+    pub fn frustum_cull_sphere(&self, center: Vec3, radius: f64) -> bool {
+        self.get_frustum_planes().iter()
+            .all(|plane| plane.distance_to_point(&center) >= -radius)
+    }
+
+    // True when the world-space box is at least partly inside the frustum.
+    // Per plane only the corner furthest along its normal is tested, so boxes
+    // near frustum corners may be kept conservatively.
+    pub fn frustum_cull_aabb(&self, bbox: &BoundingBox) -> bool {
+        self.get_frustum_planes().iter().all(|plane| {
+            let positive = Vec3::new(
+                if plane.x >= 0.0 { bbox.max.x } else { bbox.min.x },
+                if plane.y >= 0.0 { bbox.max.y } else { bbox.min.y },
+                if plane.z >= 0.0 { bbox.max.z } else { bbox.min.z },
+            );
+            plane.distance_to_point(&positive) >= 0.0
+        })
+    }
+
+    // Screen position in pixels (y down) and NDC depth (-1 at the near plane,
+    // 1 at the far plane) back to world space. A degenerate projection maps
+    // everything to the camera position.
+    pub fn unproject(&self, screen_pos: Vec2, depth: f64, viewport_width: f64, viewport_height: f64) -> Vec3 {
+        let ndc = Vec3::new(
+            screen_pos.x / viewport_width * 2.0 - 1.0,
+            1.0 - screen_pos.y / viewport_height * 2.0,
+            depth,
+        );
+        match self.get_view_projection_matrix().inverse() {
+            Some(inverse) => inverse.transform_vec3(&ndc),
+            None => self.position,
+        }
+    }
+
+    // World-space ray through a screen position, starting on the near plane
+    // so it also works for orthographic cameras. Returns (origin, direction).
+    pub fn screen_to_ray(&self, screen_pos: Vec2, viewport: (f64, f64)) -> (Vec3, Vec3) {
+        let (width, height) = viewport;
+        let near = self.unproject(screen_pos, -1.0, width, height);
+        let far = self.unproject(screen_pos, 1.0, width, height);
+        (near, (far - near).normalize())
+    }
+
+    // World-space picking ray through pixel (x, y), y down, as (origin,
+    // direction) with a unit-length direction. Perspective rays start at the
+    // camera position, orthographic ones on the near plane.
+    pub fn get_ray(&self, x: f64, y: f64, width: f64, height: f64) -> (Vec3, Vec3) {
+        let forward = (self.target - self.position).normalize();
+        let (Some(inverse_projection), Some(inverse_view)) =
+            (self.projection_matrix.inverse(), self.view_matrix.inverse())
+        else {
+            return (self.position, forward);
+        };
+
+        let ndc = |z: f64| Vec3::new(x / width * 2.0 - 1.0, 1.0 - y / height * 2.0, z);
+        let near = inverse_projection.transform_vec3(&ndc(-1.0));
+        let far = inverse_projection.transform_vec3(&ndc(1.0));
+        let origin = match self.projection_mode {
+            ProjectionMode::Perspective => Vec3::zero(),
+            ProjectionMode::Orthographic => near,
+        };
+        let direction = inverse_view.transform_direction(&(far - near)).normalize();
+        (inverse_view.transform_vec3(&origin), direction)
+    }
+
     pub fn set_position(&mut self, position: Vec3) {
         self.position = position;
     }
@@ -220,11 +392,11 @@ impl Camera {
 
 // Helper struct for frustum planes
 #[derive(Debug, Clone, Copy)]
-struct Vec4 {
-    x: f64,
-    y: f64,
-    z: f64,
-    w: f64,
+pub struct Vec4 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub w: f64,
 }
 
 impl Vec4 {
@@ -232,6 +404,11 @@ impl Vec4 {
         Self { x, y, z, w }
     }
 
+    // Signed distance from a point to the plane; positive is inside the frustum
+    pub fn distance_to_point(&self, p: &Vec3) -> f64 {
+        self.x * p.x + self.y * p.y + self.z * p.z + self.w
+    }
+
     fn normalize(&self) -> Self {
         let length = (self.x * self.x + self.y * self.y + self.z * self.z).sqrt();
         if length != 0.0 {
@@ -278,6 +455,134 @@ mod tests {
         assert!(camera.target.x != initial_target.x);
     }
 
+    #[test]
+    fn test_orbit() {
+        let mut camera = Camera::new(800.0, 600.0);
+        let target = Vec3::new(1.0, 2.0, 3.0);
+
+        camera.orbit(target, 4.0, PI / 2.0, 0.0);
+        assert!((camera.position - Vec3::new(5.0, 2.0, 3.0)).length() < 1e-9);
+
+        camera.orbit_rotate(0.0, 0.5);
+        camera.orbit_zoom(-1.0);
+        assert!(((camera.position - target).length() - 3.0).abs() < 1e-9);
+        assert!(((camera.position.y - 2.0) / 3.0 - 0.5f64.sin()).abs() < 1e-9);
+
+        // Elevation stops short of the pole
+        camera.orbit_rotate(0.0, PI);
+        assert!(camera.position.y - 2.0 < 3.0);
+        let view = camera.get_view_matrix();
+        assert!(view.data[0][0].is_finite() && view.data[1][1].is_finite());
+    }
+
+    #[test]
+    fn test_fit_to_scene() {
+        let mut scene = Scene::new();
+        let cube = scene.create_mesh_node("cube".to_string(), crate::geometry::Mesh::create_cube(1.0));
+        let transform = &mut scene.get_node_mut(cube).unwrap().transform;
+        transform.set_position(Vec3::new(3.0, 0.0, 0.0));
+        transform.set_scale(Vec3::new(0.01, 0.01, 0.01));
+        scene.update_transforms();
+
+        let mut camera = Camera::new(800.0, 600.0);
+        camera.fit_to_scene(&scene);
+
+        assert!(camera.near < 0.001);
+        assert!(camera.near > 0.0 && camera.far > camera.near);
+        assert_eq!(camera.target, Vec3::new(3.0, 0.0, 0.0));
+
+        // The whole cube sits between the clip planes
+        let view_projection = camera.get_view_projection_matrix();
+        let ndc = view_projection.transform_vec3(&Vec3::new(3.0, 0.0, 0.0));
+        assert!(ndc.x.abs() < 1e-9 && ndc.y.abs() < 1e-9);
+        assert!(ndc.z > -1.0 && ndc.z < 1.0);
+    }
+
+    #[test]
+    fn test_orthographic_projection() {
+        let mut camera = Camera::new(800.0, 400.0);
+        camera.set_orthographic(2.0);
+        let view_projection = camera.get_view_projection_matrix();
+
+        // Size on screen doesn't depend on distance
+        let near_point = view_projection.transform_vec3(&Vec3::new(-4.0, 2.0, 0.0));
+        let far_point = view_projection.transform_vec3(&Vec3::new(-4.0, 2.0, 50.0));
+        assert!((near_point.x - 1.0).abs() < 1e-9 && (near_point.y - 1.0).abs() < 1e-9);
+        assert!((far_point.x - near_point.x).abs() < 1e-9);
+        assert!(far_point.z > near_point.z);
+
+        camera.set_perspective(PI / 2.0);
+        assert_eq!(camera.projection_mode, ProjectionMode::Perspective);
+        let view_projection = camera.get_view_projection_matrix();
+        let far_point = view_projection.transform_vec3(&Vec3::new(-4.0, 2.0, 50.0));
+        assert!(far_point.x.abs() < 0.1);
+    }
+
+    #[test]
+    fn test_frustum_culling_helpers() {
+        // Looks down +Z from z = -5 towards the origin
+        let camera = Camera::new(800.0, 600.0);
+
+        assert!(camera.frustum_cull_sphere(Vec3::zero(), 1.0));
+        assert!(!camera.frustum_cull_sphere(Vec3::new(0.0, 0.0, -10.0), 1.0));
+        assert!(!camera.frustum_cull_sphere(Vec3::new(50.0, 0.0, 0.0), 1.0));
+        // Straddling the right side plane still counts as visible
+        assert!(camera.frustum_cull_sphere(Vec3::new(4.5, 0.0, 0.0), 1.0));
+        assert!(!camera.frustum_cull_sphere(Vec3::new(0.0, 0.0, 200.0), 1.0));
+
+        let unit = |c: Vec3| BoundingBox::new(c - Vec3::new(0.5, 0.5, 0.5), c + Vec3::new(0.5, 0.5, 0.5));
+        assert!(camera.frustum_cull_aabb(&unit(Vec3::zero())));
+        assert!(camera.frustum_cull_aabb(&unit(Vec3::new(0.0, 0.0, 90.0))));
+        assert!(!camera.frustum_cull_aabb(&unit(Vec3::new(0.0, 0.0, -10.0))));
+        assert!(!camera.frustum_cull_aabb(&unit(Vec3::new(0.0, 40.0, 0.0))));
+        assert!(camera.frustum_cull_aabb(&BoundingBox::new(Vec3::new(-100.0, -1.0, 0.0), Vec3::new(100.0, 1.0, 1.0))));
+    }
+
+    #[test]
+    fn test_unproject_round_trip() {
+        let mut camera = Camera::new(800.0, 600.0);
+        let point = Vec3::new(1.0, 0.5, 2.0);
+
+        for orthographic in [false, true] {
+            if orthographic {
+                camera.set_orthographic(3.0);
+            }
+            let ndc = camera.get_view_projection_matrix().transform_vec3(&point);
+            let screen = Vec2::new((ndc.x + 1.0) * 400.0, (1.0 - ndc.y) * 300.0);
+            let unprojected = camera.unproject(screen, ndc.z, 800.0, 600.0);
+            assert!((unprojected - point).length() < 1e-9);
+
+            // The picking ray passes through the point
+            let (origin, direction) = camera.screen_to_ray(screen, (800.0, 600.0));
+            let t = (point - origin).dot(&direction);
+            assert!((origin + direction * t - point).length() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_get_ray() {
+        let mut camera = Camera::new(800.0, 600.0);
+        camera.set_position(Vec3::new(1.0, 2.0, -4.0));
+        camera.look_at(Vec3::new(0.0, 0.5, 1.0));
+        camera.update();
+        let forward = (camera.target - camera.position).normalize();
+
+        let (origin, direction) = camera.get_ray(400.0, 300.0, 800.0, 600.0);
+        assert!((origin - camera.position).length() < 1e-9);
+        assert!((direction - forward).length() < 1e-9);
+
+        // Matches the near-plane based picking ray off center too
+        let (_, corner) = camera.get_ray(10.0, 590.0, 800.0, 600.0);
+        let (_, expected) = camera.screen_to_ray(Vec2::new(10.0, 590.0), (800.0, 600.0));
+        assert!((corner - expected).length() < 1e-9);
+        assert!((corner.length() - 1.0).abs() < 1e-12);
+
+        camera.set_orthographic(3.0);
+        let (origin, direction) = camera.get_ray(400.0, 300.0, 800.0, 600.0);
+        assert!((origin - (camera.position + forward * camera.near)).length() < 1e-9);
+        assert!((direction - forward).length() < 1e-9);
+    }
+
     #[test]
     fn test_view_matrix() {
         let camera = Camera::new(800.0, 600.0);
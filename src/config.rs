@@ -1,11 +1,57 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "I/O error: {}", err),
+            ConfigError::Parse(err) => write!(f, "invalid config: {}", err),
+            ConfigError::Serialize(err) => write!(f, "failed to serialize config: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<io::Error> for ConfigError {
+    fn from(err: io::Error) -> Self {
+        ConfigError::Io(err)
+    }
+}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(err: toml::de::Error) -> Self {
+        ConfigError::Parse(err)
+    }
+}
+
+impl From<toml::ser::Error> for ConfigError {
+    fn from(err: toml::ser::Error) -> Self {
+        ConfigError::Serialize(err)
+    }
+}
+
+// Keys missing from a config file keep their default values
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     pub window_width: usize,
     pub window_height: usize,
-    pub window_title: &'static str,
+    pub window_title: String,
     pub clear_color: (u8, u8, u8, u8),
     pub movement_speed: f64,
     pub rotation_speed: f64,
-    pub fov: f64,
+    pub fov: f64,        // Degrees
     pub near_plane: f64,
     pub far_plane: f64,
 }
@@ -15,7 +61,7 @@ impl Default for Config {
         Self {
             window_width: 800,
             window_height: 600,
-            window_title: "sgr-rs",
+            window_title: "sgr-rs".to_string(),
             clear_color: (0, 0, 0, 255),
             movement_speed: 5.0,
             rotation_speed: 2.0,
@@ -25,3 +71,41 @@ impl Default for Config {
         }
     }
 }
+
+impl Config {
+    pub fn from_file(path: &Path) -> Result<Config, ConfigError> {
+        let src = fs::read_to_string(path)?;
+        Ok(toml::from_str(&src)?)
+    }
+
+    pub fn to_file(&self, path: &Path) -> Result<(), ConfigError> {
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_file_round_trip() {
+        let path = std::env::temp_dir().join(format!("ironsight_config_{}.toml", std::process::id()));
+
+        fs::write(&path, "window_width = 1280\nfov = 90.0\n").unwrap();
+        let config = Config::from_file(&path).unwrap();
+        assert_eq!(config.window_width, 1280);
+        assert_eq!(config.fov, 90.0);
+        assert_eq!(config.window_height, Config::default().window_height);
+
+        let config = Config { clear_color: (10, 20, 30, 255), ..config };
+        config.to_file(&path).unwrap();
+        assert_eq!(Config::from_file(&path).unwrap(), config);
+
+        fs::write(&path, "window_width = \"wide\"\n").unwrap();
+        assert!(matches!(Config::from_file(&path), Err(ConfigError::Parse(_))));
+
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(Config::from_file(&path), Err(ConfigError::Io(_))));
+    }
+}
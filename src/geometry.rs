@@ -1,17 +1,27 @@
 use crate::math::{Mat4, Vec2, Vec3};
-use std::collections::HashMap;
+use crate::rasterizer::Color;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fmt;
+use std::ops::Range;
 
 #[derive(Debug, Clone)]
 pub struct Vertex {
     pub position: Vec3,
     pub normal: Vec3,
     pub uv: Vec2,
+    pub uv2: Vec2,  // Secondary channel, e.g. non-overlapping lightmap UVs
+    pub color: Color,
+    // Tangent frame for normal mapping, see Mesh::compute_tangents_and_bitangents
+    pub tangent: Vec3,
+    pub bitangent: Vec3,
 }
 
 #[derive(Debug, Clone)]
 pub struct Face {
     pub vertices: [usize; 3],  // Indices into vertex array
     pub normal: Vec3,
+    pub color: Option<Color>,  // Overrides the draw color when set
 }
 
 #[derive(Debug, Clone)]
@@ -27,21 +37,126 @@ pub struct BoundingBox {
     pub max: Vec3,
 }
 
+// Meshes of decreasing detail, each used up to its distance threshold
+#[derive(Debug, Clone)]
+pub struct LodGroup {
+    pub lods: Vec<(f64, Mesh)>,
+}
+
+// Problems reported by Mesh::validate
+#[derive(Debug, Clone, PartialEq)]
+pub enum MeshError {
+    InvalidFaceIndex { face_idx: usize, vertex_idx: usize },
+    NanVertexPosition { vertex_idx: usize },
+    DegenerateFace { face_idx: usize },
+    NanNormal { vertex_idx: usize },
+    NonUnitNormal { vertex_idx: usize },
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MeshError::InvalidFaceIndex { face_idx, vertex_idx } => {
+                write!(f, "face {} references missing vertex {}", face_idx, vertex_idx)
+            }
+            MeshError::NanVertexPosition { vertex_idx } => write!(f, "vertex {} has a NaN position", vertex_idx),
+            MeshError::DegenerateFace { face_idx } => write!(f, "face {} has zero area", face_idx),
+            MeshError::NanNormal { vertex_idx } => write!(f, "vertex {} has a NaN normal", vertex_idx),
+            MeshError::NonUnitNormal { vertex_idx } => write!(f, "vertex {} normal is not unit length", vertex_idx),
+        }
+    }
+}
+
+impl std::error::Error for MeshError {}
+
 impl Vertex {
     pub fn new(position: Vec3, normal: Vec3, uv: Vec2) -> Self {
         Self {
             position,
             normal: normal.normalize(),
             uv,
+            uv2: Vec2::zero(),
+            color: Color::new(255, 255, 255, 255),
+            tangent: Vec3::zero(),
+            bitangent: Vec3::zero(),
         }
     }
 
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
     pub fn transform(&self, matrix: &Mat4) -> Self {
         Self {
             position: matrix.transform_vec3(&self.position),
             normal: matrix.transform_vec3(&self.normal).normalize(),
             uv: self.uv,
+            uv2: self.uv2,
+            color: self.color,
+            tangent: matrix.transform_direction(&self.tangent).normalize(),
+            bitangent: matrix.transform_direction(&self.bitangent).normalize(),
+        }
+    }
+}
+
+impl BoundingBox {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) * 0.5
+    }
+
+    // Boxes are closed, so points on the surface are inside
+    pub fn contains_point(&self, p: Vec3) -> bool {
+        p.x >= self.min.x && p.x <= self.max.x
+            && p.y >= self.min.y && p.y <= self.max.y
+            && p.z >= self.min.z && p.z <= self.max.z
+    }
+
+    // True when the boxes overlap or touch
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min.x <= other.max.x && other.min.x <= self.max.x
+            && self.min.y <= other.max.y && other.min.y <= self.max.y
+            && self.min.z <= other.max.z && other.min.z <= self.max.z
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        let size = self.max - self.min;
+        2.0 * (size.x * size.y + size.y * size.z + size.z * size.x)
+    }
+
+    // Smallest box containing both boxes
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            min: self.min.component_min(other.min),
+            max: self.max.component_max(other.max),
+        }
+    }
+
+    // Axis-aligned box enclosing this box after transformation
+    pub fn transform(&self, matrix: &Mat4) -> BoundingBox {
+        let mut result: Option<BoundingBox> = None;
+        for i in 0..8 {
+            let corner = Vec3::new(
+                if i & 1 == 0 { self.min.x } else { self.max.x },
+                if i & 2 == 0 { self.min.y } else { self.max.y },
+                if i & 4 == 0 { self.min.z } else { self.max.z },
+            );
+            let p = matrix.transform_vec3(&corner);
+            let point_box = BoundingBox::new(p, p);
+            result = Some(match result {
+                Some(bbox) => bbox.union(&point_box),
+                None => point_box,
+            });
         }
+        result.unwrap()
     }
 }
 
@@ -50,6 +165,14 @@ impl Face {
         Self {
             vertices,
             normal: Vec3::zero(), // Will be calculated later
+            color: None,
+        }
+    }
+
+    pub fn with_color(vertices: [usize; 3], color: Color) -> Self {
+        Self {
+            color: Some(color),
+            ..Self::new(vertices)
         }
     }
 
@@ -93,6 +216,12 @@ impl Mesh {
         self.faces.push(face);
     }
 
+    pub fn set_all_face_colors(&mut self, color: Color) {
+        for face in &mut self.faces {
+            face.color = Some(color);
+        }
+    }
+
     pub fn transform(&mut self, matrix: Mat4) {
         self.transform = matrix.multiply(&self.transform);
     }
@@ -103,6 +232,61 @@ impl Mesh {
             .collect()
     }
 
+    // Bakes the pending transform into the vertex positions and resets it.
+    // Vertex normals are regenerated from the faces afterwards.
+    pub fn apply_transform(&mut self) {
+        self.apply_transform_to_vertices();
+        self.generate_vertex_normals();
+    }
+
+    // Like apply_transform, but rewrites the vertices in place and keeps
+    // their normals (through the normal matrix) instead of regenerating them
+    pub fn apply_transform_to_vertices(&mut self) {
+        let matrix = self.transform;
+        let normal_matrix = matrix.normal_matrix();
+        for vertex in &mut self.vertices {
+            vertex.position = matrix.transform_vec3(&vertex.position);
+            vertex.normal = normal_matrix.transform_direction(&vertex.normal).normalize();
+            vertex.tangent = matrix.transform_direction(&vertex.tangent).normalize();
+            vertex.bitangent = matrix.transform_direction(&vertex.bitangent).normalize();
+        }
+        self.transform = Mat4::identity();
+
+        for face in &mut self.faces {
+            face.calculate_normal(&self.vertices);
+        }
+    }
+
+    pub fn transformed_clone(&self) -> Mesh {
+        let mut mesh = self.clone();
+        mesh.apply_transform();
+        mesh
+    }
+
+    // Combined mesh keeping `self`'s transform. `other` is appended as-is,
+    // so its own transform is ignored; bake it first if it matters.
+    pub fn merge(&self, other: &Mesh) -> Mesh {
+        let mut merged = self.clone();
+        merged.merge_in_place(other);
+        merged
+    }
+
+    pub fn merge_in_place(&mut self, other: &Mesh) {
+        let offset = self.vertices.len();
+        self.vertices.extend_from_slice(&other.vertices);
+        self.faces.extend(other.faces.iter().map(|face| Face {
+            vertices: face.vertices.map(|i| i + offset),
+            ..face.clone()
+        }));
+        self.generate_vertex_normals();
+    }
+
+    pub fn to_point_cloud(&self) -> Vec<Vec3> {
+        self.vertices.iter()
+            .map(|v| self.transform.transform_vec3(&v.position))
+            .collect()
+    }
+
     pub fn calculate_bounding_box(&self) -> BoundingBox {
         if self.vertices.is_empty() {
             return BoundingBox {
@@ -128,6 +312,67 @@ impl Mesh {
         BoundingBox { min, max }
     }
 
+    pub fn bounding_box_center(&self) -> Vec3 {
+        let bbox = self.calculate_bounding_box();
+        (bbox.min + bbox.max) * 0.5
+    }
+
+    // Area of every face, in the same order as `faces`
+    pub fn compute_face_areas(&self) -> Vec<f64> {
+        let transformed = self.get_transformed_vertices();
+        self.faces.iter()
+            .map(|face| {
+                let v0 = transformed[face.vertices[0]].position;
+                let v1 = transformed[face.vertices[1]].position;
+                let v2 = transformed[face.vertices[2]].position;
+                0.5 * (v1 - v0).cross(&(v2 - v0)).length()
+            })
+            .collect()
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        self.compute_face_areas().iter().sum()
+    }
+
+    // Named to pair with calculate_volume and calculate_bounding_box
+    pub fn calculate_surface_area(&self) -> f64 {
+        self.surface_area()
+    }
+
+    // Sum of the signed volumes of the tetrahedra spanned by the origin and
+    // each face. Only meaningful for closed meshes; negative when the faces
+    // wind inwards.
+    pub fn calculate_volume(&self) -> f64 {
+        let transformed = self.get_transformed_vertices();
+        self.faces.iter()
+            .map(|face| {
+                let [v0, v1, v2] = face.vertices.map(|i| transformed[i].position);
+                v0.dot(&v1.cross(&v2)) / 6.0
+            })
+            .sum()
+    }
+
+    // Area-weighted centroid of the surface
+    pub fn center_of_mass(&self) -> Vec3 {
+        let transformed = self.get_transformed_vertices();
+        let areas = self.compute_face_areas();
+        let total_area: f64 = areas.iter().sum();
+        if total_area == 0.0 {
+            return self.bounding_box_center();
+        }
+
+        let weighted_sum = self.faces.iter()
+            .zip(&areas)
+            .fold(Vec3::zero(), |sum, (face, &area)| {
+                let centroid = (transformed[face.vertices[0]].position
+                    + transformed[face.vertices[1]].position
+                    + transformed[face.vertices[2]].position) / 3.0;
+                sum + centroid * area
+            });
+
+        weighted_sum / total_area
+    }
+
     pub fn generate_vertex_normals(&mut self) {
         // Initialize normal accumulators
         let mut vertex_normals = HashMap::new();
@@ -138,7 +383,7 @@ impl Mesh {
             for &vertex_idx in &face.vertices {
                 vertex_normals
                     .entry(vertex_idx)
-                    .and_modify(|n: &mut Vec3| *n = *n + face.normal)
+                    .and_modify(|n: &mut Vec3| *n += face.normal)
                     .or_insert(face.normal);
 
                 *normal_counts
@@ -153,6 +398,266 @@ impl Mesh {
             self.vertices[vertex_idx].normal = (normal / count).normalize();
         }
     }
+
+    // Like generate_vertex_normals, but only averages faces whose normals are
+    // within `crease_angle_radians` of each other. Vertices on sharper edges
+    // are split so every side keeps its own normal.
+    pub fn smooth_normals_with_threshold(&mut self, crease_angle_radians: f64) {
+        let cos_threshold = crease_angle_radians.cos();
+
+        let mut incident_faces: Vec<Vec<usize>> = vec![Vec::new(); self.vertices.len()];
+        for (face_idx, face) in self.faces.iter().enumerate() {
+            for &vertex_idx in &face.vertices {
+                incident_faces[vertex_idx].push(face_idx);
+            }
+        }
+
+        // Normal groups already assigned to each original vertex
+        let mut groups: Vec<Vec<(Vec3, usize)>> = vec![Vec::new(); self.vertices.len()];
+        let mut new_faces = self.faces.clone();
+
+        for (face_idx, new_face) in new_faces.iter_mut().enumerate() {
+            let face_normal = self.faces[face_idx].normal;
+            for corner in 0..3 {
+                let vertex_idx = self.faces[face_idx].vertices[corner];
+                let normal = incident_faces[vertex_idx].iter()
+                    .map(|&other| self.faces[other].normal)
+                    .filter(|n| n.dot(&face_normal) >= cos_threshold - 1e-12)
+                    .fold(Vec3::zero(), |sum, n| sum + n)
+                    .normalize();
+
+                let existing = groups[vertex_idx].iter()
+                    .find(|(n, _)| (*n - normal).length() < 1e-9)
+                    .map(|&(_, idx)| idx);
+
+                let target = match existing {
+                    Some(idx) => idx,
+                    None => {
+                        // The first group reuses the original slot
+                        let idx = if groups[vertex_idx].is_empty() {
+                            vertex_idx
+                        } else {
+                            let copy = self.vertices[vertex_idx].clone();
+                            self.add_vertex(copy)
+                        };
+                        self.vertices[idx].normal = normal;
+                        groups[vertex_idx].push((normal, idx));
+                        idx
+                    }
+                };
+                new_face.vertices[corner] = target;
+            }
+        }
+
+        self.faces = new_faces;
+    }
+
+    // Copy of the mesh split into OBJ-style smoothing groups: faces meeting at
+    // more than `angle_threshold_deg` get their own vertices along the crease
+    pub fn compute_smooth_groups(&self, angle_threshold_deg: f64) -> Mesh {
+        let mut mesh = self.clone();
+        mesh.smooth_normals_with_threshold(angle_threshold_deg.to_radians());
+        mesh
+    }
+
+    // For meshes that use the same layout for both UV channels
+    pub fn copy_uv_to_uv2(&mut self) {
+        for vertex in &mut self.vertices {
+            vertex.uv2 = vertex.uv;
+        }
+    }
+
+    // Per-vertex tangents along +U and bitangents along +V, following the
+    // MikkTSpace convention: face tangents from the UV deltas are accumulated
+    // per vertex and Gram-Schmidt orthonormalised against the vertex normal.
+    // Needs vertex normals; faces with degenerate UVs are ignored.
+    pub fn compute_tangents_and_bitangents(&mut self) {
+        let mut tangents = vec![Vec3::zero(); self.vertices.len()];
+        let mut bitangents = vec![Vec3::zero(); self.vertices.len()];
+
+        for face in &self.faces {
+            let [a, b, c] = face.vertices.map(|i| &self.vertices[i]);
+            let (edge1, edge2) = (b.position - a.position, c.position - a.position);
+            let (duv1, duv2) = (b.uv - a.uv, c.uv - a.uv);
+            let det = duv1.x * duv2.y - duv2.x * duv1.y;
+            if det.abs() < 1e-12 {
+                continue;
+            }
+
+            let tangent = (edge1 * duv2.y - edge2 * duv1.y) / det;
+            let bitangent = (edge2 * duv1.x - edge1 * duv2.x) / det;
+            for &i in &face.vertices {
+                tangents[i] += tangent;
+                bitangents[i] += bitangent;
+            }
+        }
+
+        for (i, vertex) in self.vertices.iter_mut().enumerate() {
+            let n = vertex.normal;
+            let tangent = (tangents[i] - n * n.dot(&tangents[i])).normalize();
+            // Mirrored UVs flip the bitangent
+            let handedness = if n.cross(&tangent).dot(&bitangents[i]) < 0.0 { -1.0 } else { 1.0 };
+            vertex.tangent = tangent;
+            vertex.bitangent = n.cross(&tangent) * handedness;
+        }
+    }
+
+    // Every edge shared by one or more faces, as sorted (low, high) vertex
+    // index pairs
+    pub fn get_unique_edges(&self) -> Vec<(usize, usize)> {
+        let mut edges: Vec<(usize, usize)> = self.faces.iter()
+            .flat_map(|face| {
+                (0..3).map(move |corner| {
+                    let a = face.vertices[corner];
+                    let b = face.vertices[(corner + 1) % 3];
+                    (a.min(b), a.max(b))
+                })
+            })
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        edges.sort_unstable();
+        edges
+    }
+
+    pub fn reverse_face_winding(&mut self) {
+        for face in &mut self.faces {
+            face.vertices.swap(1, 2);
+            face.calculate_normal(&self.vertices);
+        }
+    }
+
+    // Negates face normals only; the winding (and so culling) is unchanged
+    pub fn flip_face_normals(&mut self) {
+        for face in &mut self.faces {
+            face.normal = -face.normal;
+        }
+    }
+
+    // Flips faces so that neighbours traverse every shared edge in opposite
+    // directions. The first face of each connected patch sets the orientation,
+    // so a consistently inside-out mesh stays inside-out.
+    pub fn ensure_consistent_winding(&mut self) {
+        let mut edge_faces: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for (face_idx, face) in self.faces.iter().enumerate() {
+            for corner in 0..3 {
+                let a = face.vertices[corner];
+                let b = face.vertices[(corner + 1) % 3];
+                edge_faces.entry((a.min(b), a.max(b))).or_default().push(face_idx);
+            }
+        }
+
+        // Whether the face walks from a to b along one of its edges
+        let has_directed_edge = |face: &Face, a: usize, b: usize| {
+            (0..3).any(|corner| face.vertices[corner] == a && face.vertices[(corner + 1) % 3] == b)
+        };
+
+        let mut visited = vec![false; self.faces.len()];
+        for seed in 0..self.faces.len() {
+            if visited[seed] {
+                continue;
+            }
+            visited[seed] = true;
+            let mut queue = std::collections::VecDeque::from([seed]);
+
+            while let Some(face_idx) = queue.pop_front() {
+                for corner in 0..3 {
+                    let a = self.faces[face_idx].vertices[corner];
+                    let b = self.faces[face_idx].vertices[(corner + 1) % 3];
+                    for &neighbor in &edge_faces[&(a.min(b), a.max(b))] {
+                        if visited[neighbor] {
+                            continue;
+                        }
+                        visited[neighbor] = true;
+                        if has_directed_edge(&self.faces[neighbor], a, b) {
+                            self.faces[neighbor].vertices.swap(1, 2);
+                            self.faces[neighbor].calculate_normal(&self.vertices);
+                        }
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+        }
+    }
+}
+
+const DEGENERATE_AREA_EPSILON: f64 = 1e-12;
+const UNIT_NORMAL_TOLERANCE: f64 = 1e-6;
+
+fn has_nan(v: &Vec3) -> bool {
+    v.x.is_nan() || v.y.is_nan() || v.z.is_nan()
+}
+
+// Validation and repair
+impl Mesh {
+    // Checks the raw vertex and face data, reporting every problem found
+    pub fn validate(&self) -> Result<(), Vec<MeshError>> {
+        let mut errors = Vec::new();
+
+        for (face_idx, face) in self.faces.iter().enumerate() {
+            let invalid: Vec<usize> = face.vertices.iter()
+                .copied()
+                .filter(|&vertex_idx| vertex_idx >= self.vertices.len())
+                .collect();
+            if !invalid.is_empty() {
+                errors.extend(invalid.into_iter().map(|vertex_idx| MeshError::InvalidFaceIndex { face_idx, vertex_idx }));
+            } else if self.face_area(face) < DEGENERATE_AREA_EPSILON {
+                errors.push(MeshError::DegenerateFace { face_idx });
+            }
+        }
+
+        for (vertex_idx, vertex) in self.vertices.iter().enumerate() {
+            if has_nan(&vertex.position) {
+                errors.push(MeshError::NanVertexPosition { vertex_idx });
+            }
+            if has_nan(&vertex.normal) {
+                errors.push(MeshError::NanNormal { vertex_idx });
+            } else if (vertex.normal.length() - 1.0).abs() > UNIT_NORMAL_TOLERANCE {
+                errors.push(MeshError::NonUnitNormal { vertex_idx });
+            }
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    // Fixes what validate reports: NaN positions become the origin, faces with
+    // missing vertices or zero area are dropped and bad normals regenerated
+    pub fn repair(&mut self) {
+        for vertex in &mut self.vertices {
+            if has_nan(&vertex.position) {
+                vertex.position = Vec3::zero();
+            }
+        }
+
+        let vertex_count = self.vertices.len();
+        let faces = std::mem::take(&mut self.faces);
+        self.faces = faces.into_iter()
+            .filter(|face| {
+                face.vertices.iter().all(|&i| i < vertex_count)
+                    && self.face_area(face) >= DEGENERATE_AREA_EPSILON
+            })
+            .collect();
+        for face in &mut self.faces {
+            face.calculate_normal(&self.vertices);
+        }
+
+        let bad_normal = |n: &Vec3| has_nan(n) || (n.length() - 1.0).abs() > UNIT_NORMAL_TOLERANCE;
+        if self.vertices.iter().any(|v| bad_normal(&v.normal)) {
+            self.generate_vertex_normals();
+            // Vertices outside any face keep their normal if it can be salvaged
+            for vertex in &mut self.vertices {
+                if bad_normal(&vertex.normal) {
+                    let normal = vertex.normal.normalize();
+                    vertex.normal = if bad_normal(&normal) { Vec3::new(0.0, 1.0, 0.0) } else { normal };
+                }
+            }
+        }
+    }
+
+    fn face_area(&self, face: &Face) -> f64 {
+        let [v0, v1, v2] = face.vertices.map(|i| self.vertices[i].position);
+        0.5 * (v1 - v0).cross(&(v2 - v0)).length()
+    }
 }
 
 // Helper function to create primitive shapes
@@ -229,7 +734,836 @@ impl Mesh {
         // Calculate proper vertex normals
         mesh.generate_vertex_normals();
         mesh
-    }}
+    }
+
+    // Extrudes a closed 2D profile in the XY plane from z = 0 to z = height.
+    // Caps are fan-triangulated, so the profile should be convex when capped.
+    pub fn extrude_profile(profile: &[Vec2], height: f64, close_caps: bool) -> Self {
+        let n = profile.len();
+        if n < 3 {
+            return Mesh::new();
+        }
+
+        // Walk the profile counter-clockwise so that faces wind outward
+        let signed_area: f64 = (0..n)
+            .map(|i| {
+                let (a, b) = (profile[i], profile[(i + 1) % n]);
+                a.x * b.y - b.x * a.y
+            })
+            .sum();
+        let ordered: Vec<Vec2> = if signed_area < 0.0 {
+            profile.iter().rev().copied().collect()
+        } else {
+            profile.to_vec()
+        };
+
+        let side_faces = 2 * n;
+        let cap_faces = if close_caps { 2 * (n - 2) } else { 0 };
+        let mut mesh = Mesh::with_capacity(2 * n, side_faces + cap_faces);
+
+        // Bottom ring is 0..n, top ring is n..2n
+        for (z, v) in [(0.0, 0.0), (height, 1.0)] {
+            for (i, p) in ordered.iter().enumerate() {
+                mesh.add_vertex(Vertex::new(
+                    Vec3::new(p.x, p.y, z),
+                    Vec3::zero(),
+                    Vec2::new(i as f64 / n as f64, v),
+                ));
+            }
+        }
+
+        for i in 0..n {
+            let next = (i + 1) % n;
+            mesh.add_face([i, next, n + next]);
+            mesh.add_face([i, n + next, n + i]);
+        }
+
+        if close_caps {
+            for i in 1..n - 1 {
+                mesh.add_face([n, n + i, n + i + 1]);
+                mesh.add_face([0, i + 1, i]);
+            }
+        }
+
+        mesh.generate_vertex_normals();
+        mesh
+    }
+
+    // Grid terrain over the XZ plane from row-major height samples, with Y up
+    pub fn from_heightmap(heights: &[f64], width: usize, height: usize, cell_size: f64, height_scale: f64) -> Self {
+        if width < 2 || height < 2 || heights.len() < width * height {
+            return Mesh::new();
+        }
+
+        let mut mesh = Mesh::with_capacity(width * height, (width - 1) * (height - 1) * 2);
+        for row in 0..height {
+            for col in 0..width {
+                mesh.add_vertex(Vertex::new(
+                    Vec3::new(
+                        col as f64 * cell_size,
+                        heights[row * width + col] * height_scale,
+                        row as f64 * cell_size,
+                    ),
+                    Vec3::zero(),
+                    Vec2::new(col as f64 / (width - 1) as f64, row as f64 / (height - 1) as f64),
+                ));
+            }
+        }
+
+        for row in 0..height - 1 {
+            for col in 0..width - 1 {
+                let a = row * width + col;
+                let b = a + 1;
+                let c = a + width + 1;
+                let d = a + width;
+                // Wound so that flat terrain faces +Y
+                mesh.add_face([a, d, c]);
+                mesh.add_face([a, c, b]);
+            }
+        }
+
+        mesh.generate_vertex_normals();
+        mesh
+    }
+}
+
+// Working face used while building a convex hull
+struct HullFace {
+    vertices: [usize; 3],
+    normal: Vec3,
+    offset: f64,
+    outside: Vec<usize>,
+    alive: bool,
+}
+
+impl HullFace {
+    fn new(vertices: [usize; 3], points: &[Vec3]) -> Self {
+        let [a, b, c] = vertices;
+        let normal = (points[b] - points[a]).cross(&(points[c] - points[a])).normalize();
+        Self {
+            vertices,
+            normal,
+            offset: normal.dot(&points[a]),
+            outside: Vec::new(),
+            alive: true,
+        }
+    }
+
+    fn distance(&self, p: &Vec3) -> f64 {
+        self.normal.dot(p) - self.offset
+    }
+}
+
+// Convex hull utilities
+impl Mesh {
+    // Builds the convex hull of the mesh vertices with the quickhull algorithm.
+    // Returns an empty mesh when all vertices are coplanar.
+    pub fn convex_hull(&self) -> Mesh {
+        let points: Vec<Vec3> = self.vertices.iter().map(|v| v.position).collect();
+        let mut hull = Mesh::new();
+        hull.transform = self.transform;
+
+        let Some(initial) = Self::initial_tetrahedron(&points) else {
+            return hull;
+        };
+        let eps = Self::hull_epsilon(&points);
+
+        // Orient the tetrahedron faces so their normals point away from its centroid
+        let centroid = initial.iter().fold(Vec3::zero(), |sum, &i| sum + points[i]) / 4.0;
+        let [a, b, c, d] = initial;
+        let mut faces: Vec<HullFace> = [[a, b, c], [a, c, d], [a, d, b], [b, d, c]].iter()
+            .map(|&tri| {
+                let face = HullFace::new(tri, &points);
+                if face.distance(&centroid) > 0.0 {
+                    HullFace::new([tri[0], tri[2], tri[1]], &points)
+                } else {
+                    face
+                }
+            })
+            .collect();
+
+        let candidates: Vec<usize> = (0..points.len()).filter(|i| !initial.contains(i)).collect();
+        Self::assign_outside_points(&mut faces, 0, &candidates, &points, eps);
+
+        while let Some(face_idx) = faces.iter().position(|f| f.alive && !f.outside.is_empty()) {
+            // Farthest outside point of this face becomes the new hull vertex
+            let eye = *faces[face_idx].outside.iter()
+                .max_by(|&&p, &&q| {
+                    let dp = faces[face_idx].distance(&points[p]);
+                    let dq = faces[face_idx].distance(&points[q]);
+                    dp.total_cmp(&dq)
+                })
+                .unwrap();
+
+            let visible: Vec<usize> = (0..faces.len())
+                .filter(|&i| faces[i].alive && faces[i].distance(&points[eye]) > eps)
+                .collect();
+
+            // Horizon edges are edges of visible faces whose twin is not visible
+            let mut visible_edges = HashSet::new();
+            for &i in &visible {
+                let [a, b, c] = faces[i].vertices;
+                visible_edges.extend([(a, b), (b, c), (c, a)]);
+            }
+            let horizon: Vec<(usize, usize)> = visible_edges.iter()
+                .filter(|&&(a, b)| !visible_edges.contains(&(b, a)))
+                .copied()
+                .collect();
+
+            let mut orphans = Vec::new();
+            for &i in &visible {
+                faces[i].alive = false;
+                orphans.append(&mut faces[i].outside);
+            }
+            orphans.retain(|&p| p != eye);
+
+            let first_new = faces.len();
+            for (a, b) in horizon {
+                faces.push(HullFace::new([a, b, eye], &points));
+            }
+            Self::assign_outside_points(&mut faces, first_new, &orphans, &points, eps);
+        }
+
+        // Compact the used points into the hull's vertex list
+        let mut remap = HashMap::new();
+        for face in faces.iter().filter(|f| f.alive) {
+            let indices = face.vertices.map(|p| {
+                *remap.entry(p).or_insert_with(|| {
+                    let source = &self.vertices[p];
+                    hull.add_vertex(Vertex::new(source.position, source.normal, source.uv))
+                })
+            });
+            hull.add_face(indices);
+        }
+        hull.generate_vertex_normals();
+        hull
+    }
+
+    // True when no vertex lies in front of any face plane
+    pub fn is_convex(&self) -> bool {
+        let points: Vec<Vec3> = self.vertices.iter().map(|v| v.position).collect();
+        let eps = Self::hull_epsilon(&points);
+
+        self.faces.iter().all(|face| {
+            let plane = HullFace::new(face.vertices, &points);
+            plane.normal.length() == 0.0 || points.iter().all(|p| plane.distance(p) <= eps)
+        })
+    }
+
+    fn hull_epsilon(points: &[Vec3]) -> f64 {
+        let extent = points.iter().fold(0.0_f64, |m, p| m.max(p.x.abs()).max(p.y.abs()).max(p.z.abs()));
+        extent.max(1.0) * 1e-9
+    }
+
+    fn initial_tetrahedron(points: &[Vec3]) -> Option<[usize; 4]> {
+        if points.len() < 4 {
+            return None;
+        }
+        let eps = Self::hull_epsilon(points);
+
+        // Start from the two most distant axis-extreme points
+        let mut extremes = Vec::new();
+        for axis in 0..3 {
+            let coord = |p: &Vec3| [p.x, p.y, p.z][axis];
+            let (min, _) = points.iter().enumerate().min_by(|a, b| coord(a.1).total_cmp(&coord(b.1)))?;
+            let (max, _) = points.iter().enumerate().max_by(|a, b| coord(a.1).total_cmp(&coord(b.1)))?;
+            extremes.push(min);
+            extremes.push(max);
+        }
+        let mut best = (0.0, 0, 0);
+        for &i in &extremes {
+            for &j in &extremes {
+                let dist = (points[i] - points[j]).length();
+                if dist > best.0 {
+                    best = (dist, i, j);
+                }
+            }
+        }
+        let (_, a, b) = best;
+        if best.0 <= eps {
+            return None;
+        }
+
+        let line = (points[b] - points[a]).normalize();
+        let c = (0..points.len()).max_by(|&i, &j| {
+            let di = (points[i] - points[a]).cross(&line).length();
+            let dj = (points[j] - points[a]).cross(&line).length();
+            di.total_cmp(&dj)
+        })?;
+        if (points[c] - points[a]).cross(&line).length() <= eps {
+            return None;
+        }
+
+        let plane = HullFace::new([a, b, c], points);
+        let d = (0..points.len()).max_by(|&i, &j| {
+            plane.distance(&points[i]).abs().total_cmp(&plane.distance(&points[j]).abs())
+        })?;
+        if plane.distance(&points[d]).abs() <= eps {
+            return None;
+        }
+
+        Some([a, b, c, d])
+    }
+
+    fn assign_outside_points(faces: &mut [HullFace], first_face: usize, candidates: &[usize], points: &[Vec3], eps: f64) {
+        for &p in candidates {
+            if let Some(face) = faces[first_face..].iter_mut()
+                .find(|f| f.alive && f.distance(&points[p]) > eps)
+            {
+                face.outside.push(p);
+            }
+        }
+    }
+}
+
+impl LodGroup {
+    pub fn new() -> Self {
+        Self { lods: Vec::new() }
+    }
+
+    pub fn add_lod(&mut self, max_distance: f64, mesh: Mesh) {
+        self.lods.push((max_distance, mesh));
+        self.lods.sort_by(|a, b| a.0.total_cmp(&b.0));
+    }
+
+    // Mesh for the given viewing distance; the coarsest level is used beyond
+    // the last threshold
+    pub fn select(&self, distance: f64) -> Option<&Mesh> {
+        self.lods.iter()
+            .find(|(max_distance, _)| distance <= *max_distance)
+            .or(self.lods.last())
+            .map(|(_, mesh)| mesh)
+    }
+}
+
+type Quadric = [[f64; 4]; 4];
+
+fn quadric_add(a: &Quadric, b: &Quadric) -> Quadric {
+    let mut result = [[0.0; 4]; 4];
+    for (i, row) in result.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = a[i][j] + b[i][j];
+        }
+    }
+    result
+}
+
+fn quadric_error(q: &Quadric, p: &Vec3) -> f64 {
+    let v = [p.x, p.y, p.z, 1.0];
+    let mut error = 0.0;
+    for i in 0..4 {
+        for j in 0..4 {
+            error += v[i] * q[i][j] * v[j];
+        }
+    }
+    error
+}
+
+// Candidate edge collapse, ordered so the cheapest pops first from a max-heap
+struct Collapse {
+    cost: f64,
+    keep: usize,
+    remove: usize,
+    target: Vec3,
+    stamps: (u32, u32),
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+// Level-of-detail generation
+impl Mesh {
+    // Simplifies the mesh by collapsing the edges with the lowest quadric error
+    // until at most `target_face_count` faces remain (or no valid collapse is left)
+    pub fn compute_lod_mesh(&self, target_face_count: usize) -> Mesh {
+        let mut positions: Vec<Vec3> = self.vertices.iter().map(|v| v.position).collect();
+        let mut faces: Vec<[usize; 3]> = self.faces.iter().map(|f| f.vertices).collect();
+        let mut face_alive = vec![true; faces.len()];
+        let mut alive_faces = faces.len();
+
+        let mut vertex_faces: Vec<Vec<usize>> = vec![Vec::new(); positions.len()];
+        for (face_idx, face) in faces.iter().enumerate() {
+            for &v in face {
+                vertex_faces[v].push(face_idx);
+            }
+        }
+
+        // Sum of the squared distances to every adjacent face plane
+        let mut quadrics: Vec<Quadric> = vec![[[0.0; 4]; 4]; positions.len()];
+        for face in &faces {
+            let [a, b, c] = face.map(|i| positions[i]);
+            let n = (b - a).cross(&(c - a)).normalize();
+            let plane = [n.x, n.y, n.z, -n.dot(&a)];
+            for &v in face {
+                for i in 0..4 {
+                    for j in 0..4 {
+                        quadrics[v][i][j] += plane[i] * plane[j];
+                    }
+                }
+            }
+        }
+
+        let mut stamps = vec![0u32; positions.len()];
+        let mut removed = vec![false; positions.len()];
+        let mut heap = BinaryHeap::new();
+
+        let evaluate = |keep: usize, remove: usize, positions: &[Vec3], quadrics: &[Quadric], stamps: &[u32]| {
+            let q = quadric_add(&quadrics[keep], &quadrics[remove]);
+            let (a, b) = (positions[keep], positions[remove]);
+            [a, b, (a + b) * 0.5].into_iter()
+                .map(|target| Collapse {
+                    cost: quadric_error(&q, &target),
+                    keep,
+                    remove,
+                    target,
+                    stamps: (stamps[keep], stamps[remove]),
+                })
+                .min_by(|x, y| x.cost.total_cmp(&y.cost))
+                .unwrap()
+        };
+
+        let mut edges = HashSet::new();
+        for face in &faces {
+            for k in 0..3 {
+                let (a, b) = (face[k], face[(k + 1) % 3]);
+                if edges.insert((a.min(b), a.max(b))) {
+                    heap.push(evaluate(a.min(b), a.max(b), &positions, &quadrics, &stamps));
+                }
+            }
+        }
+
+        while alive_faces > target_face_count {
+            let Some(collapse) = heap.pop() else { break };
+            let (keep, remove) = (collapse.keep, collapse.remove);
+            if removed[keep] || removed[remove] || collapse.stamps != (stamps[keep], stamps[remove]) {
+                continue;
+            }
+
+            // Reject collapses that would flip a surviving face
+            let flips = vertex_faces[keep].iter().chain(&vertex_faces[remove])
+                .filter(|&&f| face_alive[f] && !(faces[f].contains(&keep) && faces[f].contains(&remove)))
+                .any(|&f| {
+                    let before = faces[f].map(|i| positions[i]);
+                    let after = faces[f].map(|i| if i == keep || i == remove { collapse.target } else { positions[i] });
+                    let n_before = (before[1] - before[0]).cross(&(before[2] - before[0]));
+                    let n_after = (after[1] - after[0]).cross(&(after[2] - after[0]));
+                    n_before.dot(&n_after) <= 0.0
+                });
+            if flips {
+                continue;
+            }
+
+            positions[keep] = collapse.target;
+            quadrics[keep] = quadric_add(&quadrics[keep], &quadrics[remove]);
+            removed[remove] = true;
+            stamps[keep] += 1;
+
+            let moved = std::mem::take(&mut vertex_faces[remove]);
+            for f in moved {
+                if !face_alive[f] {
+                    continue;
+                }
+                if faces[f].contains(&keep) {
+                    face_alive[f] = false;
+                    alive_faces -= 1;
+                } else {
+                    for v in faces[f].iter_mut().filter(|v| **v == remove) {
+                        *v = keep;
+                    }
+                    vertex_faces[keep].push(f);
+                }
+            }
+            vertex_faces[keep].retain(|&f| face_alive[f]);
+
+            let neighbours: HashSet<usize> = vertex_faces[keep].iter()
+                .flat_map(|&f| faces[f])
+                .filter(|&v| v != keep)
+                .collect();
+            for v in neighbours {
+                let (a, b) = (keep.min(v), keep.max(v));
+                heap.push(evaluate(a, b, &positions, &quadrics, &stamps));
+            }
+        }
+
+        // Compact the surviving vertices
+        let mut lod = Mesh::new();
+        lod.transform = self.transform;
+        let mut remap = HashMap::new();
+        for (face_idx, face) in faces.iter().enumerate() {
+            if !face_alive[face_idx] {
+                continue;
+            }
+            let indices = face.map(|v| {
+                *remap.entry(v).or_insert_with(|| {
+                    let source = &self.vertices[v];
+                    lod.add_vertex(Vertex::new(positions[v], source.normal, source.uv))
+                })
+            });
+            lod.add_face(indices);
+        }
+        lod.generate_vertex_normals();
+        lod
+    }
+}
+
+// Loop subdivision
+impl Mesh {
+    pub const MAX_SUBDIVISION_LEVELS: u32 = 5;
+
+    // Each level splits every triangle into four; levels are clamped to
+    // [0, MAX_SUBDIVISION_LEVELS] since the face count grows by 4^levels
+    pub fn subdivide(&self, levels: u32) -> Mesh {
+        let mut mesh = self.clone();
+        for _ in 0..levels.min(Self::MAX_SUBDIVISION_LEVELS) {
+            mesh = mesh.subdivide_once();
+        }
+        mesh
+    }
+
+    fn subdivide_once(&self) -> Mesh {
+        // Vertices opposite each edge; boundary edges have only one
+        let mut edge_opposites: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for face in &self.faces {
+            for k in 0..3 {
+                let (a, b) = (face.vertices[k], face.vertices[(k + 1) % 3]);
+                edge_opposites.entry((a.min(b), a.max(b))).or_default().push(face.vertices[(k + 2) % 3]);
+            }
+        }
+
+        let mut neighbours: Vec<HashSet<usize>> = vec![HashSet::new(); self.vertices.len()];
+        let mut boundary_neighbours: Vec<Vec<usize>> = vec![Vec::new(); self.vertices.len()];
+        for (&(a, b), opposites) in &edge_opposites {
+            neighbours[a].insert(b);
+            neighbours[b].insert(a);
+            if opposites.len() == 1 {
+                boundary_neighbours[a].push(b);
+                boundary_neighbours[b].push(a);
+            }
+        }
+
+        let mut result = Mesh::with_capacity(self.vertices.len() + edge_opposites.len(), self.faces.len() * 4);
+        result.transform = self.transform;
+
+        // Even vertices: the originals, relaxed towards their neighbours
+        for (i, vertex) in self.vertices.iter().enumerate() {
+            let mut even = vertex.clone();
+            if boundary_neighbours[i].len() == 2 {
+                let (a, b) = (boundary_neighbours[i][0], boundary_neighbours[i][1]);
+                even.position = vertex.position * 0.75
+                    + (self.vertices[a].position + self.vertices[b].position) * 0.125;
+            } else if boundary_neighbours[i].is_empty() && !neighbours[i].is_empty() {
+                let n = neighbours[i].len();
+                let beta = if n == 3 { 3.0 / 16.0 } else { 3.0 / (8.0 * n as f64) };
+                let sum = neighbours[i].iter()
+                    .fold(Vec3::zero(), |acc, &j| acc + self.vertices[j].position);
+                even.position = vertex.position * (1.0 - n as f64 * beta) + sum * beta;
+            }
+            result.add_vertex(even);
+        }
+
+        // Odd vertices: one per edge
+        let mut edge_vertex = HashMap::with_capacity(edge_opposites.len());
+        let mut edges: Vec<_> = edge_opposites.iter().collect();
+        edges.sort_unstable_by_key(|(edge, _)| **edge);
+        for (&(a, b), opposites) in edges {
+            let (va, vb) = (&self.vertices[a], &self.vertices[b]);
+            let position = if let [c, d] = opposites[..] {
+                (va.position + vb.position) * 0.375
+                    + (self.vertices[c].position + self.vertices[d].position) * 0.125
+            } else {
+                (va.position + vb.position) * 0.5
+            };
+            let mut odd = Vertex::new(position, va.normal + vb.normal, (va.uv + vb.uv) * 0.5)
+                .with_color(Color::interpolate([va.color, vb.color, va.color], [0.5, 0.5, 0.0]));
+            odd.uv2 = (va.uv2 + vb.uv2) * 0.5;
+            edge_vertex.insert((a, b), result.add_vertex(odd));
+        }
+
+        for face in &self.faces {
+            let [a, b, c] = face.vertices;
+            let mid = |x: usize, y: usize| edge_vertex[&(x.min(y), x.max(y))];
+            let (ab, bc, ca) = (mid(a, b), mid(b, c), mid(c, a));
+            for vertices in [[a, ab, ca], [ab, b, bc], [ca, bc, c], [ab, bc, ca]] {
+                result.add_face(vertices);
+                result.faces.last_mut().unwrap().color = face.color;
+            }
+        }
+
+        result.generate_vertex_normals();
+        result
+    }
+}
+
+// Bounding volume hierarchy over a mesh's faces, for ray casts
+#[derive(Debug, Clone)]
+pub struct Bvh {
+    root: Option<BvhNode>,
+    // Face indices reordered so every leaf covers a contiguous range
+    face_order: Vec<usize>,
+    triangles: Vec<[Vec3; 3]>,
+}
+
+#[derive(Debug, Clone)]
+pub enum BvhNode {
+    Interior { bounds: BoundingBox, left: Box<BvhNode>, right: Box<BvhNode> },
+    // Range into the face_order array; Bvh::leaf_faces resolves it to face indices
+    Leaf { bounds: BoundingBox, faces: Range<usize> },
+}
+
+// Closest ray-triangle intersection. `barycentric` holds the weights of the
+// face's three vertices at the hit point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HitRecord {
+    pub t: f64,
+    pub face_index: usize,
+    pub barycentric: Vec3,
+}
+
+impl BvhNode {
+    pub fn bounds(&self) -> &BoundingBox {
+        match self {
+            BvhNode::Interior { bounds, .. } | BvhNode::Leaf { bounds, .. } => bounds,
+        }
+    }
+}
+
+impl Bvh {
+    const MAX_LEAF_FACES: usize = 4;
+    const SAH_BINS: usize = 12;
+
+    // Built over the mesh's transformed vertices, like calculate_bounding_box,
+    // splitting each node where the surface area heuristic is cheapest
+    pub fn build(mesh: &Mesh) -> Bvh {
+        let vertices = mesh.get_transformed_vertices();
+        let triangles: Vec<[Vec3; 3]> = mesh.faces.iter()
+            .map(|face| face.vertices.map(|i| vertices[i].position))
+            .collect();
+
+        let mut bvh = Bvh {
+            root: None,
+            face_order: (0..triangles.len()).collect(),
+            triangles,
+        };
+        if !bvh.triangles.is_empty() {
+            bvh.root = Some(bvh.build_node(0..bvh.face_order.len()));
+        }
+        bvh
+    }
+
+    pub fn root(&self) -> Option<&BvhNode> {
+        self.root.as_ref()
+    }
+
+    // Face indices of a leaf's `faces` range
+    pub fn leaf_faces(&self, faces: Range<usize>) -> &[usize] {
+        &self.face_order[faces]
+    }
+
+    fn triangle_bounds(&self, face: usize) -> BoundingBox {
+        let [a, b, c] = self.triangles[face];
+        BoundingBox::new(a.component_min(b).component_min(c), a.component_max(b).component_max(c))
+    }
+
+    fn centroid(&self, face: usize) -> Vec3 {
+        let [a, b, c] = self.triangles[face];
+        (a + b + c) / 3.0
+    }
+
+    // SAH bin of the face's centroid within [lo, hi] along the axis
+    fn centroid_bin(&self, face: usize, axis: usize, lo: f64, hi: f64) -> usize {
+        let offset = (axis_value(self.centroid(face), axis) - lo) / (hi - lo);
+        ((offset * Self::SAH_BINS as f64) as usize).min(Self::SAH_BINS - 1)
+    }
+
+    fn build_node(&mut self, range: Range<usize>) -> BvhNode {
+        let faces = &self.face_order[range.clone()];
+        let bounds = faces.iter()
+            .map(|&f| self.triangle_bounds(f))
+            .reduce(|a, b| a.union(&b))
+            .unwrap();
+        if faces.len() <= Self::MAX_LEAF_FACES {
+            return BvhNode::Leaf { bounds, faces: range };
+        }
+
+        let centroids = faces.iter()
+            .map(|&f| { let c = self.centroid(f); BoundingBox::new(c, c) })
+            .reduce(|a, b| a.union(&b))
+            .unwrap();
+
+        // Bin the centroids along each axis and keep the cheapest split plane
+        let mut best: Option<(f64, usize, usize)> = None;
+        for axis in 0..3 {
+            let (lo, hi) = (axis_value(centroids.min, axis), axis_value(centroids.max, axis));
+            if hi - lo <= 0.0 {
+                continue;
+            }
+            let mut bins: Vec<(usize, Option<BoundingBox>)> = vec![(0, None); Self::SAH_BINS];
+            for &f in faces {
+                let bin = &mut bins[self.centroid_bin(f, axis, lo, hi)];
+                let face_bounds = self.triangle_bounds(f);
+                bin.0 += 1;
+                bin.1 = Some(match bin.1.take() {
+                    Some(b) => b.union(&face_bounds),
+                    None => face_bounds,
+                });
+            }
+
+            for split in 1..Self::SAH_BINS {
+                let side_cost = |bins: &[(usize, Option<BoundingBox>)]| {
+                    let count: usize = bins.iter().map(|b| b.0).sum();
+                    let area = bins.iter()
+                        .filter_map(|b| b.1.clone())
+                        .reduce(|a, b| a.union(&b))
+                        .map_or(0.0, |b| b.surface_area());
+                    count as f64 * area
+                };
+                let cost = side_cost(&bins[..split]) + side_cost(&bins[split..]);
+                if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                    best = Some((cost, axis, split));
+                }
+            }
+        }
+
+        // Splitting must beat testing every face of this node
+        let leaf_cost = faces.len() as f64 * bounds.surface_area();
+        let Some((_, axis, split)) = best.filter(|&(cost, _, _)| cost < leaf_cost) else {
+            return BvhNode::Leaf { bounds, faces: range };
+        };
+
+        let (lo, hi) = (axis_value(centroids.min, axis), axis_value(centroids.max, axis));
+        let (left, right): (Vec<usize>, Vec<usize>) = faces.iter()
+            .partition(|&&f| self.centroid_bin(f, axis, lo, hi) < split);
+        let mid = range.start + left.len();
+        self.face_order[range.start..mid].copy_from_slice(&left);
+        self.face_order[mid..range.end].copy_from_slice(&right);
+
+        BvhNode::Interior {
+            bounds,
+            left: Box::new(self.build_node(range.start..mid)),
+            right: Box::new(self.build_node(mid..range.end)),
+        }
+    }
+
+    // Nearest face hit by the ray, from either side. `ray_dir` need not be
+    // normalized; t is measured in multiples of it.
+    pub fn intersect(&self, ray_origin: Vec3, ray_dir: Vec3) -> Option<HitRecord> {
+        let inv_dir = Vec3::new(1.0 / ray_dir.x, 1.0 / ray_dir.y, 1.0 / ray_dir.z);
+        let mut closest: Option<HitRecord> = None;
+        let mut stack: Vec<&BvhNode> = self.root.iter().collect();
+
+        while let Some(node) = stack.pop() {
+            let t_max = closest.map_or(f64::INFINITY, |hit| hit.t);
+            if ray_box_entry(node.bounds(), ray_origin, inv_dir, t_max).is_none() {
+                continue;
+            }
+            match node {
+                BvhNode::Leaf { faces, .. } => {
+                    for &face_index in &self.face_order[faces.clone()] {
+                        let Some((t, barycentric)) = ray_triangle(self.triangles[face_index], ray_origin, ray_dir) else {
+                            continue;
+                        };
+                        if closest.is_none_or(|hit| t < hit.t) {
+                            closest = Some(HitRecord { t, face_index, barycentric });
+                        }
+                    }
+                }
+                BvhNode::Interior { left, right, .. } => {
+                    // Visit the nearer child first so it can prune the other
+                    let entry = |n: &BvhNode| ray_box_entry(n.bounds(), ray_origin, inv_dir, f64::INFINITY);
+                    match (entry(left), entry(right)) {
+                        (Some(l), Some(r)) if r < l => {
+                            stack.push(left);
+                            stack.push(right);
+                        }
+                        _ => {
+                            stack.push(right);
+                            stack.push(left);
+                        }
+                    }
+                }
+            }
+        }
+        closest
+    }
+}
+
+fn axis_value(v: Vec3, axis: usize) -> f64 {
+    match axis {
+        0 => v.x,
+        1 => v.y,
+        _ => v.z,
+    }
+}
+
+// Slab test; parameter where the ray enters the box, if it does before t_max
+fn ray_box_entry(bounds: &BoundingBox, origin: Vec3, inv_dir: Vec3, t_max: f64) -> Option<f64> {
+    let mut t_enter: f64 = 0.0;
+    let mut t_exit = t_max;
+    for axis in 0..3 {
+        let inv = axis_value(inv_dir, axis);
+        let o = axis_value(origin, axis);
+        let mut t0 = (axis_value(bounds.min, axis) - o) * inv;
+        let mut t1 = (axis_value(bounds.max, axis) - o) * inv;
+        if inv < 0.0 {
+            std::mem::swap(&mut t0, &mut t1);
+        }
+        // NaN from 0 * inf (ray in the slab's plane) leaves the bounds alone
+        if t0 > t_enter {
+            t_enter = t0;
+        }
+        if t1 < t_exit {
+            t_exit = t1;
+        }
+        if t_enter > t_exit {
+            return None;
+        }
+    }
+    Some(t_enter)
+}
+
+// Moller-Trumbore; returns t and the barycentric weights of the corners
+fn ray_triangle(triangle: [Vec3; 3], origin: Vec3, dir: Vec3) -> Option<(f64, Vec3)> {
+    const EPSILON: f64 = 1e-12;
+    let [a, b, c] = triangle;
+    let (edge1, edge2) = (b - a, c - a);
+    let p = dir.cross(&edge2);
+    let det = edge1.dot(&p);
+    if det.abs() < EPSILON {
+        return None;
+    }
+
+    let inv_det = 1.0 / det;
+    let s = origin - a;
+    let u = s.dot(&p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(&edge1);
+    let v = dir.dot(&q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = edge2.dot(&q) * inv_det;
+    (t > EPSILON).then(|| (t, Vec3::new(1.0 - u - v, u, v)))
+}
 
 #[cfg(test)]
 mod tests {
@@ -287,4 +1621,454 @@ mod tests {
         assert!((bbox.min.z + 1.0).abs() < 1e-10);
         assert!((bbox.max.z - 1.0).abs() < 1e-10);
     }
+
+    // Flat mesh made of unit squares in the XY plane, each split into two triangles
+    fn squares_mesh(cells: &[(f64, f64)]) -> Mesh {
+        let mut mesh = Mesh::new();
+        let normal = Vec3::new(0.0, 0.0, 1.0);
+        for &(x, y) in cells {
+            let a = mesh.add_vertex(Vertex::new(Vec3::new(x, y, 0.0), normal, Vec2::zero()));
+            let b = mesh.add_vertex(Vertex::new(Vec3::new(x + 1.0, y, 0.0), normal, Vec2::zero()));
+            let c = mesh.add_vertex(Vertex::new(Vec3::new(x + 1.0, y + 1.0, 0.0), normal, Vec2::zero()));
+            let d = mesh.add_vertex(Vertex::new(Vec3::new(x, y + 1.0, 0.0), normal, Vec2::zero()));
+            mesh.add_face([a, b, c]);
+            mesh.add_face([a, c, d]);
+        }
+        mesh
+    }
+
+    #[test]
+    fn test_bounding_box_queries() {
+        let unit = BoundingBox::new(Vec3::zero(), Vec3::new(1.0, 1.0, 1.0));
+        assert_eq!(unit.center(), Vec3::new(0.5, 0.5, 0.5));
+        assert_eq!(unit.half_extents(), Vec3::new(0.5, 0.5, 0.5));
+        assert!(unit.contains_point(Vec3::new(1.0, 0.5, 0.0)));
+        assert!(!unit.contains_point(Vec3::new(1.0, 0.5, -0.001)));
+
+        // Sharing only a face counts as intersecting; any gap does not
+        let touching = BoundingBox::new(Vec3::new(1.0, 0.0, 0.0), Vec3::new(2.0, 1.0, 1.0));
+        let apart = BoundingBox::new(Vec3::new(1.001, 0.0, 0.0), Vec3::new(2.0, 1.0, 1.0));
+        assert!(unit.intersects(&touching) && touching.intersects(&unit));
+        assert!(!unit.intersects(&apart));
+
+        // A zero-size box behaves like a point
+        let point = BoundingBox::new(Vec3::new(0.5, 0.5, 0.5), Vec3::new(0.5, 0.5, 0.5));
+        assert!(unit.intersects(&point) && point.intersects(&point));
+        assert_eq!(point.half_extents(), Vec3::zero());
+        assert!(point.contains_point(point.center()));
+
+        let merged = point.union(&apart);
+        assert_eq!(merged.min, Vec3::new(0.5, 0.0, 0.0));
+        assert_eq!(merged.max, Vec3::new(2.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn test_face_areas_and_center_of_mass() {
+        let cube = Mesh::create_cube(2.0);
+        let areas = cube.compute_face_areas();
+        assert_eq!(areas.len(), 12);
+        assert!(areas.iter().all(|&a| (a - 2.0).abs() < 1e-10));
+        assert!((cube.surface_area() - 24.0).abs() < 1e-10);
+        assert!(cube.center_of_mass().length() < 1e-10);
+
+        // L shape: a long arm of three squares along X and one square on top
+        let l_shape = squares_mesh(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (0.0, 1.0)]);
+        let com = l_shape.center_of_mass();
+        assert!((com.x - 1.25).abs() < 1e-10);
+        assert!((com.y - 0.75).abs() < 1e-10);
+
+        // Pulled down towards the long arm compared to the box center
+        assert!(com.y < l_shape.bounding_box_center().y);
+    }
+
+    #[test]
+    fn test_surface_area_and_volume() {
+        let mut cube = Mesh::create_cube(1.0);
+        assert!((cube.calculate_surface_area() - 6.0).abs() < 1e-10);
+        assert!((cube.calculate_volume() - 1.0).abs() < 1e-10);
+
+        // Measured after the mesh transform
+        cube.transform(Mat4::trs(Vec3::new(3.0, -1.0, 2.0), Vec3::new(0.3, 0.2, 0.1), Vec3::new(2.0, 2.0, 2.0)));
+        assert!((cube.calculate_surface_area() - 24.0).abs() < 1e-9);
+        assert!((cube.calculate_volume() - 8.0).abs() < 1e-9);
+
+        let sphere = crate::shape_factory::ShapeFactory::create_sphere(2.0, 128, 128);
+        let pi = std::f64::consts::PI;
+        assert!((sphere.calculate_surface_area() / (4.0 * pi * 4.0) - 1.0).abs() < 1e-3);
+        assert!((sphere.calculate_volume() / (4.0 / 3.0 * pi * 8.0) - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        let cube = Mesh::create_cube(2.0);
+        assert!(cube.is_convex());
+        let hull = cube.convex_hull();
+        assert_eq!(hull.vertices.len(), 8);
+        assert_eq!(hull.faces.len(), 12);
+        assert!(hull.is_convex());
+
+        // L shape built from three cubes; the inner corner at (1, 1) is concave
+        let mut l_shape = Mesh::new();
+        for offset in [Vec3::zero(), Vec3::new(2.0, 0.0, 0.0), Vec3::new(0.0, 2.0, 0.0)] {
+            let base = l_shape.vertices.len();
+            for v in &cube.vertices {
+                l_shape.add_vertex(Vertex::new(v.position + offset, v.normal, v.uv));
+            }
+            for f in &cube.faces {
+                l_shape.add_face(f.vertices.map(|i| base + i));
+            }
+        }
+        assert!(!l_shape.is_convex());
+
+        let hull = l_shape.convex_hull();
+        assert!(hull.is_convex());
+        assert_eq!(hull.vertices.len(), 10);
+        assert!(hull.vertices.iter().all(|v| !((v.position.x - 1.0).abs() < 1e-10 && (v.position.y - 1.0).abs() < 1e-10)));
+        assert!(hull.surface_area() < l_shape.surface_area());
+    }
+
+    #[test]
+    fn test_smooth_normals_with_threshold() {
+        let mut cube = Mesh::create_cube(2.0);
+        cube.smooth_normals_with_threshold(80.0_f64.to_radians());
+        assert_eq!(cube.vertices.len(), 24);
+        for face in &cube.faces {
+            for &idx in &face.vertices {
+                assert!((cube.vertices[idx].normal - face.normal).length() < 1e-10);
+            }
+        }
+
+        // A threshold of 180 degrees averages everything, like generate_vertex_normals
+        let smooth = Mesh::create_cube(2.0);
+        let mut creased = Mesh::create_cube(2.0);
+        creased.smooth_normals_with_threshold(std::f64::consts::PI);
+        assert_eq!(creased.vertices.len(), smooth.vertices.len());
+        for (a, b) in creased.vertices.iter().zip(&smooth.vertices) {
+            assert!((a.normal - b.normal).length() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_compute_smooth_groups() {
+        let cube = Mesh::create_cube(2.0);
+        let grouped = cube.compute_smooth_groups(45.0);
+        // Every corner is shared by three faces at 90 degrees
+        assert_eq!(grouped.vertices.len(), 24);
+        assert_eq!(grouped.faces.len(), cube.faces.len());
+        assert_eq!(cube.vertices.len(), 8);
+
+        let plane = Mesh::from_heightmap(&[0.0; 9], 3, 3, 1.0, 1.0);
+        assert_eq!(plane.compute_smooth_groups(45.0).vertices.len(), 9);
+    }
+
+    #[test]
+    fn test_apply_transform() {
+        let mut mesh = Mesh::create_cube(1.0);
+        mesh.transform(Mat4::scaling(2.0, 3.0, 4.0));
+        let expected = mesh.get_transformed_vertices();
+
+        let baked = mesh.transformed_clone();
+        assert_eq!(mesh.vertices[0].position, Mesh::create_cube(1.0).vertices[0].position);
+
+        mesh.apply_transform();
+        assert_eq!(mesh.vertices[0].position, expected[0].position);
+        assert_eq!(baked.vertices[0].position, expected[0].position);
+        assert_eq!(mesh.transform.data, Mat4::identity().data);
+
+        let bbox = mesh.calculate_bounding_box();
+        assert_eq!(bbox.max, Vec3::new(1.0, 1.5, 2.0));
+    }
+
+    #[test]
+    fn test_apply_transform_to_vertices() {
+        let mut mesh = Mesh::create_cube(1.0);
+        mesh.smooth_normals_with_threshold(0.1);
+        mesh.transform(Mat4::trs(Vec3::new(1.0, -2.0, 0.5), Vec3::new(0.3, 0.2, 0.1), Vec3::new(2.0, 1.0, 1.0)));
+        let before = mesh.calculate_bounding_box();
+
+        mesh.apply_transform_to_vertices();
+        assert_eq!(mesh.transform.data, Mat4::identity().data);
+        let after = mesh.calculate_bounding_box();
+        assert!((after.min - before.min).length() < 1e-12);
+        assert!((after.max - before.max).length() < 1e-12);
+
+        // Hard-edged normals survive and still match their faces
+        for face in &mesh.faces {
+            for &i in &face.vertices {
+                assert!((mesh.vertices[i].normal - face.normal).length() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let left = Mesh::create_cube(1.0);
+        let mut right = Mesh::create_cube(1.0);
+        right.transform(Mat4::translation(3.0, 0.0, 0.0));
+        right.apply_transform();
+
+        let merged = left.merge(&right);
+        assert_eq!(merged.vertices.len(), 16);
+        assert_eq!(merged.faces.len(), 24);
+        assert!(merged.faces[12..].iter().all(|face| face.vertices.iter().all(|&i| i >= 8)));
+
+        let bbox = merged.calculate_bounding_box();
+        assert_eq!(bbox.min, Vec3::new(-0.5, -0.5, -0.5));
+        assert_eq!(bbox.max, Vec3::new(3.5, 0.5, 0.5));
+
+        let mut in_place = left.clone();
+        in_place.merge_in_place(&right);
+        assert_eq!(in_place.vertices.len(), merged.vertices.len());
+    }
+
+    #[test]
+    fn test_from_heightmap() {
+        let flat = Mesh::from_heightmap(&[0.0; 12], 4, 3, 1.0, 5.0);
+        assert_eq!(flat.faces.len(), 3 * 2 * 2);
+        assert!(flat.vertices.iter().all(|v| v.position.y == 0.0));
+        assert!(flat.faces.iter().all(|f| (f.normal - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-10));
+        assert_eq!(flat.vertices[11].uv, Vec2::new(1.0, 1.0));
+
+        // One full sine hump along X, repeated on every row
+        let (width, height) = (9, 5);
+        let heights: Vec<f64> = (0..width * height)
+            .map(|i| ((i % width) as f64 / (width - 1) as f64 * std::f64::consts::PI).sin())
+            .collect();
+        let terrain = Mesh::from_heightmap(&heights, width, height, 0.5, 3.0);
+        assert_eq!(terrain.faces.len(), (width - 1) * (height - 1) * 2);
+
+        let peak = terrain.vertices.iter().map(|v| v.position.y).fold(f64::MIN, f64::max);
+        assert!((peak - 3.0).abs() < 1e-10);
+        assert!((terrain.calculate_bounding_box().max.z - 2.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_second_uv_channel() {
+        let mut mesh = Mesh::create_cube(1.0);
+        assert!(mesh.vertices.iter().all(|v| v.uv2 == Vec2::zero()));
+
+        mesh.copy_uv_to_uv2();
+        assert!(mesh.vertices.iter().all(|v| v.uv2 == v.uv));
+
+        mesh.vertices[0].uv2 = Vec2::new(0.25, 0.75);
+        mesh.transform(Mat4::translation(1.0, 0.0, 0.0));
+        assert_eq!(mesh.get_transformed_vertices()[0].uv2, Vec2::new(0.25, 0.75));
+    }
+
+    #[test]
+    fn test_tangents_follow_uv_axes() {
+        let mut quad = Mesh::new();
+        for (x, y) in [(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)] {
+            quad.add_vertex(Vertex::new(Vec3::new(x, y, 0.0), Vec3::new(0.0, 0.0, 1.0), Vec2::new(x / 2.0, y / 2.0)));
+        }
+        quad.add_face([0, 1, 2]);
+        quad.add_face([0, 2, 3]);
+        quad.compute_tangents_and_bitangents();
+
+        for vertex in &quad.vertices {
+            assert!((vertex.tangent - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-9);
+            assert!((vertex.bitangent - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-9);
+        }
+
+        // Mirroring U flips the tangent but keeps the bitangent along +V
+        for vertex in &mut quad.vertices {
+            vertex.uv.x = 1.0 - vertex.uv.x;
+        }
+        quad.compute_tangents_and_bitangents();
+        assert!((quad.vertices[0].tangent - Vec3::new(-1.0, 0.0, 0.0)).length() < 1e-9);
+        assert!((quad.vertices[0].bitangent - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn test_unique_edges() {
+        // 12 box edges plus one diagonal per side
+        let edges = Mesh::create_cube(1.0).get_unique_edges();
+        assert_eq!(edges.len(), 18);
+        assert!(edges.iter().all(|&(a, b)| a < b));
+        assert!(edges.contains(&(0, 1)) && edges.contains(&(0, 2)));
+
+        assert_eq!(squares_mesh(&[(0.0, 0.0)]).get_unique_edges(), vec![(0, 1), (0, 2), (0, 3), (1, 2), (2, 3)]);
+    }
+
+    #[test]
+    fn test_validate_and_repair() {
+        let mut mesh = Mesh::create_cube(1.0);
+        assert_eq!(mesh.validate(), Ok(()));
+
+        mesh.vertices[3].position.y = f64::NAN;
+        mesh.faces.push(Face::new([0, 1, 42]));
+        assert_eq!(mesh.validate(), Err(vec![
+            MeshError::InvalidFaceIndex { face_idx: 12, vertex_idx: 42 },
+            MeshError::NanVertexPosition { vertex_idx: 3 },
+        ]));
+
+        mesh.repair();
+        assert_eq!(mesh.validate(), Ok(()));
+        assert_eq!(mesh.faces.len(), 12);
+        assert_eq!(mesh.vertices[3].position, Vec3::zero());
+
+        // Zero-area faces and broken normals are caught and fixed too
+        let mut flat = squares_mesh(&[(0.0, 0.0)]);
+        flat.faces.push(Face::new([0, 1, 1]));
+        flat.vertices[2].normal = Vec3::new(f64::NAN, 0.0, 0.0);
+        flat.vertices[3].normal = Vec3::new(0.0, 0.0, 2.0);
+        assert_eq!(flat.validate(), Err(vec![
+            MeshError::DegenerateFace { face_idx: 2 },
+            MeshError::NanNormal { vertex_idx: 2 },
+            MeshError::NonUnitNormal { vertex_idx: 3 },
+        ]));
+        flat.repair();
+        assert_eq!(flat.validate(), Ok(()));
+        assert_eq!(flat.faces.len(), 2);
+    }
+
+    #[test]
+    fn test_face_winding() {
+        let cube = Mesh::create_cube(1.0);
+
+        let mut reversed = cube.clone();
+        reversed.reverse_face_winding();
+        for (face, original) in reversed.faces.iter().zip(&cube.faces) {
+            assert!((face.normal + original.normal).length() < 1e-10);
+        }
+
+        let mut flipped = cube.clone();
+        flipped.flip_face_normals();
+        for (face, original) in flipped.faces.iter().zip(&cube.faces) {
+            assert_eq!(face.vertices, original.vertices);
+            assert!((face.normal + original.normal).length() < 1e-10);
+        }
+
+        // Scramble a few faces, then let the first face decide the orientation
+        let mut mixed = cube.clone();
+        for &i in &[1, 4, 5, 9] {
+            mixed.faces[i].vertices.swap(1, 2);
+            mixed.faces[i].calculate_normal(&mixed.vertices);
+        }
+        mixed.ensure_consistent_winding();
+        for (face, original) in mixed.faces.iter().zip(&cube.faces) {
+            assert!((face.normal - original.normal).length() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_extrude_profile() {
+        // Clockwise square, extruded into a 2x2x2 box
+        let square = [
+            Vec2::new(-1.0, -1.0),
+            Vec2::new(-1.0, 1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(1.0, -1.0),
+        ];
+        let mut prism = Mesh::extrude_profile(&square, 2.0, true);
+        assert_eq!(prism.faces.len(), Mesh::create_cube(2.0).faces.len());
+        assert_eq!(prism.vertices.len(), 8);
+
+        // All faces point away from the center of the solid
+        prism.transform(Mat4::translation(0.0, 0.0, -1.0));
+        let vertices = prism.get_transformed_vertices();
+        for face in &prism.faces {
+            let centroid = face.vertices.iter()
+                .fold(Vec3::zero(), |sum, &i| sum + vertices[i].position) / 3.0;
+            assert!(face.normal.dot(&centroid) > 0.0);
+        }
+        let bbox = prism.calculate_bounding_box();
+        assert!((bbox.min - Vec3::new(-1.0, -1.0, -1.0)).length() < 1e-10);
+        assert!((bbox.max - Vec3::new(1.0, 1.0, 1.0)).length() < 1e-10);
+
+        let hexagon: Vec<Vec2> = (0..6)
+            .map(|i| {
+                let angle = i as f64 * std::f64::consts::PI / 3.0;
+                Vec2::new(angle.cos(), angle.sin())
+            })
+            .collect();
+        assert_eq!(Mesh::extrude_profile(&hexagon, 1.0, true).faces.len(), 20);
+        assert_eq!(Mesh::extrude_profile(&hexagon, 1.0, false).faces.len(), 12);
+    }
+
+    // Closed sphere-like mesh: hull of evenly spread points, 2 * count - 4 faces
+    fn fibonacci_sphere(count: usize) -> Mesh {
+        let mut points = Mesh::new();
+        let golden_angle = std::f64::consts::PI * (3.0 - 5.0_f64.sqrt());
+        for i in 0..count {
+            let y = 1.0 - 2.0 * (i as f64 + 0.5) / count as f64;
+            let r = (1.0 - y * y).sqrt();
+            let theta = golden_angle * i as f64;
+            let p = Vec3::new(r * theta.cos(), y, r * theta.sin());
+            points.add_vertex(Vertex::new(p, p, Vec2::zero()));
+        }
+        points.convex_hull()
+    }
+
+    #[test]
+    fn test_compute_lod_mesh() {
+        let sphere = fibonacci_sphere(258);
+        assert_eq!(sphere.faces.len(), 512);
+
+        let lod = sphere.compute_lod_mesh(128);
+        assert!(lod.faces.len() <= 128);
+        assert!(lod.faces.len() >= 100);
+
+        let original = sphere.calculate_bounding_box();
+        let simplified = lod.calculate_bounding_box();
+        let tolerance = (original.max - original.min) * 0.1;
+        let within = |a: Vec3, b: Vec3| (a - b).abs().component_max(tolerance) == tolerance;
+        assert!(within(simplified.min, original.min));
+        assert!(within(simplified.max, original.max));
+
+        let mut group = LodGroup::new();
+        group.add_lod(50.0, lod);
+        group.add_lod(10.0, sphere);
+        assert_eq!(group.select(5.0).unwrap().faces.len(), 512);
+        assert!(group.select(20.0).unwrap().faces.len() <= 128);
+        assert!(group.select(1000.0).unwrap().faces.len() <= 128);
+    }
+
+    #[test]
+    fn test_bvh_intersect() {
+        let cube = Mesh::create_cube(2.0);
+        let bvh = Bvh::build(&cube);
+        let hit = bvh.intersect(Vec3::new(0.2, 0.3, -5.0), Vec3::new(0.0, 0.0, 1.0)).unwrap();
+        assert!((hit.t - 4.0).abs() < 1e-9);
+        assert!(cube.faces[hit.face_index].normal.z < -0.5);
+        let b = hit.barycentric;
+        assert!((b.x + b.y + b.z - 1.0).abs() < 1e-9 && b.x >= 0.0 && b.y >= 0.0 && b.z >= 0.0);
+        assert!(bvh.intersect(Vec3::new(3.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0)).is_none());
+        assert!(bvh.intersect(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, -1.0)).is_none());
+        assert!(Bvh::build(&Mesh::new()).intersect(Vec3::zero(), Vec3::new(1.0, 0.0, 0.0)).is_none());
+
+        // Same answers as testing every face, on a mesh large enough to split
+        let sphere = fibonacci_sphere(258);
+        let bvh = Bvh::build(&sphere);
+        assert!(matches!(bvh.root(), Some(BvhNode::Interior { .. })));
+        let triangles: Vec<[Vec3; 3]> = sphere.faces.iter()
+            .map(|f| f.vertices.map(|i| sphere.vertices[i].position))
+            .collect();
+        for i in 0..200 {
+            let angle = i as f64 * 0.37;
+            let origin = Vec3::new(3.0 * angle.cos(), (i as f64 * 0.13).sin() * 2.0, 3.0 * angle.sin());
+            let dir = (Vec3::new((i as f64 * 0.71).sin() * 0.5, 0.1, 0.0) - origin).normalize();
+            let brute = triangles.iter().enumerate()
+                .filter_map(|(face, &tri)| ray_triangle(tri, origin, dir).map(|(t, _)| (t, face)))
+                .min_by(|a, b| a.0.total_cmp(&b.0));
+            let hit = bvh.intersect(origin, dir);
+            assert_eq!(hit.map(|h| h.face_index), brute.map(|(_, face)| face));
+        }
+    }
+
+    #[test]
+    fn test_subdivide() {
+        let cube = Mesh::create_cube(2.0);
+        let radius = 3.0_f64.sqrt();
+
+        let once = cube.subdivide(1);
+        assert_eq!(once.vertices.len(), 26);
+        assert_eq!(once.faces.len(), 48);
+        assert!(once.vertices.iter().all(|v| v.position.length() <= radius + 1e-9));
+        assert!(once.validate().is_ok());
+
+        // Levels beyond the maximum are clamped
+        assert_eq!(cube.subdivide(0).faces.len(), 12);
+        assert_eq!(cube.subdivide(9).faces.len(), 12 * 4usize.pow(Mesh::MAX_SUBDIVISION_LEVELS));
+    }
 }
@@ -0,0 +1,328 @@
+use crate::math::{Vec2, Vec3};
+use std::sync::Arc;
+use crate::rasterizer::{Color, ColorF, ToneMapping};
+use crate::texture::Texture;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum AttenuationModel {
+    // Classic 1 / (constant + linear * d + quadratic * d^2)
+    Blinn { constant: f64, linear: f64, quadratic: f64 },
+    // Inverse-square falloff windowed to reach zero at `radius`
+    InverseSquare { radius: f64 },
+    None,
+}
+
+impl AttenuationModel {
+    pub fn evaluate(&self, distance: f64) -> f64 {
+        match *self {
+            AttenuationModel::Blinn { constant, linear, quadratic } => {
+                let denom = constant + linear * distance + quadratic * distance * distance;
+                if denom > 0.0 { 1.0 / denom } else { 1.0 }
+            }
+            AttenuationModel::InverseSquare { radius } => {
+                if radius <= 0.0 {
+                    return 0.0;
+                }
+                // saturate(1 - (d/r)^4)^2 / (d^2 + 1), as used by Unreal Engine
+                let ratio = distance / radius;
+                let window = (1.0 - ratio.powi(4)).clamp(0.0, 1.0);
+                window * window / (distance * distance + 1.0)
+            }
+            AttenuationModel::None => 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PointLight {
+    pub position: Vec3,
+    pub color: Color,
+    pub intensity: f64,
+    pub attenuation: AttenuationModel,
+}
+
+impl PointLight {
+    pub fn new(position: Vec3, color: Color, intensity: f64) -> Self {
+        Self {
+            position,
+            color,
+            intensity,
+            attenuation: AttenuationModel::None,
+        }
+    }
+
+    pub fn with_attenuation(mut self, attenuation: AttenuationModel) -> Self {
+        self.attenuation = attenuation;
+        self
+    }
+
+    // Light intensity reaching `point` after attenuation
+    pub fn intensity_at(&self, point: Vec3) -> f64 {
+        let distance = (point - self.position).length();
+        self.intensity * self.attenuation.evaluate(distance)
+    }
+}
+
+// Parallel light arriving from infinitely far away along `direction`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectionalLight {
+    pub direction: Vec3,
+    pub color: Color,
+    pub intensity: f64,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vec3, color: Color, intensity: f64) -> Self {
+        Self {
+            direction: direction.normalize(),
+            color,
+            intensity,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Light {
+    Point(PointLight),
+    Directional(DirectionalLight),
+}
+
+impl Light {
+    // Point light attenuated by 1 / (c + l * d + q * d^2) for
+    // `attenuation` = (c, l, q)
+    pub fn point(position: Vec3, color: Color, intensity: f64, attenuation: (f64, f64, f64)) -> Self {
+        let (constant, linear, quadratic) = attenuation;
+        Light::Point(
+            PointLight::new(position, color, intensity)
+                .with_attenuation(AttenuationModel::Blinn { constant, linear, quadratic }),
+        )
+    }
+
+    pub fn directional(direction: Vec3, color: Color, intensity: f64) -> Self {
+        Light::Directional(DirectionalLight::new(direction, color, intensity))
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Light::Point(light) => light.color,
+            Light::Directional(light) => light.color,
+        }
+    }
+
+    // Unit vector from `point` towards the light
+    pub fn direction_from(&self, point: Vec3) -> Vec3 {
+        match self {
+            Light::Point(light) => (light.position - point).normalize(),
+            Light::Directional(light) => -light.direction.normalize(),
+        }
+    }
+
+    pub fn intensity_at(&self, point: Vec3) -> f64 {
+        match self {
+            Light::Point(light) => light.intensity_at(point),
+            Light::Directional(light) => light.intensity,
+        }
+    }
+}
+
+impl From<PointLight> for Light {
+    fn from(light: PointLight) -> Self {
+        Light::Point(light)
+    }
+}
+
+impl From<DirectionalLight> for Light {
+    fn from(light: DirectionalLight) -> Self {
+        Light::Directional(light)
+    }
+}
+
+// Phong surface parameters; the reflection coefficients scale `color`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Material {
+    pub color: Color,
+    pub ambient: f64,
+    pub diffuse: f64,
+    pub specular: f64,
+    pub shininess: f64,
+    // Applied to the summed lighting before it is stored as a Color
+    #[serde(default)]
+    pub tone_mapping: ToneMapping,
+    // Baked lighting sampled with the vertices' second UV channel. Not saved
+    // with scenes.
+    #[serde(skip)]
+    pub lightmap_texture: Option<Arc<Texture>>,
+}
+
+impl Material {
+    pub fn new(color: Color) -> Self {
+        Self {
+            color,
+            ambient: 0.1,
+            diffuse: 0.8,
+            specular: 0.5,
+            shininess: 32.0,
+            tone_mapping: ToneMapping::None,
+            lightmap_texture: None,
+        }
+    }
+
+    pub fn with_shininess(mut self, shininess: f64) -> Self {
+        self.shininess = shininess;
+        self
+    }
+
+    pub fn with_tone_mapping(mut self, tone_mapping: ToneMapping) -> Self {
+        self.tone_mapping = tone_mapping;
+        self
+    }
+
+    pub fn with_lightmap(mut self, lightmap: Arc<Texture>) -> Self {
+        self.lightmap_texture = Some(lightmap);
+        self
+    }
+
+    // Tints a shaded color by the lightmap at `uv2`, if there is one
+    pub fn apply_lightmap(&self, color: Color, uv2: Vec2) -> Color {
+        match &self.lightmap_texture {
+            Some(lightmap) => color.modulate(lightmap.sample(uv2.x, uv2.y)),
+            None => color,
+        }
+    }
+
+    // Phong lighting at a surface point: ambient + diffuse max(0, N.L) +
+    // specular max(0, R.V)^shininess, summed over all lights
+    pub fn shade(&self, position: Vec3, normal: Vec3, eye: Vec3, lights: &[Light]) -> Color {
+        let base = ColorF::from_color(self.color);
+        let base = [base.r as f64, base.g as f64, base.b as f64];
+        let view = (eye - position).normalize();
+
+        let mut rgb = base.map(|c| c * self.ambient);
+        for light in lights {
+            let to_light = light.direction_from(position);
+            let n_dot_l = normal.dot(&to_light);
+            if n_dot_l <= 0.0 {
+                continue;
+            }
+
+            let reflected = (-to_light).reflect(normal);
+            let specular = reflected.dot(&view).max(0.0).powf(self.shininess) * self.specular;
+            let diffuse = n_dot_l * self.diffuse;
+
+            let intensity = light.intensity_at(position);
+            let light_color = ColorF::from_color(light.color());
+            let light_rgb = [light_color.r as f64, light_color.g as f64, light_color.b as f64];
+            for i in 0..3 {
+                rgb[i] += (base[i] * diffuse + specular) * light_rgb[i] * intensity;
+            }
+        }
+
+        let alpha = self.color.a as f32 / 255.0;
+        ColorF::new(rgb[0] as f32, rgb[1] as f32, rgb[2] as f32, alpha)
+            .tone_map(self.tone_mapping)
+            .to_color_clamped()
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self::new(Color::white())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inverse_square_attenuation() {
+        let model = AttenuationModel::InverseSquare { radius: 10.0 };
+        assert!((model.evaluate(0.0) - 1.0).abs() < 1e-10);
+        assert!(model.evaluate(10.0).abs() < 1e-10);
+
+        for i in 0..200 {
+            let value = model.evaluate(i as f64 * 0.1);
+            assert!((0.0..=1.0).contains(&value));
+        }
+    }
+
+    #[test]
+    fn test_attenuation_models_at_unit_distance() {
+        let blinn = AttenuationModel::Blinn { constant: 0.0, linear: 1.0, quadratic: 0.0 };
+        let inverse_square = AttenuationModel::InverseSquare { radius: 100.0 };
+
+        assert!((blinn.evaluate(1.0) - 1.0).abs() < 1e-10);
+        // 1 / (1 + 1) with an almost fully open window
+        assert!((inverse_square.evaluate(1.0) - 0.5).abs() < 1e-6);
+        assert!(inverse_square.evaluate(1.0) <= blinn.evaluate(1.0));
+        assert_eq!(AttenuationModel::None.evaluate(1.0), 1.0);
+    }
+
+    #[test]
+    fn test_phong_shading() {
+        let material = Material::new(Color::new(255, 0, 0, 255)).with_shininess(16.0);
+        let light = Light::from(PointLight::new(Vec3::new(0.0, 0.0, 10.0), Color::white(), 1.0));
+        let normal = Vec3::new(0.0, 0.0, 1.0);
+        let eye = Vec3::new(0.0, 0.0, 10.0);
+
+        // Facing the light head on: full diffuse plus the specular peak
+        let lit = material.shade(Vec3::zero(), normal, eye, std::slice::from_ref(&light));
+        assert_eq!((lit.r, lit.g, lit.b), (255, 128, 128));
+
+        // Facing away leaves only ambient
+        let unlit = material.shade(Vec3::zero(), -normal, eye, &[light]);
+        assert_eq!((unlit.r, unlit.g, unlit.b), (26, 0, 0));
+    }
+
+    #[test]
+    fn test_tone_mapped_shading() {
+        let light = Light::from(PointLight::new(Vec3::new(0.0, 0.0, 10.0), Color::white(), 10.0));
+        let normal = Vec3::new(0.0, 0.0, 1.0);
+        let eye = Vec3::new(0.0, 10.0, 10.0);
+        let shade = |tone_mapping: ToneMapping| {
+            let material = Material::new(Color::new(255, 128, 0, 255)).with_tone_mapping(tone_mapping);
+            material.shade(Vec3::zero(), normal, eye, std::slice::from_ref(&light))
+        };
+
+        // Without tone mapping the bright channels clip to full intensity
+        let clipped = shade(ToneMapping::None);
+        assert_eq!((clipped.r, clipped.g), (255, 255));
+
+        for mapping in [ToneMapping::Reinhard, ToneMapping::Aces] {
+            let mapped = shade(mapping);
+            assert!(mapped.r < 255 && mapped.g < mapped.r, "{:?}", mapping);
+            assert!(mapped.r > 200, "{:?}", mapping);
+            assert_eq!(mapped.a, 255);
+        }
+    }
+
+    #[test]
+    fn test_light_kinds() {
+        let point = Light::point(Vec3::new(0.0, 4.0, 0.0), Color::white(), 2.0, (1.0, 0.5, 0.25));
+        // 2 / (1 + 0.5 * 4 + 0.25 * 16)
+        assert!((point.intensity_at(Vec3::zero()) - 2.0 / 7.0).abs() < 1e-12);
+        assert_eq!(point.direction_from(Vec3::zero()), Vec3::new(0.0, 1.0, 0.0));
+
+        // Directional lights have the same direction and strength everywhere
+        let sun = Light::directional(Vec3::new(0.0, -2.0, 0.0), Color::white(), 0.5);
+        for p in [Vec3::zero(), Vec3::new(100.0, -50.0, 3.0)] {
+            assert_eq!(sun.direction_from(p), Vec3::new(0.0, 1.0, 0.0));
+            assert_eq!(sun.intensity_at(p), 0.5);
+        }
+
+        let material = Material::new(Color::white());
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let lit = material.shade(Vec3::zero(), up, Vec3::new(1.0, 1.0, 0.0), &[sun]);
+        // ambient + 0.5 * diffuse, with a little specular off-axis
+        assert!(lit.r > 127 && lit.r < 140);
+    }
+
+    #[test]
+    fn test_point_light_intensity() {
+        let light = PointLight::new(Vec3::zero(), Color::white(), 2.0)
+            .with_attenuation(AttenuationModel::InverseSquare { radius: 5.0 });
+        assert!((light.intensity_at(Vec3::zero()) - 2.0).abs() < 1e-10);
+        assert!(light.intensity_at(Vec3::new(5.0, 0.0, 0.0)).abs() < 1e-10);
+    }
+}
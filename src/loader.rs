@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::geometry::{Mesh, Vertex};
+use crate::math::{Vec2, Vec3};
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    Parse { line: usize, msg: String },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Io(err) => write!(f, "I/O error: {}", err),
+            LoadError::Parse { line, msg } => write!(f, "parse error on line {}: {}", line, msg),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<io::Error> for LoadError {
+    fn from(err: io::Error) -> Self {
+        LoadError::Io(err)
+    }
+}
+
+// Position, uv, normal and second uv indices of one face vertex
+type FaceVertexKey = (usize, Option<usize>, Option<usize>, Option<usize>);
+
+pub fn load_obj(path: &Path) -> Result<Mesh, LoadError> {
+    let src = fs::read_to_string(path)?;
+    load_obj_from_str(&src)
+}
+
+// Parses Wavefront OBJ geometry (v, vt, vn, f). Polygons are fan-triangulated
+// and unsupported statements (groups, materials, ...) are ignored. As an
+// extension, a fourth index on a face vertex (v/vt/vn/vt2) picks another vt
+// entry for the second UV channel, e.g. lightmap coordinates.
+pub fn load_obj_from_str(src: &str) -> Result<Mesh, LoadError> {
+    let mut positions = Vec::new();
+    let mut uvs = Vec::new();
+    let mut normals = Vec::new();
+
+    let mut mesh = Mesh::new();
+    // Each distinct v/vt/vn combination becomes one mesh vertex
+    let mut vertex_lookup: HashMap<FaceVertexKey, usize> = HashMap::new();
+    let mut has_normals = true;
+
+    for (index, line) in src.lines().enumerate() {
+        let line_number = index + 1;
+        let error = |msg: String| LoadError::Parse { line: line_number, msg };
+
+        let line = line.split('#').next().unwrap_or("");
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+        let args: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "v" => {
+                let values = parse_floats(&args, 3).map_err(error)?;
+                positions.push(Vec3::new(values[0], values[1], values[2]));
+            }
+            "vt" => {
+                let values = parse_floats(&args, 2).map_err(error)?;
+                uvs.push(Vec2::new(values[0], values[1]));
+            }
+            "vn" => {
+                let values = parse_floats(&args, 3).map_err(error)?;
+                normals.push(Vec3::new(values[0], values[1], values[2]).normalize());
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(error(format!("face needs at least 3 vertices, got {}", args.len())));
+                }
+
+                let mut polygon = Vec::with_capacity(args.len());
+                for arg in &args {
+                    let mut parts = arg.split('/');
+                    let position = resolve_index(parts.next(), positions.len()).map_err(&error)?
+                        .ok_or_else(|| error(format!("face vertex '{}' has no position", arg)))?;
+                    let uv = resolve_index(parts.next(), uvs.len()).map_err(&error)?;
+                    let normal = resolve_index(parts.next(), normals.len()).map_err(&error)?;
+                    let uv2 = resolve_index(parts.next(), uvs.len()).map_err(&error)?;
+                    has_normals &= normal.is_some();
+
+                    let vertex = *vertex_lookup.entry((position, uv, normal, uv2)).or_insert_with(|| {
+                        let mut vertex = Vertex::new(
+                            positions[position],
+                            normal.map_or(Vec3::zero(), |n| normals[n]),
+                            uv.map_or(Vec2::new(0.0, 0.0), |t| uvs[t]),
+                        );
+                        if let Some(t) = uv2 {
+                            vertex.uv2 = uvs[t];
+                        }
+                        mesh.add_vertex(vertex)
+                    });
+                    polygon.push(vertex);
+                }
+
+                for i in 1..polygon.len() - 1 {
+                    mesh.add_face([polygon[0], polygon[i], polygon[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !has_normals {
+        mesh.generate_vertex_normals();
+    }
+    Ok(mesh)
+}
+
+// Reads at least `count` floats, ignoring any extras (e.g. the optional w)
+fn parse_floats(args: &[&str], count: usize) -> Result<Vec<f64>, String> {
+    if args.len() < count {
+        return Err(format!("expected {} values, got {}", count, args.len()));
+    }
+    args[..count].iter()
+        .map(|arg| arg.parse::<f64>().map_err(|_| format!("invalid number '{}'", arg)))
+        .collect()
+}
+
+// OBJ indices are 1-based; negative ones count back from the latest element
+fn resolve_index(token: Option<&str>, len: usize) -> Result<Option<usize>, String> {
+    let Some(token) = token.filter(|t| !t.is_empty()) else {
+        return Ok(None);
+    };
+    let index: i64 = token.parse().map_err(|_| format!("invalid index '{}'", token))?;
+    let resolved = if index < 0 { len as i64 + index } else { index - 1 };
+    if resolved < 0 || resolved >= len as i64 {
+        return Err(format!("index {} out of range", index));
+    }
+    Ok(Some(resolved as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_obj_from_str() {
+        let src = "\
+# unit quad
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+f 1/1/1 2/2/1 3/3/1 4/4/1
+";
+        let mesh = load_obj_from_str(src).unwrap();
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.faces.len(), 2);
+        assert_eq!(mesh.faces[1].vertices, [0, 2, 3]);
+        assert_eq!(mesh.vertices[2].uv.x, 1.0);
+        assert_eq!(mesh.vertices[2].normal.z, 1.0);
+
+        // A fourth index selects the second UV channel
+        let mesh = load_obj_from_str("v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvt 0.5 0.25\nf 1/1//2 2/1//1 3/1\n").unwrap();
+        assert_eq!(mesh.vertices[0].uv2, Vec2::new(0.5, 0.25));
+        assert_eq!(mesh.vertices[0].uv, Vec2::zero());
+        assert_eq!(mesh.vertices[1].uv2, Vec2::zero());
+        assert_eq!(mesh.vertices[2].uv2, Vec2::zero());
+
+        // Without vn the normals are generated
+        let mesh = load_obj_from_str("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 -1\n").unwrap();
+        assert!((mesh.vertices[0].normal.z.abs() - 1.0).abs() < 1e-9);
+
+        match load_obj_from_str("v 0 0 0\nf 1 2 3\n") {
+            Err(LoadError::Parse { line, .. }) => assert_eq!(line, 2),
+            other => panic!("expected a parse error, got {:?}", other.map(|m| m.faces.len())),
+        }
+    }
+}
@@ -1,18 +1,24 @@
+mod animation;
 mod app;
 mod camera;
 mod geometry;
+mod lighting;
+mod loader;
 mod math;
+mod ply;
 mod renderer;
 mod rasterizer;
 mod scene;
 mod shape_factory;
+mod texture;
+mod timing;
 mod config;
 
 use app::Application;
 
 fn main() {
     println!("Starting application...");
-    let mut app = Application::new(800, 600, "sgr-rs test");
+    let mut app = Application::from_config_file();
     println!("Application created, running...");
     app.run();
 }
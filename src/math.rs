@@ -1,4 +1,5 @@
-use std::ops::{Add, Sub, Mul, Div};
+use std::ops::{Add, AddAssign, Sub, SubAssign, Mul, MulAssign, Div, DivAssign, Neg, Index, IndexMut};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Vec2 {
@@ -6,7 +7,7 @@ pub struct Vec2 {
     pub y: f64,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Vec3 {
     pub x: f64,
     pub y: f64,
@@ -18,6 +19,15 @@ pub struct Mat4 {
     pub data: [[f64; 4]; 4],
 }
 
+// Rotation quaternion, w being the scalar part
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Quaternion {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub w: f64,
+}
+
 // Vec2 implementations
 impl Vec2 {
     pub fn new(x: f64, y: f64) -> Self {
@@ -44,6 +54,44 @@ impl Vec2 {
     pub fn dot(&self, other: &Vec2) -> f64 {
         self.x * other.x + self.y * other.y
     }
+
+    // Component-wise helpers, mirroring the shader built-ins
+    pub fn component_min(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x.min(other.x), self.y.min(other.y))
+    }
+
+    pub fn component_max(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x.max(other.x), self.y.max(other.y))
+    }
+
+    pub fn abs(self) -> Vec2 {
+        Vec2::new(self.x.abs(), self.y.abs())
+    }
+
+    pub fn floor(self) -> Vec2 {
+        Vec2::new(self.x.floor(), self.y.floor())
+    }
+
+    pub fn ceil(self) -> Vec2 {
+        Vec2::new(self.x.ceil(), self.y.ceil())
+    }
+
+    pub fn clamp(self, min: Vec2, max: Vec2) -> Vec2 {
+        self.component_max(min).component_min(max)
+    }
+
+    pub fn saturate(self) -> Vec2 {
+        self.clamp(Vec2::zero(), Vec2::new(1.0, 1.0))
+    }
+
+    // `t` is clamped to [0, 1]
+    pub fn lerp(self, other: Vec2, t: f64) -> Vec2 {
+        self + (other - self) * t.clamp(0.0, 1.0)
+    }
+
+    pub fn distance(self, other: Vec2) -> f64 {
+        (other - self).length()
+    }
 }
 
 impl Mul<f64> for Vec3 {
@@ -104,6 +152,64 @@ impl Vec3 {
             self.x * other.y - self.y * other.x,
         )
     }
+
+    // Component-wise helpers, mirroring the shader built-ins
+    pub fn component_min(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x.min(other.x), self.y.min(other.y), self.z.min(other.z))
+    }
+
+    pub fn component_max(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x.max(other.x), self.y.max(other.y), self.z.max(other.z))
+    }
+
+    pub fn abs(self) -> Vec3 {
+        Vec3::new(self.x.abs(), self.y.abs(), self.z.abs())
+    }
+
+    pub fn floor(self) -> Vec3 {
+        Vec3::new(self.x.floor(), self.y.floor(), self.z.floor())
+    }
+
+    pub fn ceil(self) -> Vec3 {
+        Vec3::new(self.x.ceil(), self.y.ceil(), self.z.ceil())
+    }
+
+    pub fn clamp(self, min: Vec3, max: Vec3) -> Vec3 {
+        self.component_max(min).component_min(max)
+    }
+
+    pub fn saturate(self) -> Vec3 {
+        self.clamp(Vec3::zero(), Vec3::new(1.0, 1.0, 1.0))
+    }
+
+    // `t` is clamped to [0, 1]
+    pub fn lerp(self, other: Vec3, t: f64) -> Vec3 {
+        self + (other - self) * t.clamp(0.0, 1.0)
+    }
+
+    // Mirrors the vector about a surface with the given unit normal
+    pub fn reflect(self, normal: Vec3) -> Vec3 {
+        self - normal * (2.0 * self.dot(&normal))
+    }
+
+    // Component of the vector along `onto`; zero when `onto` is zero
+    pub fn project(self, onto: Vec3) -> Vec3 {
+        let length_squared = onto.dot(&onto);
+        if length_squared == 0.0 {
+            return Vec3::zero();
+        }
+        onto * (self.dot(&onto) / length_squared)
+    }
+
+    // Angle in radians, 0 if either vector is zero
+    pub fn angle_between(&self, other: &Vec3) -> f64 {
+        let lengths = self.length() * other.length();
+        if lengths == 0.0 {
+            return 0.0;
+        }
+        // Rounding can push the cosine just outside acos' domain
+        (self.dot(other) / lengths).clamp(-1.0, 1.0).acos()
+    }
 }
 
 // Mat4 implementations
@@ -170,6 +276,43 @@ impl Mat4 {
         m
     }
 
+    // Combined translation * rotation * scale, using the same Z * Y * X Euler
+    // order as scene transforms
+    pub fn trs(translation: Vec3, rotation: Vec3, scale: Vec3) -> Self {
+        Self::translation(translation.x, translation.y, translation.z)
+            .multiply(&Self::rotation_z(rotation.z))
+            .multiply(&Self::rotation_y(rotation.y))
+            .multiply(&Self::rotation_x(rotation.x))
+            .multiply(&Self::scaling(scale.x, scale.y, scale.z))
+    }
+
+    // Same as trs, with the rotation given as a quaternion
+    pub fn trs_quat(translation: Vec3, rotation: Quaternion, scale: Vec3) -> Self {
+        Self::translation(translation.x, translation.y, translation.z)
+            .multiply(&rotation.to_mat4())
+            .multiply(&Self::scaling(scale.x, scale.y, scale.z))
+    }
+
+    // View matrix looking from `eye` towards `target`
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        let forward = (target - eye).normalize();
+        let right = forward.cross(&up).normalize();
+        let up = right.cross(&forward).normalize();
+
+        let mut rotation = Self::identity();
+        rotation.data[0][0] = right.x;
+        rotation.data[0][1] = right.y;
+        rotation.data[0][2] = right.z;
+        rotation.data[1][0] = up.x;
+        rotation.data[1][1] = up.y;
+        rotation.data[1][2] = up.z;
+        rotation.data[2][0] = -forward.x;
+        rotation.data[2][1] = -forward.y;
+        rotation.data[2][2] = -forward.z;
+
+        rotation.multiply(&Self::translation(-eye.x, -eye.y, -eye.z))
+    }
+
     pub fn multiply(&self, other: &Mat4) -> Mat4 {
         let mut result = [[0.0; 4]; 4];
         for i in 0..4 {
@@ -194,6 +337,156 @@ impl Mat4 {
             Vec3::new(x, y, z)
         }
     }
+
+    // Applies only the upper 3x3 part, for directions that ignore translation
+    pub fn transform_direction(&self, v: &Vec3) -> Vec3 {
+        let m = &self.data;
+        Vec3::new(
+            v.x * m[0][0] + v.y * m[0][1] + v.z * m[0][2],
+            v.x * m[1][0] + v.y * m[1][1] + v.z * m[1][2],
+            v.x * m[2][0] + v.y * m[2][1] + v.z * m[2][2],
+        )
+    }
+
+    pub fn transpose(&self) -> Mat4 {
+        let mut result = [[0.0; 4]; 4];
+        for (i, row) in self.data.iter().enumerate() {
+            for (j, &value) in row.iter().enumerate() {
+                result[j][i] = value;
+            }
+        }
+        Mat4::new(result)
+    }
+
+    // Determinant of the 3x3 matrix left after removing `row` and `col`
+    fn minor(&self, row: usize, col: usize) -> f64 {
+        let mut m = [[0.0; 3]; 3];
+        for (r, i) in (0..4).filter(|&i| i != row).enumerate() {
+            for (c, j) in (0..4).filter(|&j| j != col).enumerate() {
+                m[r][c] = self.data[i][j];
+            }
+        }
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    fn cofactor(&self, row: usize, col: usize) -> f64 {
+        let sign = if (row + col).is_multiple_of(2) { 1.0 } else { -1.0 };
+        sign * self.minor(row, col)
+    }
+
+    // Cofactor expansion along the first row. Also handy when debugging
+    // transforms: zero means degenerate, negative means mirrored.
+    pub fn determinant(&self) -> f64 {
+        (0..4).map(|j| self.data[0][j] * self.cofactor(0, j)).sum()
+    }
+
+    // Adjugate divided by the determinant; None for (near) singular matrices
+    pub fn inverse(&self) -> Option<Mat4> {
+        let det = self.determinant();
+        if det.abs() < 1e-12 {
+            return None;
+        }
+
+        let mut result = [[0.0; 4]; 4];
+        for (i, row) in result.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                // The adjugate is the transposed cofactor matrix
+                *value = self.cofactor(j, i) / det;
+            }
+        }
+        Some(Mat4::new(result))
+    }
+
+    // Debug check that M * M^T is the identity within 1e-6, i.e. the matrix
+    // only rotates or mirrors
+    pub fn is_orthogonal(&self) -> bool {
+        let product = self.multiply(&self.transpose());
+        (0..4).all(|i| (0..4).all(|j| {
+            let expected = if i == j { 1.0 } else { 0.0 };
+            (product.data[i][j] - expected).abs() < 1e-6
+        }))
+    }
+
+    // Inverse transpose, for transforming normals under non-uniform scale.
+    // Only the upper 3x3 part is meaningful.
+    pub fn normal_matrix(&self) -> Mat4 {
+        self.inverse().unwrap_or_else(Mat4::identity).transpose()
+    }
+}
+
+// Quaternion implementations
+impl Quaternion {
+    pub fn new(x: f64, y: f64, z: f64, w: f64) -> Self {
+        Self { x, y, z, w }
+    }
+
+    pub fn identity() -> Self {
+        Self::new(0.0, 0.0, 0.0, 1.0)
+    }
+
+    pub fn from_axis_angle(axis: Vec3, angle: f64) -> Self {
+        let axis = axis.normalize();
+        let (sin, cos) = (angle / 2.0).sin_cos();
+        Self::new(axis.x * sin, axis.y * sin, axis.z * sin, cos)
+    }
+
+    // Same Z * Y * X order as Mat4::trs
+    pub fn from_euler(angles: Vec3) -> Self {
+        Self::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), angles.z)
+            * Self::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), angles.y)
+            * Self::from_axis_angle(Vec3::new(1.0, 0.0, 0.0), angles.x)
+    }
+
+    pub fn dot(&self, other: &Quaternion) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    pub fn normalize(&self) -> Self {
+        let length = self.dot(self).sqrt();
+        if length != 0.0 {
+            Self::new(self.x / length, self.y / length, self.z / length, self.w / length)
+        } else {
+            Self::identity()
+        }
+    }
+
+    pub fn to_mat4(&self) -> Mat4 {
+        let Quaternion { x, y, z, w } = self.normalize();
+        Mat4::new([
+            [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - z * w), 2.0 * (x * z + y * w), 0.0],
+            [2.0 * (x * y + z * w), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - x * w), 0.0],
+            [2.0 * (x * z - y * w), 2.0 * (y * z + x * w), 1.0 - 2.0 * (x * x + y * y), 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    // Constant speed interpolation along the shorter arc
+    pub fn slerp(&self, other: &Quaternion, t: f64) -> Quaternion {
+        let mut other = *other;
+        let mut cos_theta = self.dot(&other);
+        if cos_theta < 0.0 {
+            other = Self::new(-other.x, -other.y, -other.z, -other.w);
+            cos_theta = -cos_theta;
+        }
+
+        // Nearly identical rotations: fall back to a normalized lerp
+        let (a, b) = if cos_theta > 0.9995 {
+            (1.0 - t, t)
+        } else {
+            let theta = cos_theta.acos();
+            let sin_theta = theta.sin();
+            (((1.0 - t) * theta).sin() / sin_theta, (t * theta).sin() / sin_theta)
+        };
+
+        Self::new(
+            self.x * a + other.x * b,
+            self.y * a + other.y * b,
+            self.z * a + other.z * b,
+            self.w * a + other.w * b,
+        ).normalize()
+    }
 }
 
 // Operator implementations for Vec2
@@ -211,6 +504,70 @@ impl Sub for Vec2 {
     }
 }
 
+impl Mul<f64> for Vec2 {
+    type Output = Self;
+    fn mul(self, scalar: f64) -> Self {
+        Self::new(self.x * scalar, self.y * scalar)
+    }
+}
+
+impl Mul<Vec2> for f64 {
+    type Output = Vec2;
+    fn mul(self, v: Vec2) -> Vec2 {
+        v * self
+    }
+}
+
+// Component-wise, e.g. scaling UVs per axis
+impl Mul for Vec2 {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        Self::new(self.x * other.x, self.y * other.y)
+    }
+}
+
+impl Div<f64> for Vec2 {
+    type Output = Self;
+    fn div(self, scalar: f64) -> Self {
+        Self::new(self.x / scalar, self.y / scalar)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, other: Self) {
+        self.x += other.x;
+        self.y += other.y;
+    }
+}
+
+impl SubAssign for Vec2 {
+    fn sub_assign(&mut self, other: Self) {
+        self.x -= other.x;
+        self.y -= other.y;
+    }
+}
+
+impl MulAssign<f64> for Vec2 {
+    fn mul_assign(&mut self, scalar: f64) {
+        self.x *= scalar;
+        self.y *= scalar;
+    }
+}
+
+impl DivAssign<f64> for Vec2 {
+    fn div_assign(&mut self, scalar: f64) {
+        self.x /= scalar;
+        self.y /= scalar;
+    }
+}
+
 // Operator implementations for Vec3
 impl Add for Vec3 {
     type Output = Self;
@@ -231,6 +588,86 @@ impl Sub for Vec3 {
     }
 }
 
+impl Neg for Vec3 {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl AddAssign for Vec3 {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Vec3 {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign<f64> for Vec3 {
+    fn mul_assign(&mut self, scalar: f64) {
+        *self = *self * scalar;
+    }
+}
+
+impl DivAssign<f64> for Vec3 {
+    fn div_assign(&mut self, scalar: f64) {
+        *self = *self / scalar;
+    }
+}
+
+// Operator implementations for Mat4
+impl Mul for Mat4 {
+    type Output = Mat4;
+    fn mul(self, other: Mat4) -> Mat4 {
+        self.multiply(&other)
+    }
+}
+
+impl Mul<&Mat4> for &Mat4 {
+    type Output = Mat4;
+    fn mul(self, other: &Mat4) -> Mat4 {
+        self.multiply(other)
+    }
+}
+
+impl Mul<Vec3> for Mat4 {
+    type Output = Vec3;
+    fn mul(self, v: Vec3) -> Vec3 {
+        self.transform_vec3(&v)
+    }
+}
+
+// Composition: (a * b) applies b first, then a
+impl Mul for Quaternion {
+    type Output = Self;
+    fn mul(self, o: Self) -> Self {
+        Self::new(
+            self.w * o.x + self.x * o.w + self.y * o.z - self.z * o.y,
+            self.w * o.y - self.x * o.z + self.y * o.w + self.z * o.x,
+            self.w * o.z + self.x * o.y - self.y * o.x + self.z * o.w,
+            self.w * o.w - self.x * o.x - self.y * o.y - self.z * o.z,
+        )
+    }
+}
+
+// Indexed as (row, column)
+impl Index<(usize, usize)> for Mat4 {
+    type Output = f64;
+    fn index(&self, (row, col): (usize, usize)) -> &f64 {
+        &self.data[row][col]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Mat4 {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut f64 {
+        &mut self.data[row][col]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -246,6 +683,10 @@ mod tests {
 
         let normalized = v1.normalize();
         assert!((normalized.length() - 1.0).abs() < 1e-10);
+
+        assert_eq!(v1.distance(v2), 8.0_f64.sqrt());
+        assert_eq!(v2.lerp(v1, 0.5), Vec2::new(2.0, 3.0));
+        assert_eq!(v2.lerp(v1, 1.5), v1);
     }
 
     #[test]
@@ -257,6 +698,75 @@ mod tests {
         assert_eq!(cross, Vec3::new(0.0, 0.0, 1.0));
     }
 
+    #[test]
+    fn test_operator_overloads() {
+        let a = Mat4::rotation_x(0.3).multiply(&Mat4::translation(1.0, 2.0, 3.0));
+        let b = Mat4::scaling(2.0, 3.0, 4.0);
+        assert_eq!(a * b, a.multiply(&b));
+        let (ref_a, ref_b) = (&a, &b);
+        assert_eq!(ref_a * ref_b, a.multiply(&b));
+
+        let v = Vec3::new(1.0, 2.0, 3.0);
+        assert_eq!(a * v, a.transform_vec3(&v));
+        assert_eq!(-v, Vec3::new(-1.0, -2.0, -3.0));
+
+        let mut m = Mat4::identity();
+        m[(0, 3)] = 5.0;
+        assert_eq!(m[(0, 3)], 5.0);
+        assert_eq!(m, Mat4::translation(5.0, 0.0, 0.0));
+
+        let mut w = v;
+        w += Vec3::new(1.0, 1.0, 1.0);
+        w *= 2.0;
+        w -= Vec3::new(0.0, 0.0, 8.0);
+        w /= 2.0;
+        assert_eq!(w, Vec3::new(2.0, 3.0, 0.0));
+
+        let mut u = 2.0 * Vec2::new(1.0, -1.0);
+        assert_eq!(u, Vec2::new(1.0, -1.0) * 2.0);
+        u += -Vec2::new(1.0, 1.0);
+        u /= 2.0;
+        assert_eq!(u, Vec2::new(0.5, -1.5));
+        assert_eq!(u / 0.5, Vec2::new(1.0, -3.0));
+        assert_eq!(u * Vec2::new(2.0, 3.0), Vec2::new(1.0, -4.5));
+    }
+
+    #[test]
+    fn test_component_wise_operations() {
+        let v = Vec3::new(1.0, -1.0, 2.0);
+        assert_eq!(v.component_min(Vec3::zero()), Vec3::new(0.0, -1.0, 0.0));
+        assert_eq!(v.component_max(Vec3::zero()), Vec3::new(1.0, 0.0, 2.0));
+        assert_eq!(v.abs(), Vec3::new(1.0, 1.0, 2.0));
+        assert_eq!(Vec3::new(-0.5, 0.5, 1.5).saturate(), Vec3::new(0.0, 0.5, 1.0));
+        assert_eq!(Vec3::new(-0.5, 0.5, 1.5).floor(), Vec3::new(-1.0, 0.0, 1.0));
+        assert_eq!(Vec3::new(-0.5, 0.5, 1.5).ceil(), Vec3::new(-0.0, 1.0, 2.0));
+
+        let w = Vec2::new(-0.5, 1.5);
+        assert_eq!(w.saturate(), Vec2::new(0.0, 1.0));
+        assert_eq!(w.abs(), Vec2::new(0.5, 1.5));
+        assert_eq!(w.clamp(Vec2::new(0.0, 0.0), Vec2::new(1.0, 1.0)), w.saturate());
+    }
+
+    #[test]
+    fn test_vec3_interpolation_and_projection() {
+        let a = Vec3::new(0.0, 0.0, 0.0);
+        let b = Vec3::new(2.0, 4.0, -2.0);
+        assert_eq!(a.lerp(b, 0.5), Vec3::new(1.0, 2.0, -1.0));
+        assert_eq!(a.lerp(b, 2.0), b);
+        assert_eq!(a.lerp(b, -1.0), a);
+
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        assert_eq!(Vec3::new(1.0, -1.0, 0.0).reflect(up), Vec3::new(1.0, 1.0, 0.0));
+
+        assert_eq!(Vec3::new(3.0, 4.0, 5.0).project(Vec3::new(0.0, 2.0, 0.0)), Vec3::new(0.0, 4.0, 0.0));
+        assert_eq!(b.project(Vec3::zero()), Vec3::zero());
+
+        assert!((up.angle_between(&Vec3::new(1.0, 0.0, 0.0)) - PI / 2.0).abs() < 1e-12);
+        let v = Vec3::new(0.1, 0.2, 0.3);
+        assert!(v.angle_between(&(v * 3.0)) < 1e-6);
+        assert!((v.angle_between(&-v) - PI).abs() < 1e-12);
+    }
+
     #[test]
     fn test_matrix_operations() {
         let translation = Mat4::translation(1.0, 2.0, 3.0);
@@ -272,4 +782,101 @@ mod tests {
         assert!((rotated.x - 0.0).abs() < 1e-10);
         assert!((rotated.z + 1.0).abs() < 1e-10);
     }
+
+    #[test]
+    fn test_quaternion() {
+        let angles = Vec3::new(0.4, -1.2, 2.5);
+        let from_euler = Quaternion::from_euler(angles).to_mat4();
+        let expected = Mat4::trs(Vec3::zero(), angles, Vec3::new(1.0, 1.0, 1.0));
+        for i in 0..4 {
+            for j in 0..4 {
+                assert!((from_euler.data[i][j] - expected.data[i][j]).abs() < 1e-10);
+            }
+        }
+
+        // Two quarter turns compose into a half turn
+        let quarter = Quaternion::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), PI / 2.0);
+        let half = (quarter * quarter).to_mat4().transform_vec3(&Vec3::new(1.0, 0.0, 0.0));
+        assert!((half - Vec3::new(-1.0, 0.0, 0.0)).length() < 1e-10);
+
+        let midway = Quaternion::identity().slerp(&quarter, 0.5);
+        let expected = Quaternion::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), PI / 4.0);
+        assert!((midway.dot(&expected) - 1.0).abs() < 1e-10);
+
+        // Slerp from straight down to straight up passes the horizon, not a pole flip
+        let down = Quaternion::from_axis_angle(Vec3::new(1.0, 0.0, 0.0), -PI / 2.0 + 0.01);
+        let up = Quaternion::from_axis_angle(Vec3::new(1.0, 0.0, 0.0), PI / 2.0 - 0.01);
+        let forward = down.slerp(&up, 0.5).to_mat4().transform_vec3(&Vec3::new(0.0, 0.0, -1.0));
+        assert!((forward - Vec3::new(0.0, 0.0, -1.0)).length() < 1e-10);
+
+        let trs = Mat4::trs_quat(Vec3::new(1.0, 2.0, 3.0), quarter, Vec3::new(2.0, 2.0, 2.0));
+        let p = trs.transform_vec3(&Vec3::new(1.0, 0.0, 0.0));
+        assert!((p - Vec3::new(1.0, 2.0, 1.0)).length() < 1e-10);
+    }
+
+    #[test]
+    fn test_matrix_inverse() {
+        let m = Mat4::trs(Vec3::new(1.0, -2.0, 3.0), Vec3::new(0.3, 0.7, -1.1), Vec3::new(2.0, 0.5, 3.0));
+        let product = m.multiply(&m.inverse().unwrap());
+        for i in 0..4 {
+            for j in 0..4 {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((product.data[i][j] - expected).abs() < 1e-10);
+            }
+        }
+
+        assert!(Mat4::scaling(1.0, 0.0, 1.0).inverse().is_none());
+        assert!((Mat4::scaling(2.0, 3.0, 4.0).determinant() - 24.0).abs() < 1e-10);
+        assert_eq!(m.transpose().transpose(), m);
+        assert_eq!(m.transpose().data[0][3], m.data[3][0]);
+
+        // Normals stay perpendicular to a surface squashed along Y
+        let squash = Mat4::scaling(1.0, 0.25, 1.0);
+        let tangent = squash.transform_direction(&Vec3::new(1.0, 1.0, 0.0));
+        let normal = squash.normal_matrix().transform_direction(&Vec3::new(1.0, -1.0, 0.0));
+        assert!(tangent.dot(&normal).abs() < 1e-10);
+        let direction = Mat4::translation(5.0, 5.0, 5.0).transform_direction(&Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(direction, Vec3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn test_determinant_and_orthogonality() {
+        assert_eq!(Mat4::identity().determinant(), 1.0);
+        assert!(Mat4::identity().is_orthogonal());
+
+        let mirrored = Mat4::scaling(2.0, -1.0, 3.0);
+        assert!((mirrored.determinant() + 6.0).abs() < 1e-10);
+        assert!(!mirrored.is_orthogonal());
+        assert!(Mat4::scaling(1.0, -1.0, 1.0).is_orthogonal());
+
+        let zero = Mat4::new([[0.0; 4]; 4]);
+        assert_eq!(zero.determinant(), 0.0);
+        assert!(!zero.is_orthogonal());
+
+        let rotation = Mat4::rotation_x(0.4) * Mat4::rotation_y(-1.2);
+        assert!(rotation.is_orthogonal());
+        assert!((rotation.determinant() - 1.0).abs() < 1e-10);
+        assert!(!Mat4::translation(1.0, 0.0, 0.0).is_orthogonal());
+    }
+
+    #[test]
+    fn test_trs_and_look_at() {
+        let v = Vec3::new(1.0, -2.0, 3.0);
+        let trs = Mat4::trs(v, Vec3::zero(), Vec3::new(1.0, 1.0, 1.0));
+        assert_eq!(trs, Mat4::translation(v.x, v.y, v.z));
+
+        let eye = Vec3::new(2.0, 3.0, -5.0);
+        let view = Mat4::look_at(eye, Vec3::zero(), Vec3::new(0.0, 1.0, 0.0));
+        let eye_in_view = view.transform_vec3(&eye);
+        assert!(eye_in_view.length() < 1e-10);
+    }
+
+    #[test]
+    fn test_look_at_forward() {
+        let view = Mat4::look_at(Vec3::new(0.0, 0.0, -5.0), Vec3::zero(), Vec3::new(0.0, 1.0, 0.0));
+        // The third row holds -forward, following gluLookAt
+        let forward = -Vec3::new(view.data[2][0], view.data[2][1], view.data[2][2]);
+        assert!((forward - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-12);
+        assert!((view.transform_vec3(&Vec3::zero()) - Vec3::new(0.0, 0.0, -5.0)).length() < 1e-12);
+    }
 }
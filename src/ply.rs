@@ -0,0 +1,444 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use crate::geometry::{Mesh, Vertex};
+use crate::math::{Vec2, Vec3};
+
+#[derive(Debug)]
+pub enum PlyError {
+    Io(io::Error),
+    InvalidHeader(String),
+    UnsupportedFormat(String),
+    InvalidData(String),
+    MissingProperty(String),
+}
+
+impl fmt::Display for PlyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlyError::Io(err) => write!(f, "I/O error: {}", err),
+            PlyError::InvalidHeader(msg) => write!(f, "invalid PLY header: {}", msg),
+            PlyError::UnsupportedFormat(format) => write!(f, "unsupported PLY format: {}", format),
+            PlyError::InvalidData(msg) => write!(f, "invalid PLY data: {}", msg),
+            PlyError::MissingProperty(name) => write!(f, "missing PLY vertex property: {}", name),
+        }
+    }
+}
+
+impl std::error::Error for PlyError {}
+
+impl From<io::Error> for PlyError {
+    fn from(err: io::Error) -> Self {
+        PlyError::Io(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScalarType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64,
+}
+
+impl ScalarType {
+    fn from_name(name: &str) -> Result<Self, PlyError> {
+        match name {
+            "char" | "int8" => Ok(ScalarType::Int8),
+            "uchar" | "uint8" => Ok(ScalarType::UInt8),
+            "short" | "int16" => Ok(ScalarType::Int16),
+            "ushort" | "uint16" => Ok(ScalarType::UInt16),
+            "int" | "int32" => Ok(ScalarType::Int32),
+            "uint" | "uint32" => Ok(ScalarType::UInt32),
+            "float" | "float32" => Ok(ScalarType::Float32),
+            "double" | "float64" => Ok(ScalarType::Float64),
+            _ => Err(PlyError::InvalidHeader(format!("unknown property type '{}'", name))),
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            ScalarType::Int8 | ScalarType::UInt8 => 1,
+            ScalarType::Int16 | ScalarType::UInt16 => 2,
+            ScalarType::Int32 | ScalarType::UInt32 | ScalarType::Float32 => 4,
+            ScalarType::Float64 => 8,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum PropertyType {
+    Scalar(ScalarType),
+    List { count: ScalarType, item: ScalarType },
+}
+
+#[derive(Debug)]
+struct Property {
+    name: String,
+    kind: PropertyType,
+}
+
+#[derive(Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+impl Element {
+    fn property_index(&self, name: &str) -> Option<usize> {
+        self.properties.iter().position(|p| p.name == name)
+    }
+}
+
+// Element data, either whitespace separated text or packed little-endian values
+enum Body<'a> {
+    Ascii(std::str::SplitAsciiWhitespace<'a>),
+    Binary { data: &'a [u8], pos: usize },
+}
+
+impl Body<'_> {
+    fn read(&mut self, ty: ScalarType) -> Result<f64, PlyError> {
+        match self {
+            Body::Ascii(tokens) => {
+                let token = tokens.next()
+                    .ok_or_else(|| PlyError::InvalidData("unexpected end of data".to_string()))?;
+                token.parse::<f64>()
+                    .map_err(|_| PlyError::InvalidData(format!("invalid number '{}'", token)))
+            }
+            Body::Binary { data, pos } => {
+                let bytes = data.get(*pos..*pos + ty.size())
+                    .ok_or_else(|| PlyError::InvalidData("unexpected end of data".to_string()))?;
+                *pos += ty.size();
+                Ok(match ty {
+                    ScalarType::Int8 => bytes[0] as i8 as f64,
+                    ScalarType::UInt8 => bytes[0] as f64,
+                    ScalarType::Int16 => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+                    ScalarType::UInt16 => u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+                    ScalarType::Int32 => i32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+                    ScalarType::UInt32 => u32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+                    ScalarType::Float32 => f32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+                    ScalarType::Float64 => f64::from_le_bytes(bytes.try_into().unwrap()),
+                })
+            }
+        }
+    }
+
+    // Reads one element item; scalar properties yield a single value
+    fn read_item(&mut self, element: &Element) -> Result<Vec<Vec<f64>>, PlyError> {
+        element.properties.iter()
+            .map(|property| match property.kind {
+                PropertyType::Scalar(ty) => Ok(vec![self.read(ty)?]),
+                PropertyType::List { count, item } => {
+                    let len = self.read(count)? as usize;
+                    (0..len).map(|_| self.read(item)).collect()
+                }
+            })
+            .collect()
+    }
+}
+
+// Splits the file into its header elements, whether it is binary, and the body
+fn parse_header(bytes: &[u8]) -> Result<(Vec<Element>, bool, &[u8]), PlyError> {
+    const END: &[u8] = b"end_header";
+    let end = bytes.windows(END.len())
+        .position(|window| window == END)
+        .ok_or_else(|| PlyError::InvalidHeader("missing end_header".to_string()))?;
+    let body_start = bytes[end..].iter()
+        .position(|&b| b == b'\n')
+        .map(|offset| end + offset + 1)
+        .unwrap_or(bytes.len());
+
+    let header = std::str::from_utf8(&bytes[..end])
+        .map_err(|_| PlyError::InvalidHeader("header is not valid text".to_string()))?;
+    let mut lines = header.lines().map(str::trim);
+    if lines.next() != Some("ply") {
+        return Err(PlyError::InvalidHeader("missing 'ply' magic".to_string()));
+    }
+
+    let mut binary = None;
+    let mut elements: Vec<Element> = Vec::new();
+    for line in lines {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] | ["comment", ..] | ["obj_info", ..] => {}
+            ["format", "ascii", _] => binary = Some(false),
+            ["format", "binary_little_endian", _] => binary = Some(true),
+            ["format", format, ..] => return Err(PlyError::UnsupportedFormat(format.to_string())),
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count.parse()
+                    .map_err(|_| PlyError::InvalidHeader(format!("invalid element count '{}'", count)))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count, item, name] => {
+                let element = elements.last_mut()
+                    .ok_or_else(|| PlyError::InvalidHeader("property before element".to_string()))?;
+                element.properties.push(Property {
+                    name: name.to_string(),
+                    kind: PropertyType::List {
+                        count: ScalarType::from_name(count)?,
+                        item: ScalarType::from_name(item)?,
+                    },
+                });
+            }
+            ["property", ty, name] => {
+                let element = elements.last_mut()
+                    .ok_or_else(|| PlyError::InvalidHeader("property before element".to_string()))?;
+                element.properties.push(Property {
+                    name: name.to_string(),
+                    kind: PropertyType::Scalar(ScalarType::from_name(ty)?),
+                });
+            }
+            _ => return Err(PlyError::InvalidHeader(format!("unexpected line '{}'", line))),
+        }
+    }
+
+    let binary = binary.ok_or_else(|| PlyError::InvalidHeader("missing format line".to_string()))?;
+    Ok((elements, binary, &bytes[body_start..]))
+}
+
+// PLY import and export
+impl Mesh {
+    // Loads ASCII or binary little-endian PLY files. Normals are generated when
+    // absent, texture coordinates default to zero, and polygons are fanned
+    // into triangles.
+    pub fn from_ply(path: &str) -> Result<Mesh, PlyError> {
+        let bytes = fs::read(path)?;
+        let (elements, binary, data) = parse_header(&bytes)?;
+
+        let mut body = if binary {
+            Body::Binary { data, pos: 0 }
+        } else {
+            let text = std::str::from_utf8(data)
+                .map_err(|_| PlyError::InvalidData("ASCII body is not valid text".to_string()))?;
+            Body::Ascii(text.split_ascii_whitespace())
+        };
+
+        let mut mesh = Mesh::new();
+        let mut has_normals = false;
+        let mut polygons: Vec<Vec<f64>> = Vec::new();
+
+        for element in &elements {
+            match element.name.as_str() {
+                "vertex" => {
+                    let required = |name: &str| {
+                        element.property_index(name).ok_or_else(|| PlyError::MissingProperty(name.to_string()))
+                    };
+                    let (x, y, z) = (required("x")?, required("y")?, required("z")?);
+                    let normal = match ["nx", "ny", "nz"].map(|name| element.property_index(name)) {
+                        [Some(nx), Some(ny), Some(nz)] => Some((nx, ny, nz)),
+                        _ => None,
+                    };
+                    let uv = element.property_index("s").or_else(|| element.property_index("u"))
+                        .zip(element.property_index("t").or_else(|| element.property_index("v")));
+                    has_normals = normal.is_some();
+
+                    for _ in 0..element.count {
+                        let values = body.read_item(element)?;
+                        // List properties may be empty
+                        let value = |i: usize| values[i].first().copied().ok_or_else(|| {
+                            PlyError::InvalidData(format!("vertex property {} has no value", element.properties[i].name))
+                        });
+                        let normal = match normal {
+                            Some((nx, ny, nz)) => Vec3::new(value(nx)?, value(ny)?, value(nz)?),
+                            None => Vec3::zero(),
+                        };
+                        let uv = match uv {
+                            Some((s, t)) => Vec2::new(value(s)?, value(t)?),
+                            None => Vec2::zero(),
+                        };
+                        mesh.add_vertex(Vertex::new(Vec3::new(value(x)?, value(y)?, value(z)?), normal, uv));
+                    }
+                }
+                "face" => {
+                    let indices = element.property_index("vertex_indices")
+                        .or_else(|| element.property_index("vertex_index"))
+                        .ok_or_else(|| PlyError::InvalidData("face element without vertex_indices".to_string()))?;
+                    for _ in 0..element.count {
+                        let mut values = body.read_item(element)?;
+                        polygons.push(std::mem::take(&mut values[indices]));
+                    }
+                }
+                _ => {
+                    for _ in 0..element.count {
+                        body.read_item(element)?;
+                    }
+                }
+            }
+        }
+
+        for polygon in polygons {
+            // Reject negative, fractional and NaN indices rather than casting them
+            let indices = polygon.iter()
+                .map(|&i| if i >= 0.0 && i.fract() == 0.0 {
+                    Ok(i as usize)
+                } else {
+                    Err(PlyError::InvalidData(format!("invalid vertex index {}", i)))
+                })
+                .collect::<Result<Vec<usize>, _>>()?;
+            if let Some(&bad) = indices.iter().find(|&&i| i >= mesh.vertices.len()) {
+                return Err(PlyError::InvalidData(format!("face references missing vertex {}", bad)));
+            }
+            for i in 1..indices.len().saturating_sub(1) {
+                mesh.add_face([indices[0], indices[i], indices[i + 1]]);
+            }
+        }
+
+        if !has_normals {
+            mesh.generate_vertex_normals();
+        }
+        Ok(mesh)
+    }
+
+    // Writes transformed positions, normals and texture coordinates as
+    // single precision floats, in ASCII or binary little-endian form
+    pub fn to_ply(&self, path: &str, binary: bool) -> Result<(), PlyError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let vertices = self.get_transformed_vertices();
+
+        writeln!(writer, "ply")?;
+        writeln!(writer, "format {} 1.0", if binary { "binary_little_endian" } else { "ascii" })?;
+        writeln!(writer, "element vertex {}", vertices.len())?;
+        for name in ["x", "y", "z", "nx", "ny", "nz", "s", "t"] {
+            writeln!(writer, "property float {}", name)?;
+        }
+        writeln!(writer, "element face {}", self.faces.len())?;
+        writeln!(writer, "property list uchar int vertex_indices")?;
+        writeln!(writer, "end_header")?;
+
+        for v in &vertices {
+            let values = [
+                v.position.x, v.position.y, v.position.z,
+                v.normal.x, v.normal.y, v.normal.z,
+                v.uv.x, v.uv.y,
+            ].map(|value| value as f32);
+            if binary {
+                for value in values {
+                    writer.write_all(&value.to_le_bytes())?;
+                }
+            } else {
+                let line: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+                writeln!(writer, "{}", line.join(" "))?;
+            }
+        }
+
+        for face in &self.faces {
+            if binary {
+                writer.write_all(&[3])?;
+                for index in face.vertices {
+                    writer.write_all(&(index as i32).to_le_bytes())?;
+                }
+            } else {
+                let [a, b, c] = face.vertices;
+                writeln!(writer, "3 {} {} {}", a, b, c)?;
+            }
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quad() -> Mesh {
+        let mut mesh = Mesh::new();
+        let normal = Vec3::new(0.0, 0.0, 1.0);
+        mesh.add_vertex(Vertex::new(Vec3::new(0.0, 0.0, 0.0), normal, Vec2::new(0.0, 0.0)));
+        mesh.add_vertex(Vertex::new(Vec3::new(1.0, 0.0, 0.0), normal, Vec2::new(1.0, 0.0)));
+        mesh.add_vertex(Vertex::new(Vec3::new(1.0, 2.0, 0.0), normal, Vec2::new(1.0, 1.0)));
+        mesh.add_vertex(Vertex::new(Vec3::new(0.0, 2.0, 0.5), normal, Vec2::new(0.0, 1.0)));
+        mesh.add_face([0, 1, 2]);
+        mesh.add_face([0, 2, 3]);
+        mesh
+    }
+
+    fn assert_same_mesh(a: &Mesh, b: &Mesh) {
+        assert_eq!(a.vertices.len(), b.vertices.len());
+        for (va, vb) in a.vertices.iter().zip(&b.vertices) {
+            assert!((va.position - vb.position).length() < 1e-6);
+            assert!((va.uv - vb.uv).length() < 1e-6);
+        }
+        let indices = |mesh: &Mesh| mesh.faces.iter().map(|f| f.vertices).collect::<Vec<_>>();
+        assert_eq!(indices(a), indices(b));
+    }
+
+    #[test]
+    fn test_ply_round_trip() {
+        let mesh = quad();
+        for binary in [false, true] {
+            let path = std::env::temp_dir().join(format!("ironsight_ply_test_{}.ply", binary));
+            let path = path.to_str().unwrap();
+            mesh.to_ply(path, binary).unwrap();
+            let loaded = Mesh::from_ply(path).unwrap();
+            std::fs::remove_file(path).unwrap();
+            assert_same_mesh(&mesh, &loaded);
+        }
+    }
+
+    #[test]
+    fn test_binary_ply_with_polygon() {
+        // Hand-packed file: positions only, one quad face, one extra element
+        let mut bytes = b"ply\nformat binary_little_endian 1.0\ncomment made by hand\n\
+            element vertex 4\nproperty float x\nproperty float y\nproperty double z\n\
+            element face 1\nproperty list uchar uint vertex_indices\n\
+            element material 1\nproperty uchar red\nend_header\n".to_vec();
+        for (x, y) in [(0.0f32, 0.0f32), (1.0, 0.0), (1.0, 2.0), (0.0, 2.0)] {
+            bytes.extend(x.to_le_bytes());
+            bytes.extend(y.to_le_bytes());
+            bytes.extend(0.5f64.to_le_bytes());
+        }
+        bytes.push(4);
+        for index in [0u32, 1, 2, 3] {
+            bytes.extend(index.to_le_bytes());
+        }
+        bytes.push(255);
+
+        let path = std::env::temp_dir().join("ironsight_ply_binary_asset.ply");
+        std::fs::write(&path, &bytes).unwrap();
+        let mesh = Mesh::from_ply(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.vertices[2].position, Vec3::new(1.0, 2.0, 0.5));
+        assert_eq!(mesh.faces.iter().map(|f| f.vertices).collect::<Vec<_>>(), vec![[0, 1, 2], [0, 2, 3]]);
+        // Normals were generated from the faces
+        assert!((mesh.vertices[0].normal - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-10);
+    }
+
+    #[test]
+    fn test_ply_errors() {
+        let path = std::env::temp_dir().join("ironsight_ply_bad.ply");
+        let path_str = path.to_str().unwrap();
+
+        std::fs::write(&path, "ply\nformat binary_big_endian 1.0\nend_header\n").unwrap();
+        assert!(matches!(Mesh::from_ply(path_str), Err(PlyError::UnsupportedFormat(_))));
+
+        std::fs::write(&path, "ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\nend_header\n1\n").unwrap();
+        assert!(matches!(Mesh::from_ply(path_str), Err(PlyError::MissingProperty(_))));
+
+        std::fs::write(&path, "ply\nformat ascii 1.0\nelement vertex 1\nproperty list uchar float x\n\
+            property float y\nproperty float z\nend_header\n0 1 2\n").unwrap();
+        assert!(matches!(Mesh::from_ply(path_str), Err(PlyError::InvalidData(_))));
+
+        let triangle = "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nproperty float y\n\
+            property float z\nelement face 1\nproperty list uchar int vertex_indices\nend_header\n\
+            0 0 0\n1 0 0\n0 1 0\n";
+        for face in ["3 0 -1 2", "3 0 nan 2"] {
+            std::fs::write(&path, format!("{}{}\n", triangle, face)).unwrap();
+            assert!(matches!(Mesh::from_ply(path_str), Err(PlyError::InvalidData(_))));
+        }
+        std::fs::write(&path, format!("{}3 0 1 2\n", triangle)).unwrap();
+        assert_eq!(Mesh::from_ply(path_str).unwrap().faces.len(), 1);
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(Mesh::from_ply(path_str), Err(PlyError::Io(_))));
+    }
+}
@@ -1,6 +1,9 @@
-use crate::math::Vec2;
+use crate::camera::ProjectionMode;
+use crate::math::{Vec2, Vec3};
+use crate::texture::Texture;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -21,16 +24,351 @@ impl Color {
         Self::new(0, 0, 0, 255)
     }
 
+    // Opaque color from hue in degrees (wrapped into [0, 360)), saturation and value in [0, 1]
+    pub fn from_hsv(h: f64, s: f64, v: f64) -> Self {
+        let h = h.rem_euclid(360.0) / 60.0;
+        let (s, v) = (s.clamp(0.0, 1.0), v.clamp(0.0, 1.0));
+        let c = v * s;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let m = v - c;
+        let to_u8 = |value: f64| ((value + m) * 255.0).round() as u8;
+        Self::new(to_u8(r), to_u8(g), to_u8(b), 255)
+    }
+
+    // Inverse of from_hsv; alpha is ignored
+    pub fn to_hsv(&self) -> (f64, f64, f64) {
+        let [r, g, b] = [self.r, self.g, self.b].map(|c| c as f64 / 255.0);
+        let max = r.max(g).max(b);
+        let delta = max - r.min(g).min(b);
+        let h = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        let s = if max == 0.0 { 0.0 } else { delta / max };
+        (h, s, max)
+    }
+
+    // 0xRRGGBBAA, as colors are usually written
+    pub fn from_hex(hex: u32) -> Self {
+        let [r, g, b, a] = hex.to_be_bytes();
+        Self::new(r, g, b, a)
+    }
+
+    // Blend towards `other`, with t clamped to [0, 1]
+    pub fn lerp(&self, other: Color, t: f64) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        Color::interpolate([*self, other, other], [1.0 - t, t, 0.0])
+    }
+
+    // Channel-wise product, e.g. a texture sample tinted by a vertex color
+    pub fn modulate(&self, other: Color) -> Color {
+        let mul = |a: u8, b: u8| ((a as u32 * b as u32 + 127) / 255) as u8;
+        Color::new(mul(self.r, other.r), mul(self.g, other.g), mul(self.b, other.b), mul(self.a, other.a))
+    }
+
+    // Weighted blend of three colors, e.g. by barycentric coordinates
+    pub fn interpolate(colors: [Color; 3], weights: [f64; 3]) -> Color {
+        let channel = |get: fn(&Color) -> u8| {
+            let value: f64 = colors.iter().zip(weights).map(|(c, w)| get(c) as f64 * w).sum();
+            value.round().clamp(0.0, 255.0) as u8
+        };
+        Color::new(channel(|c| c.r), channel(|c| c.g), channel(|c| c.b), channel(|c| c.a))
+    }
+
     pub fn to_u32(&self) -> u32 {
         ((self.a as u32) << 24) | ((self.b as u32) << 16) | ((self.g as u32) << 8) | (self.r as u32)
     }
+
+    pub fn to_u32_argb(&self) -> u32 {
+        self.to_u32_as(PixelFormat::ARGB)
+    }
+
+    pub fn to_u32_rgba(&self) -> u32 {
+        self.to_u32_as(PixelFormat::RGBA)
+    }
+
+    pub fn to_u32_bgra(&self) -> u32 {
+        self.to_u32_as(PixelFormat::BGRA)
+    }
+
+    pub fn to_u32_as(&self, format: PixelFormat) -> u32 {
+        let [r, g, b, a] = [self.r, self.g, self.b, self.a].map(|c| c as u32);
+        match format {
+            PixelFormat::ARGB => (a << 24) | (r << 16) | (g << 8) | b,
+            PixelFormat::RGBA => (r << 24) | (g << 16) | (b << 8) | a,
+            PixelFormat::BGRA => (b << 24) | (g << 16) | (r << 8) | a,
+            PixelFormat::ABGR => self.to_u32(),
+        }
+    }
+
+    // Inverse of to_u32
+    pub fn from_u32(value: u32) -> Self {
+        Self::new(
+            (value & 0xFF) as u8,
+            ((value >> 8) & 0xFF) as u8,
+            ((value >> 16) & 0xFF) as u8,
+            ((value >> 24) & 0xFF) as u8,
+        )
+    }
+}
+
+// Channel order of a packed pixel, from the most to the least significant
+// byte. The color buffer itself is ABGR, see Color::to_u32.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum PixelFormat {
+    ARGB,
+    RGBA,
+    BGRA,
+    ABGR,
+}
+
+// Operator mapping HDR colors into [0, 1] before they are stored as Color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ToneMapping {
+    // Values above 1.0 are clipped
+    #[default]
+    None,
+    Reinhard,
+    Aces,
+}
+
+// Floating point color for HDR values above 1.0
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorF {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl ColorF {
+    pub fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    pub fn from_color(c: Color) -> Self {
+        Self::new(
+            c.r as f32 / 255.0,
+            c.g as f32 / 255.0,
+            c.b as f32 / 255.0,
+            c.a as f32 / 255.0,
+        )
+    }
+
+    pub fn to_color_clamped(&self) -> Color {
+        let channel = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        Color::new(channel(self.r), channel(self.g), channel(self.b), channel(self.a))
+    }
+
+    // Applies `f` to the color channels, leaving alpha alone
+    fn map_rgb(&self, f: impl Fn(f32) -> f32) -> Self {
+        Self::new(f(self.r), f(self.g), f(self.b), self.a)
+    }
+
+    pub fn tone_map(&self, mapping: ToneMapping) -> ColorF {
+        match mapping {
+            ToneMapping::None => *self,
+            ToneMapping::Reinhard => self.tone_map_reinhard(),
+            ToneMapping::Aces => self.tone_map_aces(),
+        }
+    }
+
+    pub fn tone_map_reinhard(&self) -> ColorF {
+        self.map_rgb(|x| x / (1.0 + x))
+    }
+
+    // Narkowicz's fit of the ACES filmic curve, with the 0.6 exposure scale
+    // that matches the reference transform's overall brightness
+    pub fn tone_map_aces(&self) -> ColorF {
+        self.map_rgb(|x| {
+            let x = x * 0.6;
+            ((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)).clamp(0.0, 1.0)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthTestMode {
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    Always,
+    Never,
+}
+
+impl DepthTestMode {
+    pub fn passes(&self, z: f64, stored: f64) -> bool {
+        match self {
+            DepthTestMode::Less => z < stored,
+            DepthTestMode::LessEqual => z <= stored,
+            DepthTestMode::Greater => z > stored,
+            DepthTestMode::GreaterEqual => z >= stored,
+            DepthTestMode::Equal => z == stored,
+            DepthTestMode::Always => true,
+            DepthTestMode::Never => false,
+        }
+    }
+}
+
+// Per-pixel depth values with the test used to accept new fragments. Stored
+// values are the NDC z passed to set_pixel; `near`/`far` and the projection
+// describe the camera so they can be read back as linear depth.
+#[derive(Debug, Clone)]
+pub struct DepthBuffer {
+    values: Vec<f64>,
+    test_mode: DepthTestMode,
+    clear_value: f64,
+    pub near: f64,
+    pub far: f64,
+    pub projection_mode: ProjectionMode,
+}
+
+impl DepthBuffer {
+    pub fn new(size: usize, near: f64, far: f64) -> Self {
+        Self {
+            values: vec![f64::INFINITY; size],
+            test_mode: DepthTestMode::Less,
+            clear_value: f64::INFINITY,
+            near,
+            far,
+            projection_mode: ProjectionMode::Perspective,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn get(&self, index: usize) -> f64 {
+        self.values[index]
+    }
+
+    pub fn clear(&mut self) {
+        self.values.fill(self.clear_value);
+    }
+
+    fn clear_range(&mut self, range: std::ops::Range<usize>) {
+        self.values[range].fill(self.clear_value);
+    }
+
+    fn resize(&mut self, size: usize) {
+        self.values = vec![self.clear_value; size];
+    }
+
+    pub fn passes(&self, index: usize, z: f64) -> bool {
+        self.test_mode.passes(z, self.values[index])
+    }
+
+    // Stores `z` if it passes the depth test
+    pub fn test_and_set(&mut self, index: usize, z: f64) -> bool {
+        let passed = self.passes(index, z);
+        if passed {
+            self.values[index] = z;
+        }
+        passed
+    }
+
+    // Maps an NDC z to linear depth between near (0) and far (1). Cleared
+    // (infinite) entries come out as 1.
+    pub fn normalize(&self, z: f64) -> f64 {
+        if !z.is_finite() {
+            return 1.0;
+        }
+        // Orthographic NDC z is already linear in view depth
+        if self.projection_mode == ProjectionMode::Orthographic {
+            return ((z + 1.0) * 0.5).clamp(0.0, 1.0);
+        }
+        let (near, far) = (self.near, self.far);
+        let view_depth = 2.0 * near * far / (far + near - z * (far - near));
+        ((view_depth - near) / (far - near)).clamp(0.0, 1.0)
+    }
+
+    // Depth as grayscale pixels, near white and far black
+    pub fn to_grayscale(&self) -> Vec<u32> {
+        self.values.iter()
+            .map(|&z| {
+                let level = ((1.0 - self.normalize(z)) * 255.0).round() as u8;
+                Color::new(level, level, level, 255).to_u32()
+            })
+            .collect()
+    }
+}
+
+// Channel-wise average of one pixel's MSAA samples
+fn average_samples(samples: &[u32]) -> u32 {
+    let mut sum = [0u32; 4];
+    for &sample in samples {
+        let color = Color::from_u32(sample);
+        for (total, channel) in sum.iter_mut().zip([color.r, color.g, color.b, color.a]) {
+            *total += channel as u32;
+        }
+    }
+    let count = samples.len() as u32;
+    let [r, g, b, a] = sum.map(|total| ((total + count / 2) / count) as u8);
+    Color::new(r, g, b, a).to_u32()
+}
+
+// Twice the signed area of (a, b, c), using only x and y
+fn edge_function(a: Vec3, b: Vec3, c: Vec2) -> f64 {
+    (c.x - a.x) * (b.y - a.y) - (c.y - a.y) * (b.x - a.x)
+}
+
+// Pixel rectangle with exclusive max bounds
+#[derive(Debug, Clone, Copy)]
+struct ScissorRect {
+    min_x: i32,
+    min_y: i32,
+    max_x: i32,
+    max_y: i32,
+}
+
+// Sub-pixel sample positions for 2x and 4x MSAA, as offsets from the
+// pixel's top-left corner (the standard D3D patterns)
+const SAMPLE_OFFSETS_2X: [(f64, f64); 2] = [(0.75, 0.75), (0.25, 0.25)];
+const SAMPLE_OFFSETS_4X: [(f64, f64); 4] = [(0.375, 0.125), (0.875, 0.375), (0.125, 0.625), (0.625, 0.875)];
+
+// Color and depth per sample, `samples` consecutive entries per pixel
+struct MultisampleBuffer {
+    offsets: &'static [(f64, f64)],
+    colors: Vec<u32>,
+    depths: Vec<f64>,
+}
+
+impl MultisampleBuffer {
+    fn samples(&self) -> usize {
+        self.offsets.len()
+    }
+
+    fn pixel_range(&self, index: usize) -> std::ops::Range<usize> {
+        index * self.samples()..(index + 1) * self.samples()
+    }
 }
 
 pub struct Rasterizer {
     width: usize,
     height: usize,
     color_buffer: Vec<u32>,
-    depth_buffer: Vec<f64>,
+    depth_buffer: DepthBuffer,
+    clear_color: u32,
+    scissor: Option<ScissorRect>,
+    overdraw_counts: Option<Vec<u32>>,
+    msaa: Option<MultisampleBuffer>,
 }
 
 impl Rasterizer {
@@ -39,31 +377,423 @@ impl Rasterizer {
             width,
             height,
             color_buffer: vec![0; width * height],
-            depth_buffer: vec![f64::INFINITY; width * height],
+            depth_buffer: DepthBuffer::new(width * height, 0.1, 100.0),
+            clear_color: 0,
+            scissor: None,
+            overdraw_counts: None,
+            msaa: None,
+        }
+    }
+
+    // Rasterizer that covers triangle edges with `samples` samples per pixel,
+    // rounded down to 1, 2 or 4. Triangles are shaded once per pixel and the
+    // color is stored in every covered sample; everything else fills all of
+    // a pixel's samples. Call resolve before reading the color buffer.
+    pub fn new_msaa(width: usize, height: usize, samples: u8) -> Self {
+        let mut rasterizer = Self::new(width, height);
+        let offsets: &'static [(f64, f64)] = match samples {
+            4.. => &SAMPLE_OFFSETS_4X,
+            2..=3 => &SAMPLE_OFFSETS_2X,
+            _ => return rasterizer,
+        };
+        let size = width * height * offsets.len();
+        rasterizer.msaa = Some(MultisampleBuffer {
+            offsets,
+            colors: vec![0; size],
+            depths: vec![rasterizer.depth_buffer.clear_value; size],
+        });
+        rasterizer
+    }
+
+    pub fn msaa_samples(&self) -> usize {
+        self.msaa.as_ref().map_or(1, |msaa| msaa.samples())
+    }
+
+    // Averages each pixel's samples into the color buffer. A no-op without MSAA.
+    pub fn resolve(&mut self) {
+        let Some(msaa) = &self.msaa else {
+            return;
+        };
+        let samples = msaa.samples();
+        for (pixel, chunk) in self.color_buffer.iter_mut().zip(msaa.colors.chunks_exact(samples)) {
+            *pixel = average_samples(chunk);
+        }
+    }
+
+    // Copies color buffer pixels written directly into all of their samples,
+    // so a later resolve keeps them
+    fn sync_samples(&mut self, pixels: std::ops::Range<usize>) {
+        if let Some(msaa) = &mut self.msaa {
+            for index in pixels {
+                let range = msaa.pixel_range(index);
+                msaa.colors[range].fill(self.color_buffer[index]);
+            }
         }
     }
 
     pub fn clear(&mut self, color: Color) {
         let clear_color = color.to_u32();
+        self.clear_color = clear_color;
         self.color_buffer.fill(clear_color);
-        self.depth_buffer.fill(f64::INFINITY);
+        self.depth_buffer.clear();
+        if let Some(msaa) = &mut self.msaa {
+            msaa.colors.fill(clear_color);
+            msaa.depths.fill(self.depth_buffer.clear_value);
+        }
+        if let Some(counts) = &mut self.overdraw_counts {
+            counts.fill(0);
+        }
+    }
+
+    // Sets the initial depth value used by this and subsequent clears.
+    // Use 0.0 together with `DepthTestMode::GreaterEqual` for reverse-Z.
+    pub fn clear_depth(&mut self, value: f64) {
+        self.depth_buffer.clear_value = value;
+        self.depth_buffer.clear();
+        if let Some(msaa) = &mut self.msaa {
+            msaa.depths.fill(value);
+        }
+    }
+
+    pub fn depth_clear_value(&self) -> f64 {
+        self.depth_buffer.clear_value
+    }
+
+    pub fn set_depth_test_mode(&mut self, mode: DepthTestMode) {
+        self.depth_buffer.test_mode = mode;
+    }
+
+    pub fn depth_test_mode(&self) -> DepthTestMode {
+        self.depth_buffer.test_mode
+    }
+
+    // Camera clip range used to read depth back as linear values
+    pub fn set_depth_range(&mut self, near: f64, far: f64) {
+        self.depth_buffer.near = near;
+        self.depth_buffer.far = far;
+    }
+
+    pub fn set_depth_projection(&mut self, mode: ProjectionMode) {
+        self.depth_buffer.projection_mode = mode;
+    }
+
+    pub fn depth_buffer(&self) -> &DepthBuffer {
+        &self.depth_buffer
+    }
+
+    // Reallocates the buffers, cleared to the last clear color and depth. A
+    // scissor rectangle is clamped to the new size.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        self.color_buffer = vec![self.clear_color; width * height];
+        self.depth_buffer.resize(width * height);
+        if let Some(msaa) = &mut self.msaa {
+            let size = width * height * msaa.samples();
+            msaa.colors = vec![self.clear_color; size];
+            msaa.depths = vec![self.depth_buffer.clear_value; size];
+        }
+        if let Some(counts) = &mut self.overdraw_counts {
+            *counts = vec![0; width * height];
+        }
+        if let Some(rect) = self.scissor {
+            let (width, height) = ((rect.max_x - rect.min_x) as usize, (rect.max_y - rect.min_y) as usize);
+            self.set_scissor(rect.min_x, rect.min_y, width, height);
+        }
+    }
+
+    // Restricts all drawing to the given rectangle, clamped to the buffer
+    pub fn set_scissor(&mut self, x: i32, y: i32, width: usize, height: usize) {
+        let buffer_width = self.width as i32;
+        let buffer_height = self.height as i32;
+        self.scissor = Some(ScissorRect {
+            min_x: x.clamp(0, buffer_width),
+            min_y: y.clamp(0, buffer_height),
+            max_x: x.saturating_add(width as i32).clamp(0, buffer_width),
+            max_y: y.saturating_add(height as i32).clamp(0, buffer_height),
+        });
+    }
+
+    pub fn clear_scissor(&mut self) {
+        self.scissor = None;
+    }
+
+    // Drawable region as (min_x, min_y, max_x, max_y), max exclusive
+    fn clip_bounds(&self) -> (i32, i32, i32, i32) {
+        match self.scissor {
+            Some(rect) => (rect.min_x, rect.min_y, rect.max_x, rect.max_y),
+            None => (0, 0, self.width as i32, self.height as i32),
+        }
+    }
+
+    // Rectangle clipped to the drawable region as (min_x, min_y, max_x, max_y)
+    fn clip_rect(&self, x: i32, y: i32, width: usize, height: usize) -> Option<(usize, usize, usize, usize)> {
+        let (clip_min_x, clip_min_y, clip_max_x, clip_max_y) = self.clip_bounds();
+        let min_x = x.max(clip_min_x);
+        let min_y = y.max(clip_min_y);
+        let max_x = x.saturating_add(width as i32).min(clip_max_x);
+        let max_y = y.saturating_add(height as i32).min(clip_max_y);
+
+        if min_x >= max_x || min_y >= max_y {
+            return None;
+        }
+        Some((min_x as usize, min_y as usize, max_x as usize, max_y as usize))
+    }
+
+    // Fills a rectangle directly in the color buffer, ignoring depth
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: usize, height: usize, color: Color) {
+        if let Some((min_x, min_y, max_x, max_y)) = self.clip_rect(x, y, width, height) {
+            let value = color.to_u32();
+            for row in min_y..max_y {
+                let start = row * self.width;
+                self.color_buffer[start + min_x..start + max_x].fill(value);
+                self.sync_samples(start + min_x..start + max_x);
+            }
+        }
+    }
+
+    // 1-pixel outline of a w x h rectangle; nothing is drawn for empty sizes
+    pub fn draw_rect(&mut self, x: i32, y: i32, w: i32, h: i32, color: Color) {
+        if w <= 0 || h <= 0 {
+            return;
+        }
+        // Corners pulled to just outside the buffer keep the lines short
+        let clamp_x = |v: i32| v.clamp(-1, self.width as i32) as f64;
+        let clamp_y = |v: i32| v.clamp(-1, self.height as i32) as f64;
+        let (x0, y0) = (clamp_x(x), clamp_y(y));
+        let (x1, y1) = (clamp_x(x.saturating_add(w - 1)), clamp_y(y.saturating_add(h - 1)));
+
+        self.draw_line(Vec2::new(x0, y0), Vec2::new(x1, y0), color);
+        self.draw_line(Vec2::new(x0, y1), Vec2::new(x1, y1), color);
+        self.draw_line(Vec2::new(x0, y0), Vec2::new(x0, y1), color);
+        self.draw_line(Vec2::new(x1, y0), Vec2::new(x1, y1), color);
+    }
+
+    pub fn draw_filled_rect(&mut self, x: i32, y: i32, w: i32, h: i32, color: Color) {
+        if w > 0 && h > 0 {
+            self.fill_rect(x, y, w as usize, h as usize, color);
+        }
+    }
+
+    // Blends `color` over the rectangle by its alpha:
+    // out = src_alpha * src + (1 - src_alpha) * dst
+    pub fn draw_filled_rect_alpha(&mut self, x: i32, y: i32, w: i32, h: i32, color: Color) {
+        if w <= 0 || h <= 0 {
+            return;
+        }
+        let Some((min_x, min_y, max_x, max_y)) = self.clip_rect(x, y, w as usize, h as usize) else {
+            return;
+        };
+        let alpha = color.a as f64 / 255.0;
+        let blend = |pixel: &mut u32| *pixel = Color::from_u32(*pixel).lerp(color, alpha).to_u32();
+        for row in min_y..max_y {
+            let start = row * self.width;
+            self.color_buffer[start + min_x..start + max_x].iter_mut().for_each(blend);
+            // Multisampled triangles only live in the samples until resolve,
+            // so blend over each sample rather than the color buffer
+            if let Some(msaa) = &mut self.msaa {
+                let samples = msaa.pixel_range(start + min_x).start..msaa.pixel_range(start + max_x).start;
+                msaa.colors[samples].iter_mut().for_each(blend);
+            }
+        }
+    }
+
+    // Resets a rectangle to the last clear color and depth
+    pub fn clear_rect(&mut self, x: i32, y: i32, width: usize, height: usize) {
+        if let Some((min_x, min_y, max_x, max_y)) = self.clip_rect(x, y, width, height) {
+            for row in min_y..max_y {
+                let start = row * self.width;
+                self.color_buffer[start + min_x..start + max_x].fill(self.clear_color);
+                self.depth_buffer.clear_range(start + min_x..start + max_x);
+                self.sync_samples(start + min_x..start + max_x);
+                if let Some(msaa) = &mut self.msaa {
+                    let range = msaa.pixel_range(start + min_x).start..msaa.pixel_range(start + max_x).start;
+                    msaa.depths[range].fill(self.depth_buffer.clear_value);
+                }
+            }
+        }
+    }
+
+    // Copies a width x height block from `src` (rows of `src_width` pixels)
+    // into the color buffer. Parts outside either buffer are skipped.
+    #[allow(clippy::too_many_arguments)]
+    pub fn copy_rect(
+        &mut self,
+        src: &[u32],
+        src_width: usize,
+        src_x: i32,
+        src_y: i32,
+        dst_x: i32,
+        dst_y: i32,
+        width: usize,
+        height: usize,
+    ) {
+        if src_width == 0 {
+            return;
+        }
+        let src_height = (src.len() / src_width) as i32;
+        let (clip_min_x, clip_min_y, clip_max_x, clip_max_y) = self.clip_bounds();
+
+        // Offsets into the block that stay inside both buffers
+        let start_x = 0.max(-src_x).max(clip_min_x - dst_x);
+        let end_x = (width as i32).min(src_width as i32 - src_x).min(clip_max_x - dst_x);
+        let start_y = 0.max(-src_y).max(clip_min_y - dst_y);
+        let end_y = (height as i32).min(src_height - src_y).min(clip_max_y - dst_y);
+        if start_x >= end_x {
+            return;
+        }
+
+        for dy in start_y..end_y {
+            let src_start = ((src_y + dy) as usize) * src_width + (src_x + start_x) as usize;
+            let dst_start = ((dst_y + dy) as usize) * self.width + (dst_x + start_x) as usize;
+            let len = (end_x - start_x) as usize;
+            self.color_buffer[dst_start..dst_start + len].copy_from_slice(&src[src_start..src_start + len]);
+            self.sync_samples(dst_start..dst_start + len);
+        }
+    }
+
+    // Draws a src_width x src_height image (in color buffer format) with its
+    // top-left corner at (dst_x, dst_y). Fully transparent pixels are skipped.
+    pub fn blit(&mut self, src: &[u32], src_width: usize, src_height: usize, dst_x: i32, dst_y: i32) {
+        self.blit_scaled(src, src_width, src_height, dst_x, dst_y, src_width, src_height);
+    }
+
+    // Like blit, but stretched to dst_width x dst_height with nearest-neighbor sampling
+    #[allow(clippy::too_many_arguments)]
+    pub fn blit_scaled(
+        &mut self,
+        src: &[u32],
+        src_width: usize,
+        src_height: usize,
+        dst_x: i32,
+        dst_y: i32,
+        dst_width: usize,
+        dst_height: usize,
+    ) {
+        if src_width == 0 || src_height == 0 || src.len() < src_width * src_height {
+            return;
+        }
+        let Some((min_x, min_y, max_x, max_y)) = self.clip_rect(dst_x, dst_y, dst_width, dst_height) else {
+            return;
+        };
+
+        for y in min_y..max_y {
+            let sy = (y as i32 - dst_y) as usize * src_height / dst_height;
+            for x in min_x..max_x {
+                let sx = (x as i32 - dst_x) as usize * src_width / dst_width;
+                let pixel = src[sy * src_width + sx];
+                if pixel >> 24 != 0 {
+                    let index = y * self.width + x;
+                    self.color_buffer[index] = pixel;
+                    self.sync_samples(index..index + 1);
+                }
+            }
+        }
+    }
+
+    // Counts how often each pixel passes the depth test, reset on clear
+    pub fn enable_overdraw_tracking(&mut self, enabled: bool) {
+        self.overdraw_counts = if enabled {
+            Some(vec![0; self.width * self.height])
+        } else {
+            None
+        };
+    }
+
+    pub fn get_overdraw_counts(&self) -> Option<&[u32]> {
+        self.overdraw_counts.as_deref()
+    }
+
+    pub fn get_max_overdraw(&self) -> u32 {
+        self.overdraw_counts.as_ref()
+            .and_then(|counts| counts.iter().max().copied())
+            .unwrap_or(0)
+    }
+
+    // Average number of writes over the pixels that were written at least once
+    pub fn get_average_overdraw(&self) -> f64 {
+        let Some(counts) = &self.overdraw_counts else {
+            return 0.0;
+        };
+        let (total, covered) = counts.iter()
+            .filter(|&&c| c > 0)
+            .fold((0u64, 0u64), |(total, covered), &c| (total + c as u64, covered + 1));
+        if covered == 0 { 0.0 } else { total as f64 / covered as f64 }
+    }
+
+    // Overdraw as a color buffer: black for untouched pixels, then blue
+    // through red up to the maximum count
+    pub fn get_overdraw_heatmap(&self) -> Vec<u32> {
+        let max = self.get_max_overdraw().max(1) as f64;
+        let Some(counts) = &self.overdraw_counts else {
+            return vec![Color::black().to_u32(); self.width * self.height];
+        };
+
+        counts.iter()
+            .map(|&count| {
+                if count == 0 {
+                    return Color::black().to_u32();
+                }
+                let t = count as f64 / max;
+                Color::new((255.0 * t) as u8, 0, (255.0 * (1.0 - t)) as u8, 255).to_u32()
+            })
+            .collect()
     }
 
     pub fn get_color_buffer(&self) -> &[u32] {
         &self.color_buffer
     }
 
+    // Copy of the color buffer with every pixel repacked in `format`
+    pub fn get_color_buffer_as(&self, format: PixelFormat) -> Vec<u32> {
+        if format == PixelFormat::ABGR {
+            return self.color_buffer.clone();
+        }
+        self.color_buffer.iter()
+            .map(|&pixel| Color::from_u32(pixel).to_u32_as(format))
+            .collect()
+    }
+
+    // None outside the buffer; ignores the scissor rect. With MSAA this is
+    // the pixel's resolved color, even before resolve is called.
+    pub fn read_pixel(&self, x: i32, y: i32) -> Option<Color> {
+        let index = self.pixel_index(x, y)?;
+        let value = match &self.msaa {
+            Some(msaa) => average_samples(&msaa.colors[msaa.pixel_range(index)]),
+            None => self.color_buffer[index],
+        };
+        Some(Color::from_u32(value))
+    }
+
+    pub fn read_depth(&self, x: i32, y: i32) -> Option<f64> {
+        self.pixel_index(x, y).map(|index| self.depth_buffer.get(index))
+    }
+
+    fn pixel_index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return None;
+        }
+        Some(y as usize * self.width + x as usize)
+    }
+
     pub fn set_pixel(&mut self, x: i32, y: i32, z: f64, color: Color) {
-        if x < 0 || x >= self.width as i32 || y < 0 || y >= self.height as i32 {
+        let (min_x, min_y, max_x, max_y) = self.clip_bounds();
+        if x < min_x || x >= max_x || y < min_y || y >= max_y {
             return;
         }
 
         let index = (y as usize) * self.width + (x as usize);
 
-        // Depth test
-        if z < self.depth_buffer[index] {
-            self.depth_buffer[index] = z;
+        if self.depth_buffer.test_and_set(index, z) {
             self.color_buffer[index] = color.to_u32();
+            if let Some(msaa) = &mut self.msaa {
+                let range = msaa.pixel_range(index);
+                msaa.colors[range.clone()].fill(color.to_u32());
+                msaa.depths[range].fill(z);
+            }
+            if let Some(counts) = &mut self.overdraw_counts {
+                counts[index] += 1;
+            }
         }
     }
 
@@ -107,19 +837,97 @@ impl Rasterizer {
         }
     }
     pub fn draw_triangle(&mut self, v0: Vec2, v1: Vec2, v2: Vec2, color: Color) {
-        // Compute bounding box
-        let min_x = v0.x.min(v1.x).min(v2.x).max(0.0) as i32;
-        let min_y = v0.y.min(v1.y).min(v2.y).max(0.0) as i32;
-        let max_x = v0.x.max(v1.x).max(v2.x).min(self.width as f64 - 1.0) as i32;
-        let max_y = v0.y.max(v1.y).max(v2.y).min(self.height as f64 - 1.0) as i32;
+        self.draw_triangle_depth(
+            Vec3::new(v0.x, v0.y, 0.0),
+            Vec3::new(v1.x, v1.y, 0.0),
+            Vec3::new(v2.x, v2.y, 0.0),
+            color,
+        );
+    }
 
-        // Edge functions
-        let edge = |a: Vec2, b: Vec2, c: Vec2| -> f64 {
-            (c.x - a.x) * (b.y - a.y) - (c.y - a.y) * (b.x - a.x)
-        };
+    // Fills a triangle given in screen space, with z holding the depth value
+    pub fn draw_triangle_depth(&mut self, v0: Vec3, v1: Vec3, v2: Vec3, color: Color) {
+        self.draw_triangle_shaded(v0, v1, v2, |_| color);
+    }
+
+    // Depth-tested fill where `shade` picks each pixel's color from its
+    // screen-space barycentric weights for v0, v1 and v2
+    pub fn draw_triangle_shaded<F>(&mut self, v0: Vec3, v1: Vec3, v2: Vec3, shade: F)
+    where
+        F: FnMut([f64; 3]) -> Color,
+    {
+        if self.msaa.is_some() {
+            self.draw_triangle_multisampled(v0, v1, v2, shade);
+            return;
+        }
+        #[cfg(not(feature = "bbox_raster"))]
+        self.draw_triangle_scanline(v0, v1, v2, shade);
+        #[cfg(feature = "bbox_raster")]
+        self.draw_triangle_bbox(v0, v1, v2, shade);
+    }
+
+    // Fills only each row's span between the triangle's edges, found by
+    // solving the three edge functions for x at the row's pixel centers
+    pub fn draw_triangle_scanline<F>(&mut self, v0: Vec3, v1: Vec3, v2: Vec3, mut shade: F)
+    where
+        F: FnMut([f64; 3]) -> Color,
+    {
+        let area = edge_function(v0, v1, Vec2::new(v2.x, v2.y));
+        if area.abs() < 1e-8 {
+            return; // Degenerate triangle
+        }
+
+        let (clip_min_x, clip_min_y, clip_max_x, clip_max_y) = self.clip_bounds();
+        let min_y = v0.y.min(v1.y).min(v2.y).max(clip_min_y as f64) as i32;
+        let max_y = v0.y.max(v1.y).max(v2.y).min(clip_max_y as f64 - 1.0) as i32;
+
+        for y in min_y..=max_y {
+            let py = y as f64 + 0.5;
+
+            // Along the row each weight is linear in x: w(px) = a * px + b
+            let mut left = f64::NEG_INFINITY;
+            let mut right = f64::INFINITY;
+            for (from, to) in [(v1, v2), (v2, v0), (v0, v1)] {
+                let a = (to.y - from.y) / area;
+                let b = ((from.x - to.x) * (py - from.y) - from.x * (to.y - from.y)) / area;
+                if a > 0.0 {
+                    left = left.max(-b / a);
+                } else if a < 0.0 {
+                    right = right.min(-b / a);
+                } else if b < 0.0 {
+                    left = f64::INFINITY; // Row lies entirely outside this edge
+                }
+            }
+            if left > right {
+                continue;
+            }
+
+            // Widen by a pixel so rounding never drops one; the exact inside
+            // test happens per pixel
+            let min_x = ((left - 0.5).ceil() - 1.0).max(clip_min_x as f64) as i32;
+            let max_x = ((right - 0.5).floor() + 1.0).min(clip_max_x as f64 - 1.0) as i32;
+            for x in min_x..=max_x {
+                self.shade_triangle_pixel(x, y, [v0, v1, v2], area, &mut shade);
+            }
+        }
+    }
+
+    // Tests every pixel in the triangle's bounding box. Kept for tests and the
+    // bbox_raster feature, which switches draw_triangle_shaded over to it.
+    #[cfg(any(test, feature = "bbox_raster"))]
+    pub fn draw_triangle_bbox<F>(&mut self, v0: Vec3, v1: Vec3, v2: Vec3, mut shade: F)
+    where
+        F: FnMut([f64; 3]) -> Color,
+    {
+        // Compute bounding box, clipped to the drawable region
+        let (clip_min_x, clip_min_y, clip_max_x, clip_max_y) = self.clip_bounds();
+        let min_x = v0.x.min(v1.x).min(v2.x).max(clip_min_x as f64) as i32;
+        let min_y = v0.y.min(v1.y).min(v2.y).max(clip_min_y as f64) as i32;
+        let max_x = v0.x.max(v1.x).max(v2.x).min(clip_max_x as f64 - 1.0) as i32;
+        let max_y = v0.y.max(v1.y).max(v2.y).min(clip_max_y as f64 - 1.0) as i32;
 
         // Triangle area
-        let area = edge(v0, v1, v2);
+        let area = edge_function(v0, v1, Vec2::new(v2.x, v2.y));
         if area.abs() < 1e-8 {
             return; // Degenerate triangle
         }
@@ -127,27 +935,247 @@ impl Rasterizer {
         // Scan through bounding box
         for y in min_y..=max_y {
             for x in min_x..=max_x {
-                let p = Vec2::new(x as f64 + 0.5, y as f64 + 0.5);
+                self.shade_triangle_pixel(x, y, [v0, v1, v2], area, &mut shade);
+            }
+        }
+    }
+
+    // Bounding box traversal testing coverage per sample, so pixels whose
+    // center lies outside the triangle still get their covered samples
+    fn draw_triangle_multisampled<F>(&mut self, v0: Vec3, v1: Vec3, v2: Vec3, mut shade: F)
+    where
+        F: FnMut([f64; 3]) -> Color,
+    {
+        let area = edge_function(v0, v1, Vec2::new(v2.x, v2.y));
+        if area.abs() < 1e-8 {
+            return; // Degenerate triangle
+        }
+
+        let (clip_min_x, clip_min_y, clip_max_x, clip_max_y) = self.clip_bounds();
+        let min_x = v0.x.min(v1.x).min(v2.x).max(clip_min_x as f64) as i32;
+        let min_y = v0.y.min(v1.y).min(v2.y).max(clip_min_y as f64) as i32;
+        let max_x = v0.x.max(v1.x).max(v2.x).min(clip_max_x as f64 - 1.0) as i32;
+        let max_y = v0.y.max(v1.y).max(v2.y).min(clip_max_y as f64 - 1.0) as i32;
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                self.shade_triangle_samples(x, y, [v0, v1, v2], area, &mut shade);
+            }
+        }
+    }
+
+    // Shades the pixel once, at its first covered sample, and stores the
+    // color in every covered sample that passes the depth test
+    fn shade_triangle_samples<F>(&mut self, x: i32, y: i32, v: [Vec3; 3], area: f64, shade: &mut F)
+    where
+        F: FnMut([f64; 3]) -> Color,
+    {
+        let index = (y as usize) * self.width + (x as usize);
+        let Some(msaa) = &mut self.msaa else {
+            return;
+        };
+
+        let mut color = None;
+        let mut written_depth = None;
+        for (sample, &(dx, dy)) in msaa.offsets.iter().enumerate() {
+            let p = Vec2::new(x as f64 + dx, y as f64 + dy);
+            let b0 = edge_function(v[1], v[2], p) / area;
+            let b1 = edge_function(v[2], v[0], p) / area;
+            let b2 = edge_function(v[0], v[1], p) / area;
+            if b0 < 0.0 || b1 < 0.0 || b2 < 0.0 {
+                continue;
+            }
+
+            let z = b0 * v[0].z + b1 * v[1].z + b2 * v[2].z;
+            let sample_index = index * msaa.offsets.len() + sample;
+            if self.depth_buffer.test_mode.passes(z, msaa.depths[sample_index]) {
+                let color = *color.get_or_insert_with(|| shade([b0, b1, b2]));
+                msaa.colors[sample_index] = color.to_u32();
+                msaa.depths[sample_index] = z;
+                written_depth = Some(z);
+            }
+        }
+
+        // The pixel's own depth follows its last written sample
+        if let Some(z) = written_depth {
+            self.depth_buffer.values[index] = z;
+            if let Some(counts) = &mut self.overdraw_counts {
+                counts[index] += 1;
+            }
+        }
+    }
+
+    fn shade_triangle_pixel<F>(&mut self, x: i32, y: i32, v: [Vec3; 3], area: f64, shade: &mut F)
+    where
+        F: FnMut([f64; 3]) -> Color,
+    {
+        let p = Vec2::new(x as f64 + 0.5, y as f64 + 0.5);
+
+        // Compute barycentric coordinates; dividing by the signed area
+        // makes them positive inside the triangle for either winding
+        let b0 = edge_function(v[1], v[2], p) / area;
+        let b1 = edge_function(v[2], v[0], p) / area;
+        let b2 = edge_function(v[0], v[1], p) / area;
+
+        // Check if point is inside triangle
+        if b0 >= 0.0 && b1 >= 0.0 && b2 >= 0.0 {
+            // Interpolate z value
+            let z = b0 * v[0].z + b1 * v[1].z + b2 * v[2].z;
+            let index = (y as usize) * self.width + (x as usize);
+            // Only shade fragments that will survive the depth test
+            if self.depth_buffer.passes(index, z) {
+                let color = shade([b0, b1, b2]);
+                self.set_pixel(x, y, z, color);
+            }
+        }
+    }
+
+    // Each vertex is (screen position, uv, 1 / w, color). UVs and colors are
+    // interpolated divided by w and then multiplied by the interpolated w,
+    // keeping them perspective correct. The texture sample is tinted by the
+    // vertex color. Like draw_triangle, pixels are written at depth 0.
+    pub fn draw_triangle_textured(
+        &mut self,
+        v0: (Vec2, Vec2, f64, Color),
+        v1: (Vec2, Vec2, f64, Color),
+        v2: (Vec2, Vec2, f64, Color),
+        texture: &Texture,
+    ) {
+        let (p0, p1, p2) = (v0.0, v1.0, v2.0);
+        let (clip_min_x, clip_min_y, clip_max_x, clip_max_y) = self.clip_bounds();
+        let min_x = p0.x.min(p1.x).min(p2.x).max(clip_min_x as f64) as i32;
+        let min_y = p0.y.min(p1.y).min(p2.y).max(clip_min_y as f64) as i32;
+        let max_x = p0.x.max(p1.x).max(p2.x).min(clip_max_x as f64 - 1.0) as i32;
+        let max_y = p0.y.max(p1.y).max(p2.y).min(clip_max_y as f64 - 1.0) as i32;
+
+        let edge = |a: Vec2, b: Vec2, c: Vec2| -> f64 {
+            (c.x - a.x) * (b.y - a.y) - (c.y - a.y) * (b.x - a.x)
+        };
 
-                // Compute barycentric coordinates
-                let w0 = edge(v1, v2, p);
-                let w1 = edge(v2, v0, p);
-                let w2 = edge(v0, v1, p);
+        let area = edge(p0, p1, p2);
+        if area.abs() < 1e-8 {
+            return;
+        }
 
-                // Check if point is inside triangle
-                if w0 >= 0.0 && w1 >= 0.0 && w2 >= 0.0 {
-                    let b0 = w0 / area;
-                    let b1 = w1 / area;
-                    let b2 = w2 / area;
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let p = Vec2::new(x as f64 + 0.5, y as f64 + 0.5);
+                let b0 = edge(p1, p2, p) / area;
+                let b1 = edge(p2, p0, p) / area;
+                let b2 = edge(p0, p1, p) / area;
 
-                    // Interpolate z value
-                    let z = b0 * v0.x + b1 * v1.x + b2 * v2.x;
-                    self.set_pixel(x, y, z, color);
+                if b0 >= 0.0 && b1 >= 0.0 && b2 >= 0.0 {
+                    let inv_w = b0 * v0.2 + b1 * v1.2 + b2 * v2.2;
+                    if inv_w <= 0.0 {
+                        continue;
+                    }
+                    let weights = [b0 * v0.2 / inv_w, b1 * v1.2 / inv_w, b2 * v2.2 / inv_w];
+                    let uv = v0.1 * weights[0] + v1.1 * weights[1] + v2.1 * weights[2];
+                    let tint = Color::interpolate([v0.3, v1.3, v2.3], weights);
+                    self.set_pixel(x, y, 0.0, texture.sample(uv.x, uv.y).modulate(tint));
                 }
             }
         }
     }
 
+    // Ellipse outline using the midpoint ellipse algorithm
+    pub fn draw_ellipse(&mut self, cx: i32, cy: i32, rx: i32, ry: i32, color: Color) {
+        if rx < 0 || ry < 0 {
+            return;
+        }
+        let (rx, ry) = (rx as i64, ry as i64);
+        let (rx2, ry2) = (rx * rx, ry * ry);
+        let plot = |r: &mut Self, x: i64, y: i64| {
+            for (sx, sy) in [(x, y), (-x, y), (x, -y), (-x, -y)] {
+                r.set_pixel(cx + sx as i32, cy + sy as i32, 0.0, color);
+            }
+        };
+
+        // Region 1: slope shallower than -1
+        let mut x = 0;
+        let mut y = ry;
+        let mut d = 4 * ry2 - 4 * rx2 * ry + rx2;
+        while ry2 * x <= rx2 * y {
+            plot(self, x, y);
+            if d < 0 {
+                d += 4 * ry2 * (2 * x + 3);
+            } else {
+                d += 4 * ry2 * (2 * x + 3) - 8 * rx2 * (y - 1);
+                y -= 1;
+            }
+            x += 1;
+        }
+
+        // Region 2: slope steeper than -1
+        let mut x = rx;
+        let mut y = 0;
+        let mut d = 4 * rx2 - 4 * ry2 * rx + ry2;
+        while rx2 * y <= ry2 * x {
+            plot(self, x, y);
+            if d < 0 {
+                d += 4 * rx2 * (2 * y + 3);
+            } else {
+                d += 4 * rx2 * (2 * y + 3) - 8 * ry2 * (x - 1);
+                x -= 1;
+            }
+            y += 1;
+        }
+    }
+
+    // Filled ellipse drawn as one horizontal span per scanline
+    pub fn fill_ellipse(&mut self, cx: i32, cy: i32, rx: i32, ry: i32, color: Color) {
+        if rx < 0 || ry < 0 {
+            return;
+        }
+        for dy in -ry..=ry {
+            let t = if ry == 0 { 0.0 } else { dy as f64 / ry as f64 };
+            let half_width = (rx as f64 * (1.0 - t * t).max(0.0).sqrt()).floor() as i32;
+            self.draw_span(cy + dy, cx - half_width, cx + half_width, color);
+        }
+    }
+
+    // Midpoint circle outline; the center and radius snap to whole pixels
+    pub fn draw_circle(&mut self, center: Vec2, radius: f64, color: Color) {
+        if radius < 0.0 {
+            return;
+        }
+        let (cx, cy) = (center.x.round() as i32, center.y.round() as i32);
+        let mut x = radius.round() as i32;
+        let mut y = 0;
+        let mut d = 1 - x;
+
+        // One octant, mirrored into the other seven
+        while x >= y {
+            for (sx, sy) in [(x, y), (y, x), (-y, x), (-x, y), (-x, -y), (-y, -x), (y, -x), (x, -y)] {
+                self.set_pixel(cx + sx, cy + sy, 0.0, color);
+            }
+            y += 1;
+            if d < 0 {
+                d += 2 * y + 1;
+            } else {
+                x -= 1;
+                d += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    // Filled circle drawn as one horizontal span per scanline
+    pub fn draw_filled_circle(&mut self, center: Vec2, radius: f64, color: Color) {
+        let radius = radius.round() as i32;
+        self.fill_ellipse(center.x.round() as i32, center.y.round() as i32, radius, radius, color);
+    }
+
+    // Horizontal run of pixels from x0 to x1 inclusive, clipped to the drawable region
+    fn draw_span(&mut self, y: i32, x0: i32, x1: i32, color: Color) {
+        let (min_x, min_y, max_x, max_y) = self.clip_bounds();
+        if y < min_y || y >= max_y {
+            return;
+        }
+        for x in x0.max(min_x)..=x1.min(max_x - 1) {
+            self.set_pixel(x, y, 0.0, color);
+        }
+    }
+
     pub fn draw_triangle_wireframe(&mut self, v0: Vec2, v1: Vec2, v2: Vec2, color: Color) {
         self.draw_line(v0, v1, color);
         self.draw_line(v1, v2, color);
@@ -167,6 +1195,112 @@ mod tests {
         assert_eq!((u32_color >> 8) & 0xFF, 128);
         assert_eq!((u32_color >> 16) & 0xFF, 64);
         assert_eq!((u32_color >> 24) & 0xFF, 255);
+
+        let decoded = Color::from_u32(u32_color);
+        assert_eq!((decoded.r, decoded.g, decoded.b, decoded.a), (255, 128, 64, 255));
+    }
+
+    #[test]
+    fn test_color_hsv_hex_and_lerp() {
+        let red = Color::from_hsv(0.0, 1.0, 1.0);
+        assert_eq!(red.to_u32(), Color::new(255, 0, 0, 255).to_u32());
+        assert_eq!(Color::from_hsv(240.0, 1.0, 1.0).to_u32(), Color::new(0, 0, 255, 255).to_u32());
+
+        for r in (0..=255).step_by(17) {
+            for g in (0..=255).step_by(51) {
+                for b in (0..=255).step_by(85) {
+                    let original = Color::new(r as u8, g as u8, b as u8, 255);
+                    let (h, s, v) = original.to_hsv();
+                    let back = Color::from_hsv(h, s, v);
+                    for (x, y) in [(original.r, back.r), (original.g, back.g), (original.b, back.b)] {
+                        assert!(x.abs_diff(y) <= 1, "{:?} -> {:?}", original, back);
+                    }
+                }
+            }
+        }
+
+        let hex = Color::from_hex(0x11223344);
+        assert_eq!((hex.r, hex.g, hex.b, hex.a), (0x11, 0x22, 0x33, 0x44));
+
+        let mid = Color::black().lerp(Color::white(), 0.5);
+        assert_eq!((mid.r, mid.g, mid.b, mid.a), (128, 128, 128, 255));
+        assert_eq!(Color::black().lerp(Color::white(), 2.0).to_u32(), Color::white().to_u32());
+    }
+
+    #[test]
+    fn test_pixel_formats() {
+        let color = Color::new(255, 128, 64, 200);
+        let channels = |value: u32| value.to_be_bytes();
+        assert_eq!(channels(color.to_u32_argb()), [200, 255, 128, 64]);
+        assert_eq!(channels(color.to_u32_rgba()), [255, 128, 64, 200]);
+        assert_eq!(channels(color.to_u32_bgra()), [64, 128, 255, 200]);
+        assert_eq!(channels(color.to_u32_as(PixelFormat::ABGR)), [200, 64, 128, 255]);
+
+        let mut rasterizer = Rasterizer::new(4, 4);
+        rasterizer.clear(Color::black());
+        rasterizer.set_pixel(1, 2, 0.0, color);
+        assert_eq!(rasterizer.get_color_buffer_as(PixelFormat::ABGR), rasterizer.get_color_buffer());
+        let rgba = rasterizer.get_color_buffer_as(PixelFormat::RGBA);
+        assert_eq!(rgba[2 * 4 + 1], color.to_u32_rgba());
+        assert_eq!(rgba[0], Color::black().to_u32_rgba());
+    }
+
+    #[test]
+    fn test_textured_triangle_is_perspective_correct() {
+        let mut rasterizer = Rasterizer::new(100, 100);
+        rasterizer.clear(Color::black());
+        let dark = Color::new(10, 10, 10, 255);
+        let light = Color::new(250, 250, 250, 255);
+        let texture = Texture::new(2, 1, vec![dark, light])
+            .with_filter(crate::texture::TextureFilter::Nearest);
+
+        // The right vertex is four times further away than the others
+        let white = Color::white();
+        rasterizer.draw_triangle_textured(
+            (Vec2::new(0.0, 0.0), Vec2::new(0.0, 0.0), 1.0, white),
+            (Vec2::new(100.0, 0.0), Vec2::new(1.0, 0.0), 0.25, white),
+            (Vec2::new(0.0, 100.0), Vec2::new(0.0, 1.0), 1.0, white),
+            &texture,
+        );
+
+        // 60% of the way across an affine mapping would already be in the
+        // light half; with perspective the far half is squeezed to the right
+        let buffer = rasterizer.get_color_buffer();
+        assert_eq!(buffer[100 + 60], dark.to_u32());
+        assert_eq!(buffer[100 + 95], light.to_u32());
+        assert_eq!(buffer[99 * 100 + 99], Color::black().to_u32());
+
+        // Vertex colors tint the texture
+        let red = Color::new(255, 0, 0, 255);
+        rasterizer.clear(Color::black());
+        rasterizer.draw_triangle_textured(
+            (Vec2::new(0.0, 0.0), Vec2::new(0.75, 0.5), 1.0, red),
+            (Vec2::new(100.0, 0.0), Vec2::new(0.75, 0.5), 1.0, red),
+            (Vec2::new(0.0, 100.0), Vec2::new(0.75, 0.5), 1.0, white),
+            &texture,
+        );
+        let top = Color::from_u32(rasterizer.get_color_buffer()[100 + 50]);
+        assert!(top.r == 250 && top.g < 10 && top.b < 10);
+        let left = Color::from_u32(rasterizer.get_color_buffer()[50 * 100 + 1]);
+        assert!(left.g > 100 && left.g < 150);
+    }
+
+    #[test]
+    fn test_hdr_tone_mapping() {
+        let reinhard = |v: f32| ColorF::new(v, v, v, 1.0).tone_map_reinhard();
+        assert!((reinhard(1.0).r - 0.5).abs() < 1e-6);
+        assert!((reinhard(9.0).g - 0.9).abs() < 1e-6);
+        assert_eq!(reinhard(9.0).a, 1.0);
+
+        let aces = ColorF::new(0.5, 0.5, 0.5, 1.0).tone_map_aces();
+        assert!(aces.b > 0.4 && aces.b < 0.6);
+        assert!(ColorF::new(100.0, 0.0, 0.0, 1.0).tone_map_aces().r <= 1.0);
+
+        let color = Color::new(255, 128, 0, 255);
+        let hdr = ColorF::from_color(color);
+        let back = hdr.to_color_clamped();
+        assert_eq!((back.r, back.g, back.b, back.a), (255, 128, 0, 255));
+        assert_eq!(ColorF::new(4.0, -1.0, 0.5, 1.0).to_color_clamped().r, 255);
     }
 
     #[test]
@@ -183,4 +1317,390 @@ mod tests {
         rasterizer.set_pixel(100, 100, 0.0, color);
         assert_eq!(rasterizer.color_buffer[100 * 800 + 100], color.to_u32());
     }
+
+    #[test]
+    fn test_msaa_resolve() {
+        let mut rasterizer = Rasterizer::new_msaa(20, 10, 4);
+        assert_eq!(rasterizer.msaa_samples(), 4);
+        assert_eq!(Rasterizer::new_msaa(4, 4, 3).msaa_samples(), 2);
+        assert_eq!(Rasterizer::new_msaa(4, 4, 1).msaa_samples(), 1);
+
+        rasterizer.clear(Color::black());
+        rasterizer.draw_triangle(Vec2::new(0.0, 0.0), Vec2::new(20.0, 0.0), Vec2::new(0.0, 10.0), Color::white());
+        rasterizer.fill_rect(18, 8, 2, 2, Color::new(255, 0, 0, 255));
+        rasterizer.resolve();
+
+        let buffer = rasterizer.get_color_buffer();
+        assert_eq!(buffer.len(), 20 * 10);
+        assert_eq!(buffer[0], Color::white().to_u32());
+        assert_eq!(buffer[9 * 20 + 19], Color::new(255, 0, 0, 255).to_u32());
+        assert_eq!(buffer[9 * 20 + 12], Color::black().to_u32());
+        // Pixels along the hypotenuse are partly covered and come out gray
+        let edge_pixels = buffer.iter()
+            .map(|&p| Color::from_u32(p))
+            .filter(|c| c.r == c.g && c.r > 0 && c.r < 255)
+            .count();
+        assert!(edge_pixels >= 10);
+
+        let mut single = Rasterizer::new_msaa(20, 10, 1);
+        single.clear(Color::black());
+        single.draw_triangle(Vec2::new(0.0, 0.0), Vec2::new(20.0, 0.0), Vec2::new(0.0, 10.0), Color::white());
+        single.resolve();
+        assert!(single.get_color_buffer().iter().all(|&p| p == Color::black().to_u32() || p == Color::white().to_u32()));
+    }
+
+    #[test]
+    fn test_msaa_alpha_rect_keeps_triangles() {
+        let mut rasterizer = Rasterizer::new_msaa(20, 10, 4);
+        rasterizer.clear(Color::black());
+        rasterizer.draw_triangle(Vec2::new(0.0, 0.0), Vec2::new(20.0, 0.0), Vec2::new(0.0, 10.0), Color::white());
+        rasterizer.draw_filled_rect_alpha(0, 0, 20, 10, Color::new(255, 0, 0, 128));
+
+        // Read back before resolving, then compare with the resolved buffer
+        let inside = rasterizer.read_pixel(1, 1).unwrap();
+        let outside = rasterizer.read_pixel(19, 9).unwrap();
+        assert_eq!((inside.r, inside.g, inside.b), (255, 127, 127));
+        assert_eq!((outside.r, outside.g, outside.b), (128, 0, 0));
+
+        rasterizer.resolve();
+        assert_eq!(rasterizer.get_color_buffer()[20 + 1], inside.to_u32());
+        assert_eq!(rasterizer.get_color_buffer()[9 * 20 + 19], outside.to_u32());
+    }
+
+    #[test]
+    fn test_read_pixel_and_depth() {
+        let mut rasterizer = Rasterizer::new(8, 4);
+        rasterizer.clear(Color::black());
+        rasterizer.set_pixel(7, 3, 0.25, Color::new(10, 20, 30, 40));
+
+        let pixel = rasterizer.read_pixel(7, 3).unwrap();
+        assert_eq!((pixel.r, pixel.g, pixel.b, pixel.a), (10, 20, 30, 40));
+        assert_eq!(rasterizer.read_depth(7, 3), Some(0.25));
+        assert_eq!(rasterizer.read_pixel(0, 0).unwrap().to_u32(), Color::black().to_u32());
+        assert_eq!(rasterizer.read_depth(0, 0), Some(rasterizer.depth_clear_value()));
+
+        for (x, y) in [(-1, 0), (0, -1), (8, 0), (0, 4)] {
+            assert!(rasterizer.read_pixel(x, y).is_none());
+            assert!(rasterizer.read_depth(x, y).is_none());
+        }
+    }
+
+    #[test]
+    fn test_depth_buffer_normalize() {
+        let mut rasterizer = Rasterizer::new(4, 1);
+        rasterizer.set_depth_range(1.0, 11.0);
+        let depth = rasterizer.depth_buffer();
+
+        assert!(depth.normalize(-1.0).abs() < 1e-12);
+        assert!((depth.normalize(1.0) - 1.0).abs() < 1e-12);
+        // NDC z of a point 6 units away, halfway through the range
+        let ndc_mid = (11.0 + 1.0 - 2.0 * 11.0 / 6.0) / (11.0 - 1.0);
+        assert!((depth.normalize(ndc_mid) - 0.5).abs() < 1e-12);
+
+        rasterizer.clear(Color::black());
+        rasterizer.set_pixel(0, 0, -1.0, Color::white());
+        let gray = rasterizer.depth_buffer().to_grayscale();
+        assert_eq!(gray[0], Color::white().to_u32());
+        assert_eq!(gray[1], Color::new(0, 0, 0, 255).to_u32());
+
+        rasterizer.set_depth_projection(ProjectionMode::Orthographic);
+        let depth = rasterizer.depth_buffer();
+        assert!((depth.normalize(0.0) - 0.5).abs() < 1e-12);
+        assert!((depth.normalize(-0.5) - 0.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_reverse_z_depth_test() {
+        let mut rasterizer = Rasterizer::new(100, 100);
+        rasterizer.clear(Color::black());
+        rasterizer.clear_depth(0.0);
+        rasterizer.set_depth_test_mode(DepthTestMode::GreaterEqual);
+
+        let near = Color::new(255, 0, 0, 255);
+        rasterizer.draw_triangle_depth(
+            Vec3::new(10.0, 10.0, 0.5),
+            Vec3::new(90.0, 10.0, 0.5),
+            Vec3::new(50.0, 90.0, 0.5),
+            near,
+        );
+        let index = 40 * 100 + 50;
+        assert_eq!(rasterizer.color_buffer[index], near.to_u32());
+
+        // Smaller z is farther away with reverse-Z and must be rejected
+        let far = Color::new(0, 255, 0, 255);
+        rasterizer.draw_triangle_depth(
+            Vec3::new(10.0, 10.0, 0.3),
+            Vec3::new(90.0, 10.0, 0.3),
+            Vec3::new(50.0, 90.0, 0.3),
+            far,
+        );
+        assert_eq!(rasterizer.color_buffer[index], near.to_u32());
+        assert!((rasterizer.depth_buffer.get(index) - 0.5).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_blit() {
+        let mut rasterizer = Rasterizer::new(20, 20);
+        rasterizer.clear(Color::black());
+        let red = Color::new(255, 0, 0, 255).to_u32();
+        let clear = Color::new(0, 255, 0, 0).to_u32();
+        // 2x2 sprite with a transparent top-right pixel
+        let sprite = [red, clear, red, red];
+
+        rasterizer.blit(&sprite, 2, 2, 5, 5);
+        let buffer = rasterizer.get_color_buffer();
+        assert_eq!(buffer[5 * 20 + 5], red);
+        assert_eq!(buffer[5 * 20 + 6], Color::black().to_u32());
+        assert_eq!(buffer[6 * 20 + 6], red);
+
+        // Doubled in size, each source pixel covers a 2x2 block; the part
+        // past the edge is dropped
+        rasterizer.clear(Color::black());
+        rasterizer.blit_scaled(&sprite, 2, 2, 17, 0, 4, 4);
+        let buffer = rasterizer.get_color_buffer();
+        assert_eq!(buffer[17], red);
+        assert_eq!(buffer[20 + 18], red);
+        assert_eq!(buffer[19], Color::black().to_u32());
+        assert_eq!(buffer[3 * 20 + 19], red);
+        let drawn = buffer.iter().filter(|&&p| p == red).count();
+        assert_eq!(drawn, 10);
+
+        // Empty sources draw nothing
+        rasterizer.clear(Color::black());
+        rasterizer.blit_scaled(&[], 2, 0, 0, 0, 4, 4);
+        rasterizer.blit_scaled(&[], 0, 2, 0, 0, 4, 4);
+        assert!(rasterizer.get_color_buffer().iter().all(|&p| p == Color::black().to_u32()));
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut rasterizer = Rasterizer::new(100, 100);
+        rasterizer.clear(Color::new(0, 0, 255, 255));
+        rasterizer.set_scissor(50, 30, 50, 50);
+        rasterizer.resize(60, 40);
+
+        assert_eq!(rasterizer.get_color_buffer().len(), 60 * 40);
+        assert_eq!(rasterizer.depth_buffer().len(), 60 * 40);
+        assert!(rasterizer.get_color_buffer().iter().all(|&p| p == Color::new(0, 0, 255, 255).to_u32()));
+
+        // The scissor shrank with the buffer, so nothing lands out of bounds
+        rasterizer.fill_rect(0, 0, 100, 100, Color::white());
+        let white = rasterizer.get_color_buffer().iter().filter(|&&p| p == Color::white().to_u32()).count();
+        assert_eq!(white, 10 * 10);
+    }
+
+    #[test]
+    fn test_draw_rects() {
+        let mut rasterizer = Rasterizer::new(10, 10);
+        rasterizer.clear(Color::black());
+        let white = Color::white().to_u32();
+        let count = |r: &Rasterizer, value: u32| r.get_color_buffer().iter().filter(|&&p| p == value).count();
+
+        rasterizer.draw_rect(2, 2, 4, 3, Color::white());
+        assert_eq!(count(&rasterizer, white), 10);
+        assert_eq!(rasterizer.get_color_buffer()[3 * 10 + 3], Color::black().to_u32());
+
+        // Off-screen and degenerate rectangles are clipped or ignored
+        rasterizer.clear(Color::black());
+        rasterizer.draw_rect(-5, -5, 1_000_000, 8, Color::white());
+        assert_eq!(count(&rasterizer, white), 10);
+        rasterizer.draw_filled_rect(8, 8, 100, 100, Color::white());
+        rasterizer.draw_filled_rect(0, 0, -3, 4, Color::white());
+        assert_eq!(count(&rasterizer, white), 14);
+
+        rasterizer.clear(Color::new(0, 0, 200, 255));
+        rasterizer.draw_filled_rect_alpha(-1, -1, 3, 3, Color::new(255, 0, 0, 128));
+        let blended = Color::from_u32(rasterizer.get_color_buffer()[11]);
+        assert_eq!((blended.r, blended.g, blended.b), (128, 0, 100));
+        assert_eq!(count(&rasterizer, blended.to_u32()), 4);
+    }
+
+    #[test]
+    fn test_scissor() {
+        let mut rasterizer = Rasterizer::new(800, 600);
+        rasterizer.clear(Color::black());
+        let background = Color::black().to_u32();
+        let color = Color::white();
+
+        rasterizer.set_scissor(400, 0, 400, 600);
+        rasterizer.draw_line(Vec2::new(0.0, 300.0), Vec2::new(799.0, 300.0), color);
+        let row = &rasterizer.color_buffer[300 * 800..301 * 800];
+        assert!(row[..400].iter().all(|&p| p == background));
+        assert!(row[400..].iter().all(|&p| p == color.to_u32()));
+
+        rasterizer.clear_scissor();
+        rasterizer.draw_line(Vec2::new(0.0, 100.0), Vec2::new(799.0, 100.0), color);
+        let row = &rasterizer.color_buffer[100 * 800..101 * 800];
+        assert!(row.iter().all(|&p| p == color.to_u32()));
+    }
+
+    #[test]
+    fn test_rect_operations() {
+        let mut rasterizer = Rasterizer::new(100, 100);
+        rasterizer.clear(Color::black());
+        let background = Color::black().to_u32();
+        let fill = Color::new(0, 0, 255, 255).to_u32();
+
+        rasterizer.fill_rect(-5, 10, 20, 30, Color::new(0, 0, 255, 255));
+        for y in 0..100 {
+            for x in 0..100 {
+                let inside = x < 15 && (10..40).contains(&y);
+                let expected = if inside { fill } else { background };
+                assert_eq!(rasterizer.color_buffer[y * 100 + x], expected);
+            }
+        }
+
+        rasterizer.set_pixel(5, 20, 0.5, Color::white());
+        rasterizer.clear_rect(0, 15, 10, 10);
+        assert_eq!(rasterizer.color_buffer[20 * 100 + 5], background);
+        assert_eq!(rasterizer.depth_buffer.get(20 * 100 + 5), f64::INFINITY);
+        assert_eq!(rasterizer.color_buffer[12 * 100 + 5], fill);
+
+        // 4x4 source copied partly off the right edge
+        let src: Vec<u32> = (0..16).collect();
+        rasterizer.copy_rect(&src, 4, 1, 1, 98, 50, 3, 3);
+        assert_eq!(rasterizer.color_buffer[50 * 100 + 98], 5);
+        assert_eq!(rasterizer.color_buffer[52 * 100 + 99], 14);
+        assert_eq!(rasterizer.color_buffer[53 * 100 + 98], background);
+    }
+
+    #[test]
+    fn test_overdraw_tracking() {
+        let mut rasterizer = Rasterizer::new(100, 100);
+        rasterizer.enable_overdraw_tracking(true);
+        rasterizer.clear(Color::black());
+
+        // Nested triangles sharing a corner, each closer than the last
+        for (size, z) in [(100.0, 0.9), (50.0, 0.5), (30.0, 0.1)] {
+            rasterizer.draw_triangle_depth(
+                Vec3::new(0.0, 0.0, z),
+                Vec3::new(size, 0.0, z),
+                Vec3::new(0.0, size, z),
+                Color::white(),
+            );
+        }
+
+        let counts = rasterizer.get_overdraw_counts().unwrap();
+        assert!(counts[5 * 100 + 5] >= 3);
+        assert_eq!(counts[5 * 100 + 80], 1);
+        assert_eq!(counts[90 * 100 + 90], 0);
+        assert_eq!(rasterizer.get_max_overdraw(), 3);
+        assert!(rasterizer.get_average_overdraw() > 1.0);
+
+        let heatmap = rasterizer.get_overdraw_heatmap();
+        assert_eq!(heatmap[5 * 100 + 5], Color::new(255, 0, 0, 255).to_u32());
+
+        rasterizer.clear(Color::black());
+        assert_eq!(rasterizer.get_max_overdraw(), 0);
+    }
+
+    #[test]
+    fn test_scanline_matches_bbox() {
+        let triangles = [
+            [Vec3::new(10.5, 3.2, 0.1), Vec3::new(190.0, 40.7, 0.5), Vec3::new(60.3, 170.1, 0.9)],
+            [Vec3::new(-50.0, -20.0, 0.0), Vec3::new(250.0, 100.0, 0.0), Vec3::new(20.0, 260.0, 0.0)],
+            [Vec3::new(100.0, 10.0, 0.0), Vec3::new(100.0, 190.0, 0.0), Vec3::new(101.0, 100.0, 0.0)],
+            [Vec3::new(5.0, 50.0, 0.0), Vec3::new(195.0, 50.0, 0.0), Vec3::new(80.0, 50.5, 0.0)],
+        ];
+        for [v0, v1, v2] in triangles {
+            let mut scanline = Rasterizer::new(200, 200);
+            let mut bbox = Rasterizer::new(200, 200);
+            scanline.draw_triangle_scanline(v0, v1, v2, |w| Color::new((w[0] * 255.0) as u8, 0, 0, 255));
+            bbox.draw_triangle_bbox(v0, v1, v2, |w| Color::new((w[0] * 255.0) as u8, 0, 0, 255));
+            assert_eq!(scanline.color_buffer, bbox.color_buffer);
+        }
+    }
+
+    // Timing only, so it asserts nothing and is skipped by default. Run with
+    // cargo test --release bench_triangle_fill -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_triangle_fill() {
+        use std::time::Instant;
+
+        // Covers about 60% of an 800x600 buffer
+        let v0 = Vec3::new(0.0, 0.0, 0.5);
+        let v1 = Vec3::new(800.0, 120.0, 0.5);
+        let v2 = Vec3::new(160.0, 600.0, 0.5);
+        let mut rasterizer = Rasterizer::new(800, 600);
+        let runs = 200;
+
+        let mut time = |scanline: bool| {
+            let start = Instant::now();
+            for _ in 0..runs {
+                rasterizer.clear(Color::black());
+                if scanline {
+                    rasterizer.draw_triangle_scanline(v0, v1, v2, |_| Color::white());
+                } else {
+                    rasterizer.draw_triangle_bbox(v0, v1, v2, |_| Color::white());
+                }
+            }
+            start.elapsed().as_secs_f64() * 1000.0 / runs as f64
+        };
+        let bbox_ms = time(false);
+        let scanline_ms = time(true);
+        println!("bounding box: {:.3} ms, scanline: {:.3} ms", bbox_ms, scanline_ms);
+    }
+
+    fn drawn_pixels(rasterizer: &Rasterizer) -> Vec<(i32, i32)> {
+        let background = Color::black().to_u32();
+        rasterizer.color_buffer.iter().enumerate()
+            .filter(|(_, &p)| p != background)
+            .map(|(i, _)| ((i % rasterizer.width) as i32, (i / rasterizer.width) as i32))
+            .collect()
+    }
+
+    #[test]
+    fn test_circles() {
+        let mut rasterizer = Rasterizer::new(100, 100);
+        rasterizer.clear(Color::black());
+        rasterizer.draw_circle(Vec2::new(50.0, 50.0), 0.0, Color::white());
+        assert_eq!(drawn_pixels(&rasterizer), vec![(50, 50)]);
+
+        for filled in [false, true] {
+            rasterizer.clear(Color::black());
+            if filled {
+                rasterizer.draw_filled_circle(Vec2::new(50.0, 50.0), 5.0, Color::white());
+            } else {
+                rasterizer.draw_circle(Vec2::new(50.0, 50.0), 5.0, Color::white());
+            }
+            let pixels = drawn_pixels(&rasterizer);
+            for (qx, qy) in [(1, 1), (-1, 1), (-1, -1), (1, -1)] {
+                assert!(pixels.iter().any(|&(x, y)| (x - 50) * qx > 0 && (y - 50) * qy > 0));
+            }
+            // Center pixel is only set when filled
+            assert_eq!(pixels.contains(&(50, 50)), filled);
+        }
+
+        // Clipped at the framebuffer edges
+        rasterizer.clear(Color::black());
+        rasterizer.draw_filled_circle(Vec2::new(0.0, 99.0), 20.0, Color::white());
+        rasterizer.draw_circle(Vec2::new(99.0, 0.0), 20.0, Color::white());
+        assert!(drawn_pixels(&rasterizer).iter().all(|&(x, y)| (x <= 20 && y >= 79) || (x >= 79 && y <= 20)));
+    }
+
+    #[test]
+    fn test_ellipses() {
+        let mut rasterizer = Rasterizer::new(200, 200);
+        rasterizer.clear(Color::black());
+        rasterizer.draw_circle(Vec2::new(100.0, 100.0), 10.0, Color::white());
+        let outline = drawn_pixels(&rasterizer);
+        assert!(outline.len() > 40);
+        for (x, y) in outline {
+            let distance = (((x - 100).pow(2) + (y - 100).pow(2)) as f64).sqrt();
+            assert!((distance - 10.0).abs() <= 1.0);
+        }
+
+        rasterizer.clear(Color::black());
+        rasterizer.fill_ellipse(100, 100, 50, 50, Color::white());
+        let area = drawn_pixels(&rasterizer).len() as f64;
+        let expected = std::f64::consts::PI * 50.0 * 50.0;
+        assert!((area / expected - 1.0).abs() <= 0.02);
+
+        // Partly off-screen shapes are clipped rather than wrapped
+        rasterizer.clear(Color::black());
+        rasterizer.fill_ellipse(0, 0, 30, 20, Color::white());
+        rasterizer.draw_ellipse(199, 199, 30, 20, Color::white());
+        assert!(drawn_pixels(&rasterizer).iter().all(|&(x, y)| (x <= 30 && y <= 20) || (x >= 169 && y >= 179)));
+    }
 }
@@ -1,88 +1,737 @@
 use crate::math::{Vec2, Vec3, Mat4};
-use crate::geometry::{Mesh, Vertex};
+use crate::geometry::{BoundingBox, LodGroup, Mesh, Vertex};
 use crate::camera::Camera;
+use crate::lighting::{Light, Material};
 use crate::rasterizer::{Rasterizer, Color};
+use crate::scene::{NodeId, Scene};
+use crate::shape_factory::ShapeFactory;
+
+// Offscreen buffer holding one node id per pixel, see Renderer::render_pick_buffer
+pub struct PickBuffer {
+    pub data: Vec<u32>,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl PickBuffer {
+    // Ids are stored off by one so that 0 means "no node"
+    fn encode(id: NodeId) -> Color {
+        Color::from_u32(id as u32 + 1)
+    }
+
+    pub fn node_at(&self, x: usize, y: usize) -> Option<NodeId> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        match self.data[y * self.width + x] {
+            0 => None,
+            value => Some(value as NodeId - 1),
+        }
+    }
+}
+
+// How render_mesh draws meshes. Flat lights each face by its normal, Gouraud
+// lights the vertices and interpolates the result across the face.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShadingMode {
+    Wireframe,
+    Flat,
+    Gouraud,
+}
+
+impl ShadingMode {
+    pub fn next(self) -> ShadingMode {
+        match self {
+            ShadingMode::Wireframe => ShadingMode::Flat,
+            ShadingMode::Flat => ShadingMode::Gouraud,
+            ShadingMode::Gouraud => ShadingMode::Wireframe,
+        }
+    }
+}
+
+// Share of the base color kept on faces turned away from the light
+const AMBIENT_BRIGHTNESS: f64 = 0.2;
+
+// Per-vertex input to filled shading, kept here so Vertex doesn't carry
+// renderer state
+#[derive(Debug, Clone, Copy)]
+struct LitVertex {
+    color: Color,
+    brightness: f64,
+}
+
+impl LitVertex {
+    fn interpolate(vertices: [LitVertex; 3], weights: [f64; 3]) -> LitVertex {
+        LitVertex {
+            color: Color::interpolate(vertices.map(|v| v.color), weights),
+            brightness: vertices.iter().zip(weights).map(|(v, w)| v.brightness * w).sum(),
+        }
+    }
+
+    fn shade(&self, base: Color) -> Color {
+        let level = (self.brightness * 255.0).round().clamp(0.0, 255.0) as u8;
+        base.modulate(self.color).modulate(Color::new(level, level, level, 255))
+    }
+}
+
+// Which faces are skipped when filling, by screen-space winding
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CullMode {
+    None,
+    Back,
+    Front,
+}
+
+// Standard orderings for queued render commands; lower draws first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderPriority {
+    Background = 0,
+    Geometry = 100,
+    Transparent = 200,
+    Overlay = 300,
+    UI = 400,
+}
+
+#[derive(Debug, Clone)]
+pub enum RenderCommandKind {
+    Mesh { mesh: Box<Mesh>, transform: Mat4, color: Color },
+    Rect { x: i32, y: i32, width: usize, height: usize, color: Color },
+}
+
+// Deferred draw call, executed in priority order by Renderer::flush
+#[derive(Debug, Clone)]
+pub struct RenderCommand {
+    pub priority: i32,
+    pub kind: RenderCommandKind,
+}
+
+impl RenderCommand {
+    pub fn mesh(mesh: &Mesh, transform: &Mat4, color: Color) -> Self {
+        Self {
+            priority: RenderPriority::Geometry as i32,
+            kind: RenderCommandKind::Mesh { mesh: Box::new(mesh.clone()), transform: *transform, color },
+        }
+    }
+
+    pub fn rect(x: i32, y: i32, width: usize, height: usize, color: Color) -> Self {
+        Self {
+            priority: RenderPriority::UI as i32,
+            kind: RenderCommandKind::Rect { x, y, width, height, color },
+        }
+    }
+
+    pub fn with_priority(mut self, priority: RenderPriority) -> Self {
+        self.priority = priority as i32;
+        self
+    }
+}
+
+pub type LayerId = usize;
+
+// Layers created by Renderer::new
+pub const SCENE_LAYER: LayerId = 0;
+pub const HUD_LAYER: LayerId = 1;
+
+// Named color and depth buffer pair. While a layer is active its buffers are
+// swapped into Renderer::rasterizer and this slot holds an empty placeholder.
+pub struct FramebufferLayer {
+    pub name: String,
+    rasterizer: Rasterizer,
+}
 
 pub struct Renderer {
     rasterizer: Rasterizer,
+    layers: Vec<FramebufferLayer>,
+    active_layer: LayerId,
+    composite: Option<Vec<u32>>,
     width: usize,
     height: usize,
     clear_color: Color,
-    wireframe_mode: bool,
+    shading_mode: ShadingMode,
+    light_direction: Vec3,
+    cull_mode: CullMode,
+    point_size: u32,
+    commands: Vec<RenderCommand>,
 }
 
 impl Renderer {
     pub fn new(width: usize, height: usize) -> Self {
+        Self::new_msaa(width, height, 1)
+    }
+
+    // Renderer whose scene layer uses MSAA, see Rasterizer::new_msaa
+    pub fn new_msaa(width: usize, height: usize, samples: u8) -> Self {
         Self {
-            rasterizer: Rasterizer::new(width, height),
+            rasterizer: Rasterizer::new_msaa(width, height, samples),
+            layers: vec![
+                FramebufferLayer { name: "scene".to_string(), rasterizer: Rasterizer::new(0, 0) },
+                FramebufferLayer { name: "hud".to_string(), rasterizer: Rasterizer::new(width, height) },
+            ],
+            active_layer: SCENE_LAYER,
+            composite: None,
             width,
             height,
             clear_color: Color::black(),
-            wireframe_mode: false,
+            shading_mode: ShadingMode::Flat,
+            light_direction: Vec3::new(0.0, -0.5, 1.0).normalize(),
+            cull_mode: CullMode::Back,
+            point_size: 1,
+            commands: Vec::new(),
         }
     }
 
+    // Clears every layer: the scene layer to the clear color and the others
+    // to transparent, so they only cover what is drawn on them
     pub fn clear(&mut self) {
-        self.rasterizer.clear(self.clear_color);
+        for id in 0..self.layers.len() {
+            let color = if id == SCENE_LAYER { self.clear_color } else { Color::new(0, 0, 0, 0) };
+            self.layer_rasterizer_mut(id).clear(color);
+        }
+        self.composite = None;
+    }
+
+    // Adds a transparent layer with its own depth buffer
+    pub fn add_layer(&mut self, name: &str) -> LayerId {
+        let mut rasterizer = Rasterizer::new(self.width, self.height);
+        rasterizer.clear(Color::new(0, 0, 0, 0));
+        self.layers.push(FramebufferLayer { name: name.to_string(), rasterizer });
+        self.layers.len() - 1
+    }
+
+    pub fn layer_id(&self, name: &str) -> Option<LayerId> {
+        self.layers.iter().position(|layer| layer.name == name)
+    }
+
+    // Subsequent drawing goes to the given layer; unknown ids are ignored
+    pub fn set_active_layer(&mut self, id: LayerId) {
+        if id == self.active_layer || id >= self.layers.len() {
+            return;
+        }
+        std::mem::swap(&mut self.rasterizer, &mut self.layers[self.active_layer].rasterizer);
+        std::mem::swap(&mut self.rasterizer, &mut self.layers[id].rasterizer);
+        self.active_layer = id;
+    }
+
+    pub fn active_layer(&self) -> LayerId {
+        self.active_layer
+    }
+
+    fn layer_rasterizer(&self, id: LayerId) -> &Rasterizer {
+        if id == self.active_layer { &self.rasterizer } else { &self.layers[id].rasterizer }
+    }
+
+    fn layer_rasterizer_mut(&mut self, id: LayerId) -> &mut Rasterizer {
+        if id == self.active_layer { &mut self.rasterizer } else { &mut self.layers[id].rasterizer }
+    }
+
+    // Blends the layers front to back by alpha, so the first layer in `order`
+    // covers the rest wherever it is opaque. Whatever no layer covers shows the
+    // clear color. get_buffer returns the result until the next clear.
+    pub fn composite_layers(&mut self, order: &[LayerId]) {
+        let layer_count = self.layers.len();
+        for &id in order.iter().filter(|&&id| id < layer_count) {
+            self.layer_rasterizer_mut(id).resolve();
+        }
+        let buffers: Vec<&[u32]> = order.iter()
+            .filter(|&&id| id < self.layers.len())
+            .map(|&id| self.layer_rasterizer(id).get_color_buffer())
+            .collect();
+
+        let background = self.clear_color;
+        let output = (0..self.width * self.height)
+            .map(|i| {
+                let mut rgb = [0.0; 3];
+                let mut coverage = 0.0;
+                let layers = buffers.iter().map(|buffer| Color::from_u32(buffer[i]));
+                for color in layers.chain(std::iter::once(Color { a: 255, ..background })) {
+                    let weight = (1.0 - coverage) * color.a as f64 / 255.0;
+                    for (sum, channel) in rgb.iter_mut().zip([color.r, color.g, color.b]) {
+                        *sum += weight * channel as f64;
+                    }
+                    coverage += weight;
+                    if coverage >= 1.0 {
+                        break;
+                    }
+                }
+                let [r, g, b] = rgb.map(|c| c.round().clamp(0.0, 255.0) as u8);
+                Color::new(r, g, b, 255).to_u32()
+            })
+            .collect();
+        self.composite = Some(output);
     }
 
     pub fn set_clear_color(&mut self, color: Color) {
         self.clear_color = color;
     }
 
-    pub fn toggle_wireframe(&mut self) {
-        self.wireframe_mode = !self.wireframe_mode;
+    pub fn set_shading_mode(&mut self, mode: ShadingMode) {
+        self.shading_mode = mode;
+    }
+
+    pub fn shading_mode(&self) -> ShadingMode {
+        self.shading_mode
+    }
+
+    // Direction the light used by flat and Gouraud shading travels in
+    pub fn set_light_direction(&mut self, direction: Vec3) {
+        self.light_direction = direction.normalize();
+    }
+
+    // Applies to filled rendering; wireframes always show every edge
+    pub fn set_cull_mode(&mut self, mode: CullMode) {
+        self.cull_mode = mode;
+    }
+
+    pub fn cull_mode(&self) -> CullMode {
+        self.cull_mode
+    }
+
+    pub fn set_point_size(&mut self, size: u32) {
+        self.point_size = size.max(1);
     }
 
-    pub fn get_buffer(&self) -> &[u32] {
+    pub fn point_size(&self) -> u32 {
+        self.point_size
+    }
+
+    // The composited layers if composite_layers ran since the last clear,
+    // otherwise the active layer, resolved if it is multisampled
+    pub fn get_buffer(&mut self) -> &[u32] {
+        if let Some(composite) = &self.composite {
+            return composite;
+        }
+        self.rasterizer.resolve();
         self.rasterizer.get_color_buffer()
     }
 
     pub fn render_mesh(&mut self, mesh: &Mesh, transform: &Mat4, camera: &Camera) {
-        let view_projection = camera.get_view_projection_matrix();
-        let model_view_projection = view_projection.multiply(transform);
+        if !self.is_aabb_visible(&mesh.calculate_bounding_box(), transform, camera) {
+            return;
+        }
+        self.draw_mesh_in_mode(mesh, transform, camera);
+    }
+
+    pub fn submit(&mut self, command: RenderCommand) {
+        self.commands.push(command);
+    }
+
+    // Queues a mesh that draws over all scene geometry
+    pub fn submit_overlay(&mut self, mesh: &Mesh, transform: &Mat4, color: Color) {
+        self.submit(RenderCommand::mesh(mesh, transform, color).with_priority(RenderPriority::Overlay));
+    }
+
+    pub fn submit_ui_rect(&mut self, x: i32, y: i32, width: usize, height: usize, color: Color) {
+        self.submit(RenderCommand::rect(x, y, width, height, color));
+    }
+
+    // Executes and drains the queued commands, lowest priority first and in
+    // submission order within a priority. The depth buffer is cleared before
+    // the first overlay or UI command so those are never hidden by the scene.
+    pub fn flush(&mut self, camera: &Camera) {
+        let mut commands = std::mem::take(&mut self.commands);
+        commands.sort_by_key(|command| command.priority);
+
+        let mut depth_cleared = false;
+        for command in commands {
+            if !depth_cleared && command.priority >= RenderPriority::Overlay as i32 {
+                let clear_value = self.rasterizer.depth_clear_value();
+                self.rasterizer.clear_depth(clear_value);
+                depth_cleared = true;
+            }
+
+            match command.kind {
+                RenderCommandKind::Mesh { mesh, transform, color } => {
+                    self.render_mesh_with_color(&mesh, &transform, camera, color);
+                }
+                RenderCommandKind::Rect { x, y, width, height, color } => {
+                    self.rasterizer.fill_rect(x, y, width, height, color);
+                }
+            }
+        }
+    }
+
+    // Renders the same mesh once per transform
+    pub fn render_instanced(&mut self, mesh: &Mesh, transforms: &[Mat4], camera: &Camera) {
+        for transform in transforms {
+            self.draw_mesh_in_mode(mesh, transform, camera);
+        }
+    }
 
-        // Transform vertices
-        let transformed_vertices: Vec<Vec3> = mesh.vertices.iter()
-            .map(|v| model_view_projection.transform_vec3(&v.position))
+    // Wireframe or lit fill with face colors (white by default), depending on
+    // the shading mode
+    fn draw_mesh_in_mode(&mut self, mesh: &Mesh, transform: &Mat4, camera: &Camera) {
+        match self.shading_mode {
+            ShadingMode::Wireframe => self.draw_mesh(mesh, transform, camera),
+            ShadingMode::Flat | ShadingMode::Gouraud => {
+                self.fill_mesh(mesh, transform, camera, None, self.cull_mode);
+            }
+        }
+    }
+
+    // Renders the level of detail matching the camera's distance to the transform origin
+    pub fn render_lod_group(&mut self, group: &LodGroup, transform: &Mat4, camera: &Camera) {
+        let origin = transform.transform_vec3(&Vec3::zero());
+        let distance = (origin - camera.position).length();
+        if let Some(mesh) = group.select(distance) {
+            self.render_mesh(mesh, transform, camera);
+        }
+    }
+
+    // False when all eight corners of the transformed box lie outside one of
+    // the frustum planes. Boxes near frustum corners may be kept conservatively.
+    pub fn is_aabb_visible(&self, bbox: &BoundingBox, transform: &Mat4, camera: &Camera) -> bool {
+        let corners: Vec<Vec3> = (0..8)
+            .map(|i| {
+                let corner = Vec3::new(
+                    if i & 1 == 0 { bbox.min.x } else { bbox.max.x },
+                    if i & 2 == 0 { bbox.min.y } else { bbox.max.y },
+                    if i & 4 == 0 { bbox.min.z } else { bbox.max.z },
+                );
+                transform.transform_vec3(&corner)
+            })
             .collect();
 
-        // Project to screen space
-        let screen_vertices: Vec<Vec2> = transformed_vertices.iter()
-            .map(|v| self.to_screen_space(v))
+        camera.get_frustum_planes().iter().all(|plane| {
+            corners.iter().any(|corner| plane.distance_to_point(corner) >= 0.0)
+        })
+    }
+
+    // Indices of the instances whose bounds intersect the view frustum
+    pub fn frustum_cull_instances(&self, mesh: &Mesh, transforms: &[Mat4], camera: &Camera) -> Vec<usize> {
+        let local_bounds = mesh.calculate_bounding_box();
+
+        transforms.iter()
+            .enumerate()
+            .filter(|(_, transform)| self.is_aabb_visible(&local_bounds, transform, camera))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    // Matches the depth buffer's linear depth readback to the camera
+    fn sync_depth_range(&mut self, camera: &Camera) {
+        self.rasterizer.set_depth_range(camera.near, camera.far);
+        self.rasterizer.set_depth_projection(camera.projection_mode);
+    }
+
+    fn draw_mesh(&mut self, mesh: &Mesh, transform: &Mat4, camera: &Camera) {
+        let model_view = camera.get_view_matrix().multiply(transform);
+        let projection = camera.get_projection_matrix();
+
+        // Transform vertices to view space, where the near plane is z = -near
+        let view_vertices: Vec<Vec3> = mesh.vertices.iter()
+            .map(|v| model_view.transform_vec3(&v.position))
             .collect();
 
-        // Draw triangles
+        // Draw triangle edges, cut off at the near plane
         for face in &mesh.faces {
-            let v0 = screen_vertices[face.vertices[0]];
-            let v1 = screen_vertices[face.vertices[1]];
-            let v2 = screen_vertices[face.vertices[2]];
+            let color = face.color.unwrap_or(Color::new(255, 255, 255, 255));
+            for k in 0..3 {
+                let a = view_vertices[face.vertices[k]];
+                let b = view_vertices[face.vertices[(k + 1) % 3]];
+                if let Some((a, b)) = clip_segment_near(a, b, camera.near) {
+                    let start = self.project_view_point(&a, &projection);
+                    let end = self.project_view_point(&b, &projection);
+                    self.rasterizer.draw_line(Vec2::new(start.x, start.y), Vec2::new(end.x, end.y), color);
+                }
+            }
+        }
+    }
+
+    // Draws every mesh edge once, however many faces share it
+    pub fn draw_wireframe_from_edges(&mut self, mesh: &Mesh, transform: &Mat4, camera: &Camera, color: Color) {
+        let model_view_projection = camera.get_view_projection_matrix().multiply(transform);
+        let screen_vertices: Vec<Option<Vec2>> = mesh.vertices.iter()
+            .map(|v| {
+                self.project_point(&v.position, &model_view_projection)
+                    .map(|p| Vec2::new(p.x, p.y))
+            })
+            .collect();
 
-            // Always draw wireframe for debugging
-            self.rasterizer.draw_triangle_wireframe(
-                v0, v1, v2,
-                Color::new(255, 255, 255, 255)
-            );
+        for (a, b) in mesh.get_unique_edges() {
+            if let (Some(start), Some(end)) = (screen_vertices[a], screen_vertices[b]) {
+                self.rasterizer.draw_line(start, end, color);
+            }
         }
     }
-    fn to_screen_space(&self, v: &Vec3) -> Vec2 {
-        // Proper perspective divide
-        if v.z.abs() < 0.001 {
-            return Vec2::new(0.0, 0.0);
+
+    // Draws an XYZ orientation widget anchored at the given screen position,
+    // using only the camera rotation so it never moves with the camera
+    pub fn draw_axis_gizmo(&mut self, camera: &Camera, screen_x: i32, screen_y: i32, size: f64) {
+        let mut rotation = camera.get_view_matrix();
+        for row in rotation.data.iter_mut().take(3) {
+            row[3] = 0.0;
+        }
+
+        let anchor = Vec2::new(screen_x as f64, screen_y as f64);
+        // Z first so that a Z axis pointing at the viewer still shows as a dot
+        let axes = [
+            (Vec3::new(0.0, 0.0, 1.0), Color::new(0, 0, 255, 255)),
+            (Vec3::new(1.0, 0.0, 0.0), Color::new(255, 0, 0, 255)),
+            (Vec3::new(0.0, 1.0, 0.0), Color::new(0, 255, 0, 255)),
+        ];
+        for (axis, color) in axes {
+            let dir = rotation.transform_vec3(&axis);
+            let end = anchor + Vec2::new(dir.x * size, -dir.y * size);
+            self.rasterizer.draw_line(anchor, end, color);
+        }
+    }
+
+    // Renders every visible mesh node in a flat color encoding its id
+    pub fn render_pick_buffer(&self, scene: &Scene, camera: &Camera) -> PickBuffer {
+        let mut pick = Renderer::new(self.width, self.height);
+        pick.rasterizer.clear(Color::new(0, 0, 0, 0));
+        scene.traverse_visible(|node| {
+            if let Some(mesh) = &node.mesh {
+                pick.fill_mesh(mesh, &node.transform.world_matrix, camera, Some(PickBuffer::encode(node.id)), CullMode::None);
+            }
+        });
+
+        PickBuffer {
+            data: pick.rasterizer.get_color_buffer().to_vec(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    // Renders the mesh in a single flat color, e.g. for selection highlights
+    pub fn render_mesh_with_color(&mut self, mesh: &Mesh, transform: &Mat4, camera: &Camera, color: Color) {
+        self.fill_mesh(mesh, transform, camera, Some(color), CullMode::Back);
+    }
+
+    // Solid red, green and blue arrows of length `scale` along the local axes
+    // of `transform`
+    pub fn draw_axes(&mut self, transform: &Mat4, camera: &Camera, scale: f64) {
+        for (arrow, color) in ShapeFactory::create_coordinate_axes(scale) {
+            self.render_mesh_with_color(&arrow, transform, camera, color);
+        }
+    }
+
+    // Phong-shades every visible mesh node with its material under the
+    // scene's lights
+    pub fn render_scene_shaded(&mut self, scene: &Scene, camera: &Camera) {
+        scene.traverse_visible(|node| {
+            if let Some(mesh) = &node.mesh {
+                self.render_mesh_shaded(mesh, &node.transform.world_matrix, camera, &node.material, scene.lights());
+            }
+        });
+    }
+
+    // Per-pixel Phong shading with vertex normals interpolated across each
+    // triangle (using screen-space barycentrics). Back faces are culled.
+    pub fn render_mesh_shaded(
+        &mut self,
+        mesh: &Mesh,
+        transform: &Mat4,
+        camera: &Camera,
+        material: &Material,
+        lights: &[Light],
+    ) {
+        if !self.is_aabb_visible(&mesh.calculate_bounding_box(), transform, camera) {
+            return;
+        }
+        self.sync_depth_range(camera);
+
+        let model_view_projection = camera.get_view_projection_matrix().multiply(transform);
+        let normal_matrix = transform.normal_matrix();
+        let world_vertices: Vec<(Vec3, Vec3, Vec2)> = mesh.vertices.iter()
+            .map(|v| {
+                let position = transform.transform_vec3(&v.position);
+                let normal = normal_matrix.transform_direction(&v.normal).normalize();
+                (position, normal, v.uv2)
+            })
+            .collect();
+        let screen_vertices: Vec<Option<Vec3>> = mesh.vertices.iter()
+            .map(|v| self.project_point(&v.position, &model_view_projection))
+            .collect();
+
+        for face in &mesh.faces {
+            let [Some(s0), Some(s1), Some(s2)] = face.vertices.map(|i| screen_vertices[i]) else {
+                continue;
+            };
+            let [p0, p1, p2] = [s0, s1, s2].map(|v| Vec2::new(v.x, v.y));
+            if !self.is_face_visible(p0, p1, p2) {
+                continue;
+            }
+
+            let [a, b, c] = face.vertices.map(|i| world_vertices[i]);
+            self.rasterizer.draw_triangle_shaded(s0, s1, s2, |w| {
+                let position = a.0 * w[0] + b.0 * w[1] + c.0 * w[2];
+                let normal = (a.1 * w[0] + b.1 * w[1] + c.1 * w[2]).normalize();
+                let uv2 = a.2 * w[0] + b.2 * w[1] + c.2 * w[2];
+                material.apply_lightmap(material.shade(position, normal, camera.position, lights), uv2)
+            });
+        }
+    }
+
+    // Draws only the back faces of a slightly enlarged copy of the mesh. Drawn
+    // after the mesh itself, the depth test leaves just a rim around it.
+    pub fn render_mesh_outline(
+        &mut self,
+        mesh: &Mesh,
+        transform: &Mat4,
+        camera: &Camera,
+        outline_color: Color,
+        outline_scale: f64,
+    ) {
+        let scale = 1.0 + outline_scale;
+        let enlarged = transform.multiply(&Mat4::scaling(scale, scale, scale));
+        self.fill_mesh(mesh, &enlarged, camera, Some(outline_color), CullMode::Front);
+    }
+
+    // Fills the mesh's faces, depth tested, in `color` or else each face's own
+    // color (white if unset) tinted by the interpolated vertex colors and lit
+    // according to the shading mode. Faces are clipped against the near plane
+    // first. Returns how many faces were drawn.
+    fn fill_mesh(
+        &mut self,
+        mesh: &Mesh,
+        transform: &Mat4,
+        camera: &Camera,
+        color: Option<Color>,
+        cull: CullMode,
+    ) -> usize {
+        self.sync_depth_range(camera);
+        let model_view = camera.get_view_matrix().multiply(transform);
+        let projection = camera.get_projection_matrix();
+        let normal_matrix = transform.normal_matrix();
+        let view_vertices: Vec<Vec3> = mesh.vertices.iter()
+            .map(|v| model_view.transform_vec3(&v.position))
+            .collect();
+
+        let mut drawn = 0;
+        for face in &mesh.faces {
+            let corners = face.vertices.map(|i| view_vertices[i]);
+            let pieces: Vec<([Vec3; 3], [Vec3; 3])> = clip_triangle_near(corners, camera.near)
+                .into_iter()
+                .map(|piece| (piece, piece.map(|p| self.project_view_point(&p, &projection))))
+                .collect();
+            let Some((_, [v0, v1, v2])) = pieces.first() else {
+                continue;
+            };
+
+            // Clipping keeps the winding, so any piece tells which way the face points
+            let [p0, p1, p2] = [v0, v1, v2].map(|v| Vec2::new(v.x, v.y));
+            let front = self.is_face_visible(p0, p1, p2);
+            let culled = match cull {
+                CullMode::None => false,
+                CullMode::Back => !front,
+                CullMode::Front => front,
+            };
+            if culled {
+                continue;
+            }
+
+            for (piece, [v0, v1, v2]) in pieces {
+                match color {
+                    Some(color) => self.rasterizer.draw_triangle_depth(v0, v1, v2, color),
+                    None => {
+                        let base = face.color.unwrap_or(Color::new(255, 255, 255, 255));
+                        let face_lit = face.vertices.map(|i| {
+                            let normal = match self.shading_mode {
+                                ShadingMode::Gouraud => mesh.vertices[i].normal,
+                                _ => face.normal,
+                            };
+                            LitVertex {
+                                color: mesh.vertices[i].color,
+                                brightness: self.brightness(&normal_matrix.transform_direction(&normal)),
+                            }
+                        });
+                        let lit = piece.map(|p| LitVertex::interpolate(face_lit, barycentric(corners, p)));
+                        self.rasterizer.draw_triangle_shaded(v0, v1, v2, |weights| {
+                            LitVertex::interpolate(lit, weights).shade(base)
+                        });
+                    }
+                }
+            }
+            drawn += 1;
+        }
+        drawn
+    }
+
+    // Lambert term for the renderer's light on top of the ambient floor
+    fn brightness(&self, normal: &Vec3) -> f64 {
+        let diffuse = normal.normalize().dot(&-self.light_direction).max(0.0);
+        AMBIENT_BRIGHTNESS + (1.0 - AMBIENT_BRIGHTNESS) * diffuse
+    }
+
+    // Draws every point as a point_size x point_size square; a point_size of 0
+    // uses the renderer's default set through set_point_size
+    pub fn draw_point_cloud(&mut self, points: &[Vec3], camera: &Camera, color: Color, point_size: u32) {
+        self.sync_depth_range(camera);
+        let view_projection = camera.get_view_projection_matrix();
+        let size = if point_size == 0 { self.point_size } else { point_size } as i32;
+        let offset = (size - 1) / 2;
+
+        for point in points {
+            if let Some(screen) = self.project_point(point, &view_projection) {
+                let x0 = screen.x as i32 - offset;
+                let y0 = screen.y as i32 - offset;
+                for y in y0..y0 + size {
+                    for x in x0..x0 + size {
+                        self.rasterizer.set_pixel(x, y, screen.z, color);
+                    }
+                }
+            }
+        }
+    }
+
+    // World-space points drawn as squares reaching `point_size - 1` pixels
+    // out from the projected pixel; the size defaults to 1 (a single pixel)
+    // and is clamped to 1..=8. Points behind the near plane are skipped.
+    pub fn render_point_cloud(&mut self, points: &[Vec3], color: Color, camera: &Camera, point_size: Option<u8>) {
+        let radius = point_size.unwrap_or(1).clamp(1, 8) as u32;
+        self.draw_point_cloud(points, camera, color, 2 * radius - 1);
+    }
+
+    // Projects a world-space point to screen space, keeping the NDC depth in z.
+    // Returns None for points in front of the near plane or behind the camera.
+    fn project_point(&self, point: &Vec3, view_projection: &Mat4) -> Option<Vec3> {
+        // Reject points in front of the near plane (z < -w in clip space),
+        // which works for both perspective and orthographic projections
+        let m = &view_projection.data;
+        let z = m[2][0] * point.x + m[2][1] * point.y + m[2][2] * point.z + m[2][3];
+        let w = m[3][0] * point.x + m[3][1] * point.y + m[3][2] * point.z + m[3][3];
+        if w <= 0.0 || z < -w {
+            return None;
         }
 
-        let inv_z = 1.0 / v.z;
-        let x = (v.x * inv_z + 1.0) * 0.5 * self.width as f64;
-        let y = (-v.y * inv_z + 1.0) * 0.5 * self.height as f64;
+        let ndc = view_projection.transform_vec3(point);
+        let screen = self.to_screen_space(&ndc);
+        Some(Vec3::new(screen.x, screen.y, ndc.z))
+    }
+
+    // Projects a view-space point already clipped against the near plane
+    fn project_view_point(&self, point: &Vec3, projection: &Mat4) -> Vec3 {
+        let ndc = projection.transform_vec3(point);
+        let screen = self.to_screen_space(&ndc);
+        Vec3::new(screen.x, screen.y, ndc.z)
+    }
+
+    fn to_screen_space(&self, v: &Vec3) -> Vec2 {
+        // Map normalized device coordinates to the viewport; the perspective
+        // divide already happened in transform_vec3
+        let x = (v.x + 1.0) * 0.5 * self.width as f64;
+        let y = (-v.y + 1.0) * 0.5 * self.height as f64;
 
         Vec2::new(x, y)
     }
 
     fn is_face_visible(&self, v0: Vec2, v1: Vec2, v2: Vec2) -> bool {
-        // Calculate signed area of triangle
+        // Calculate signed area of triangle; counter-clockwise faces end up
+        // clockwise (negative area) once screen y points down
         let area = (v1.x - v0.x) * (v2.y - v0.y) - (v2.x - v0.x) * (v1.y - v0.y);
-        area > 0.0
+        area < 0.0
     }
+
+    pub fn resize(&mut self, width: usize, height: usize) {
+        for id in 0..self.layers.len() {
+            self.layer_rasterizer_mut(id).resize(width, height);
+        }
+        self.composite = None;
+        self.width = width;
+        self.height = height;
+    }
+
     pub fn width(&self) -> usize {
         self.width
     }
@@ -92,14 +741,101 @@ impl Renderer {
     }
 }
 
+// Sutherland-Hodgman clip of a view-space triangle against the near plane
+// z = -near (the camera looks down -z). Yields no triangles when it lies
+// entirely on the camera's side of the plane, the triangle itself when
+// nothing is cut, and otherwise one or two triangles with the same winding.
+pub fn clip_triangle_near(verts: [Vec3; 3], near: f64) -> Vec<[Vec3; 3]> {
+    let inside = |p: &Vec3| p.z <= -near;
+
+    let mut polygon = Vec::with_capacity(4);
+    for k in 0..3 {
+        let (current, next) = (verts[k], verts[(k + 1) % 3]);
+        if inside(&current) {
+            polygon.push(current);
+        }
+        if inside(&current) != inside(&next) {
+            polygon.push(near_plane_intersection(current, next, near));
+        }
+    }
+
+    (1..polygon.len().saturating_sub(1))
+        .map(|i| [polygon[0], polygon[i], polygon[i + 1]])
+        .collect()
+}
+
+// Part of the view-space segment beyond the near plane, if any
+fn clip_segment_near(a: Vec3, b: Vec3, near: f64) -> Option<(Vec3, Vec3)> {
+    match (a.z <= -near, b.z <= -near) {
+        (true, true) => Some((a, b)),
+        (true, false) => Some((a, near_plane_intersection(a, b, near))),
+        (false, true) => Some((near_plane_intersection(a, b, near), b)),
+        (false, false) => None,
+    }
+}
+
+// Point where the segment crosses z = -near; the endpoints must lie on
+// opposite sides of the plane
+fn near_plane_intersection(a: Vec3, b: Vec3, near: f64) -> Vec3 {
+    let t = (-near - a.z) / (b.z - a.z);
+    a + (b - a) * t
+}
+
+// Barycentric weights of a point on the plane of the triangle
+fn barycentric(triangle: [Vec3; 3], p: Vec3) -> [f64; 3] {
+    let [a, b, c] = triangle;
+    let normal = (b - a).cross(&(c - a));
+    let area = normal.dot(&normal);
+    if area == 0.0 {
+        return [1.0 / 3.0; 3];
+    }
+    let wa = (c - b).cross(&(p - b)).dot(&normal) / area;
+    let wb = (a - c).cross(&(p - c)).dot(&normal) / area;
+    [wa, wb, 1.0 - wa - wb]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rasterizer::DepthTestMode;
+    use crate::texture::{Texture, TextureFilter};
+    use std::collections::HashSet;
 
     #[test]
     fn test_renderer_creation() {
         let renderer = Renderer::new(800, 600);
-        assert!(!renderer.wireframe_mode);
+        assert_eq!(renderer.shading_mode(), ShadingMode::Flat);
+    }
+
+    #[test]
+    fn test_layers_composite_front_to_back() {
+        let mut renderer = Renderer::new(40, 40);
+        renderer.set_clear_color(Color::new(0, 0, 255, 255));
+        renderer.clear();
+        assert_eq!(renderer.layer_id("hud"), Some(HUD_LAYER));
+
+        let camera = Camera::new(40.0, 40.0);
+        renderer.render_mesh_with_color(&ShapeFactory::create_cube(2.0), &Mat4::identity(), &camera, Color::new(255, 0, 0, 255));
+        let center = 20 * 40 + 20;
+        let scene_center = renderer.get_buffer()[center];
+
+        renderer.set_active_layer(HUD_LAYER);
+        renderer.rasterizer.draw_filled_rect(15, 15, 10, 10, Color::new(0, 255, 0, 255));
+        renderer.rasterizer.draw_filled_rect(0, 0, 4, 4, Color::new(0, 255, 0, 128));
+        // Drawing to the HUD leaves the scene layer alone
+        assert_eq!(renderer.layer_rasterizer(SCENE_LAYER).get_color_buffer()[center], scene_center);
+
+        let overlay = renderer.add_layer("overlay");
+        renderer.composite_layers(&[HUD_LAYER, SCENE_LAYER, overlay]);
+        let buffer = renderer.get_buffer();
+        assert_eq!(buffer[center], Color::new(0, 255, 0, 255).to_u32());
+        // Half transparent HUD over the blue background
+        assert_eq!(buffer[0], Color::new(0, 128, 127, 255).to_u32());
+        assert_eq!(buffer[40 * 40 - 1], Color::new(0, 0, 255, 255).to_u32());
+
+        renderer.clear();
+        renderer.set_active_layer(SCENE_LAYER);
+        assert_eq!(renderer.get_buffer()[center], Color::new(0, 0, 255, 255).to_u32());
     }
 
     #[test]
@@ -110,4 +846,492 @@ mod tests {
         assert_eq!(screen_point.x as i32, 400);
         assert_eq!(screen_point.y as i32, 300);
     }
+
+    fn count_drawn_pixels(renderer: &mut Renderer) -> usize {
+        let background = Color::black().to_u32();
+        renderer.get_buffer().iter().filter(|&&p| p != background).count()
+    }
+
+    #[test]
+    fn test_point_cloud_rendering() {
+        let mut renderer = Renderer::new(800, 600);
+        renderer.clear();
+        let camera = Camera::new(800.0, 600.0);
+
+        let cube = Mesh::create_cube(2.0);
+        let mut points = cube.to_point_cloud();
+        assert_eq!(points.len(), cube.vertices.len());
+
+        // A point behind the camera must not show up anywhere
+        points.push(Vec3::new(0.0, 0.0, -10.0));
+
+        renderer.draw_point_cloud(&points, &camera, Color::white(), 3);
+        assert_eq!(count_drawn_pixels(&mut renderer), cube.vertices.len() * 9);
+    }
+
+    #[test]
+    fn test_render_point_cloud() {
+        let mut renderer = Renderer::new(800, 600);
+        renderer.clear();
+        let camera = Camera::new(800.0, 600.0);
+
+        // Fibonacci spiral of 100 points on a unit sphere
+        let golden_angle = std::f64::consts::PI * (3.0 - 5.0_f64.sqrt());
+        let points: Vec<Vec3> = (0..100)
+            .map(|i| {
+                let y = 1.0 - 2.0 * (i as f64 + 0.5) / 100.0;
+                let r = (1.0 - y * y).sqrt();
+                let theta = golden_angle * i as f64;
+                Vec3::new(r * theta.cos(), y, r * theta.sin())
+            })
+            .collect();
+
+        renderer.render_point_cloud(&points, Color::white(), &camera, None);
+        let single = count_drawn_pixels(&mut renderer);
+        assert!((50..=100).contains(&single));
+
+        renderer.clear();
+        renderer.render_point_cloud(&points, Color::white(), &camera, Some(20));
+        assert!(count_drawn_pixels(&mut renderer) > single * 9);
+    }
+
+    #[test]
+    fn test_pick_buffer() {
+        let renderer = Renderer::new(800, 600);
+        let camera = Camera::new(800.0, 600.0);
+
+        let mut scene = Scene::new();
+        let left = scene.create_mesh_node("left".to_string(), Mesh::create_cube(1.0));
+        let right = scene.create_mesh_node("right".to_string(), Mesh::create_cube(1.0));
+        scene.get_node_mut(left).unwrap().transform.set_position(Vec3::new(-1.5, 0.0, 0.0));
+        scene.get_node_mut(right).unwrap().transform.set_position(Vec3::new(1.5, 0.0, 0.0));
+        scene.update_transforms();
+
+        let pick = renderer.render_pick_buffer(&scene, &camera);
+        let view_projection = camera.get_view_projection_matrix();
+        for (id, center) in [(left, Vec3::new(-1.5, 0.0, 0.0)), (right, Vec3::new(1.5, 0.0, 0.0))] {
+            let screen = renderer.project_point(&center, &view_projection).unwrap();
+            assert_eq!(pick.node_at(screen.x as usize, screen.y as usize), Some(id));
+        }
+        assert_eq!(pick.node_at(0, 0), None);
+        assert_eq!(pick.node_at(400, 300), None);
+    }
+
+    #[test]
+    fn test_mesh_outline() {
+        let mut renderer = Renderer::new(200, 200);
+        renderer.clear();
+        let mut camera = Camera::new(200.0, 200.0);
+        camera.set_position(Vec3::new(2.0, 1.5, 4.0));
+        camera.look_at(Vec3::zero());
+        camera.update();
+
+        let cube = Mesh::create_cube(1.0);
+        let fill = Color::new(255, 0, 0, 255);
+        let outline = Color::new(255, 255, 0, 255);
+
+        renderer.render_mesh_with_color(&cube, &Mat4::identity(), &camera, fill);
+        let silhouette: Vec<bool> = renderer.get_buffer().iter()
+            .map(|&p| p == fill.to_u32())
+            .collect();
+        let silhouette_size = silhouette.iter().filter(|&&inside| inside).count();
+        assert!(silhouette_size > 0);
+
+        renderer.render_mesh_outline(&cube, &Mat4::identity(), &camera, outline, 0.1);
+        let buffer = renderer.get_buffer();
+        let outline_pixels: Vec<usize> = (0..buffer.len())
+            .filter(|&i| buffer[i] == outline.to_u32())
+            .collect();
+
+        assert!(!outline_pixels.is_empty());
+        assert!(outline_pixels.iter().all(|&i| !silhouette[i]));
+        assert_eq!(buffer.iter().filter(|&&p| p == fill.to_u32()).count(), silhouette_size);
+    }
+
+    #[test]
+    fn test_render_mesh_cull_modes() {
+        let camera = Camera::new(200.0, 200.0);
+        let cube = Mesh::create_cube(1.0);
+        let turned = Mat4::trs(Vec3::zero(), Vec3::new(0.4, 0.7, 0.0), Vec3::new(1.0, 1.0, 1.0));
+
+        // Face-on, only the two front triangles face the camera
+        let mut renderer = Renderer::new(200, 200);
+        assert_eq!(renderer.cull_mode(), CullMode::Back);
+        assert_eq!(renderer.fill_mesh(&cube, &Mat4::identity(), &camera, None, CullMode::Back), 2);
+        // Turned so three sides show, exactly half of the faces are culled
+        assert_eq!(renderer.fill_mesh(&cube, &turned, &camera, None, CullMode::Back), 6);
+        assert_eq!(renderer.fill_mesh(&cube, &turned, &camera, None, CullMode::Front), 6);
+        assert_eq!(renderer.fill_mesh(&cube, &turned, &camera, None, CullMode::None), 12);
+
+        // A panel facing away disappears when filled but not as a wireframe
+        let mut panel = Mesh::create_cube(1.0);
+        panel.faces.retain(|face| face.normal.z > 0.5);
+        renderer.clear();
+        renderer.render_mesh(&panel, &Mat4::identity(), &camera);
+        assert_eq!(count_drawn_pixels(&mut renderer), 0);
+
+        renderer.set_shading_mode(ShadingMode::Wireframe);
+        renderer.render_mesh(&panel, &Mat4::identity(), &camera);
+        assert!(count_drawn_pixels(&mut renderer) > 0);
+    }
+
+    #[test]
+    fn test_vertex_colors_are_interpolated() {
+        let mut renderer = Renderer::new(200, 200);
+        renderer.clear();
+        let camera = Camera::new(200.0, 200.0);
+        let cube = ShapeFactory::create_cube(1.0);
+        renderer.render_mesh(&cube, &Mat4::identity(), &camera);
+
+        // The front side blends its four corner colors
+        let buffer = renderer.get_buffer();
+        let center = Color::from_u32(buffer[100 * 200 + 100]);
+        assert!((100..=155).contains(&center.r) && (100..=155).contains(&center.g));
+        let shades: HashSet<u32> = buffer.iter().copied().collect();
+        assert!(shades.len() > 100);
+    }
+
+    #[test]
+    fn test_flat_and_gouraud_shading() {
+        let camera = Camera::new(200.0, 200.0);
+        let sphere = ShapeFactory::create_sphere(1.5, 12, 12);
+        let render = |mode: ShadingMode| {
+            let mut renderer = Renderer::new(200, 200);
+            renderer.set_shading_mode(mode);
+            renderer.clear();
+            renderer.render_mesh(&sphere, &Mat4::identity(), &camera);
+            renderer.get_buffer().to_vec()
+        };
+
+        // Flat shading gives one shade per face, Gouraud blends across them
+        let flat: HashSet<u32> = render(ShadingMode::Flat).into_iter().collect();
+        let gouraud: HashSet<u32> = render(ShadingMode::Gouraud).into_iter().collect();
+        assert!(flat.len() > 10);
+        assert!(gouraud.len() > flat.len() * 2);
+
+        // The light comes from above, so the top is brighter than the bottom
+        let buffer = render(ShadingMode::Gouraud);
+        let top = Color::from_u32(buffer[60 * 200 + 100]);
+        let bottom = Color::from_u32(buffer[140 * 200 + 100]);
+        assert!(top.r > bottom.r && bottom.r > 0);
+
+        assert_eq!(ShadingMode::Gouraud.next(), ShadingMode::Wireframe);
+    }
+
+    #[test]
+    fn test_reversed_winding_is_culled() {
+        let mut renderer = Renderer::new(200, 200);
+        renderer.clear();
+        let camera = Camera::new(200.0, 200.0);
+
+        // Open panel made of the cube side facing the camera; a closed mesh
+        // would just show its far side from the inside once reversed
+        let mut panel = Mesh::create_cube(1.0);
+        panel.faces.retain(|face| face.normal.z < -0.5);
+        panel.reverse_face_winding();
+        renderer.render_mesh_with_color(&panel, &Mat4::identity(), &camera, Color::white());
+        assert_eq!(count_drawn_pixels(&mut renderer), 0);
+
+        panel.reverse_face_winding();
+        renderer.render_mesh_with_color(&panel, &Mat4::identity(), &camera, Color::white());
+        assert!(count_drawn_pixels(&mut renderer) > 0);
+    }
+
+    #[test]
+    fn test_near_plane_clipping() {
+        let near = 1.0;
+        let a = Vec3::new(0.0, 0.0, -5.0);
+        let b = Vec3::new(2.0, 0.0, -5.0);
+        let c = Vec3::new(0.0, 2.0, 5.0);
+        assert_eq!(clip_triangle_near([a, b, c], near).len(), 2);
+        assert_eq!(clip_triangle_near([a, Vec3::new(2.0, 0.0, 5.0), c], near).len(), 1);
+        assert!(clip_triangle_near([Vec3::new(0.0, 0.0, 5.0), Vec3::new(1.0, 0.0, 5.0), c], near).is_empty());
+        let whole = clip_triangle_near([a, b, Vec3::new(0.0, 2.0, -5.0)], near);
+        assert_eq!(whole.len(), 1);
+        assert_eq!(whole[0][2].y, 2.0);
+        for triangle in clip_triangle_near([a, b, c], near) {
+            assert!(triangle.iter().all(|p| p.z <= -near + 1e-9));
+        }
+
+        // A floor triangle reaching behind the camera still covers the
+        // bottom of the screen instead of being dropped
+        let camera = Camera::new(200.0, 200.0);
+        let mut floor = Mesh::new();
+        for position in [Vec3::new(-5.0, -1.0, -10.0), Vec3::new(0.0, -1.0, 10.0), Vec3::new(5.0, -1.0, -10.0)] {
+            floor.add_vertex(Vertex::new(position, Vec3::new(0.0, 1.0, 0.0), Vec2::zero()));
+        }
+        floor.add_face([0, 1, 2]);
+
+        let mut renderer = Renderer::new(200, 200);
+        renderer.clear();
+        renderer.render_mesh(&floor, &Mat4::identity(), &camera);
+        let buffer = renderer.get_buffer();
+        assert_ne!(buffer[199 * 200 + 100], Color::black().to_u32());
+        assert_eq!(buffer[50 * 200 + 100], Color::black().to_u32());
+
+        // The wireframe is cut at the near plane rather than wrapping around
+        renderer.clear();
+        renderer.set_shading_mode(ShadingMode::Wireframe);
+        renderer.render_mesh(&floor, &Mat4::identity(), &camera);
+        assert!(count_drawn_pixels(&mut renderer) > 0);
+        assert_eq!(renderer.get_buffer()[50 * 200 + 100], Color::black().to_u32());
+    }
+
+    #[test]
+    fn test_depth_range_follows_camera() {
+        let mut camera = Camera::new(200.0, 200.0);
+        camera.near = 1.0;
+        camera.far = 11.0;
+        camera.update_matrices();
+        let cube = ShapeFactory::create_cube(2.0);
+        let mut renderer = Renderer::new(200, 200);
+
+        // The cube's front face is 4 units from the camera, 30% into its range
+        for orthographic in [false, true] {
+            if orthographic {
+                camera.set_orthographic(5.0);
+            }
+            renderer.clear();
+            renderer.render_mesh(&cube, &Mat4::identity(), &camera);
+            let depth = renderer.rasterizer.depth_buffer();
+            assert_eq!((depth.near, depth.far), (1.0, 11.0));
+            assert_eq!(depth.projection_mode, camera.projection_mode);
+            let z = renderer.rasterizer.read_depth(100, 100).unwrap();
+            assert!((depth.normalize(z) - 0.3).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_render_scene_shaded_uses_scene_lights() {
+        let camera = Camera::new(100.0, 100.0);
+        let mut scene = Scene::new();
+        scene.create_mesh_node("ball".to_string(), ShapeFactory::create_sphere(1.0, 8, 8));
+        scene.update_transforms();
+
+        let mut renderer = Renderer::new(100, 100);
+        renderer.clear();
+        renderer.render_scene_shaded(&scene, &camera);
+        let ambient_only = Color::from_u32(renderer.get_buffer()[50 * 100 + 50]);
+
+        scene.add_light(Light::directional(Vec3::new(0.0, 0.0, 1.0), Color::white(), 1.0));
+        renderer.clear();
+        renderer.render_scene_shaded(&scene, &camera);
+        let lit = Color::from_u32(renderer.get_buffer()[50 * 100 + 50]);
+        assert!(ambient_only.r > 0 && lit.r > ambient_only.r);
+    }
+
+    #[test]
+    fn test_wireframe_from_edges() {
+        let camera = Camera::new(200.0, 200.0);
+        let mut panel = Mesh::create_cube(1.0);
+        panel.faces.retain(|face| face.normal.z < -0.5);
+
+        let view_projection = camera.get_view_projection_matrix();
+        let projector = Renderer::new(200, 200);
+        let project = |p: &Vec3| projector.project_point(p, &view_projection).unwrap();
+        let corners: Vec<Vec3> = panel.vertices.iter().map(|v| project(&v.position)).collect();
+
+        // Highest per-pixel write count away from the corners, where edges meet
+        let max_edge_overdraw = |renderer: &Renderer| {
+            let counts = renderer.rasterizer.get_overdraw_counts().unwrap();
+            (0..counts.len())
+                .filter(|&i| {
+                    let (x, y) = ((i % 200) as f64, (i / 200) as f64);
+                    corners.iter().all(|c| (c.x - x).abs() > 3.0 || (c.y - y).abs() > 3.0)
+                })
+                .map(|i| counts[i])
+                .max()
+                .unwrap()
+        };
+        let setup = || {
+            let mut renderer = Renderer::new(200, 200);
+            renderer.rasterizer.set_depth_test_mode(DepthTestMode::Always);
+            renderer.rasterizer.enable_overdraw_tracking(true);
+            renderer.clear();
+            renderer
+        };
+
+        let mut per_edge = setup();
+        per_edge.draw_wireframe_from_edges(&panel, &Mat4::identity(), &camera, Color::white());
+        assert_eq!(max_edge_overdraw(&per_edge), 1);
+
+        // Drawing face by face traces the shared diagonal twice
+        let mut per_face = setup();
+        for face in &panel.faces {
+            let [v0, v1, v2] = face.vertices.map(|i| {
+                let p = project(&panel.vertices[i].position);
+                Vec2::new(p.x, p.y)
+            });
+            per_face.rasterizer.draw_triangle_wireframe(v0, v1, v2, Color::white());
+        }
+        assert_eq!(max_edge_overdraw(&per_face), 2);
+    }
+
+    #[test]
+    fn test_render_command_priority() {
+        let mut renderer = Renderer::new(200, 200);
+        renderer.clear();
+        let camera = Camera::new(200.0, 200.0);
+        let scene_color = Color::new(255, 0, 0, 255);
+        let overlay_color = Color::new(0, 255, 0, 255);
+        let ui_color = Color::new(0, 0, 255, 255);
+
+        // The overlay cube sits inside the scene cube, so only the depth
+        // reset keeps it visible
+        renderer.submit_ui_rect(0, 0, 10, 10, ui_color);
+        renderer.submit_overlay(&Mesh::create_cube(0.5), &Mat4::identity(), overlay_color);
+        renderer.submit(RenderCommand::mesh(&Mesh::create_cube(2.0), &Mat4::identity(), scene_color));
+        renderer.flush(&camera);
+
+        let buffer = renderer.get_buffer();
+        assert_eq!(buffer[100 * 200 + 100], overlay_color.to_u32());
+        assert_eq!(buffer[100 * 200 + 70], scene_color.to_u32());
+        assert_eq!(buffer[5 * 200 + 5], ui_color.to_u32());
+        assert!(renderer.commands.is_empty());
+    }
+
+    #[test]
+    fn test_render_mesh_shaded() {
+        let mut renderer = Renderer::new(200, 200);
+        renderer.clear();
+        let camera = Camera::new(200.0, 200.0);
+        let sphere = ShapeFactory::create_sphere(1.0, 8, 8);
+        let light = Light::point(Vec3::new(0.0, 0.0, -10.0), Color::white(), 1.0, (1.0, 0.0, 0.0));
+        let material = Material::new(Color::new(200, 200, 200, 255));
+
+        renderer.render_mesh_shaded(&sphere, &Mat4::identity(), &camera, &material, &[light]);
+
+        // The middle faces the light and camera, the rim is lit at a grazing angle
+        let buffer = renderer.get_buffer();
+        let center = Color::from_u32(buffer[100 * 200 + 100]);
+        let rim = Color::from_u32(buffer[100 * 200 + 100 - 34]);
+        assert!(rim.r > 0 && center.r > rim.r);
+
+        // Interpolated normals give many more shades than there are faces
+        let shades: HashSet<u32> = buffer.iter().copied().collect();
+        assert!(shades.len() > sphere.faces.len());
+    }
+
+    #[test]
+    fn test_render_mesh_shaded_samples_lightmap_with_uv2() {
+        let mut renderer = Renderer::new(100, 100);
+        renderer.clear();
+        let camera = Camera::new(100.0, 100.0);
+
+        // Quad facing the camera; the primary UVs all point at the red texel,
+        // the second channel runs across it
+        let mut quad = Mesh::new();
+        for (x, y) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+            let mut vertex = Vertex::new(Vec3::new(x, y, 0.0), Vec3::new(0.0, 0.0, -1.0), Vec2::zero());
+            vertex.uv2 = Vec2::new((x + 1.0) / 2.0, (1.0 - y) / 2.0);
+            quad.add_vertex(vertex);
+        }
+        quad.add_face([0, 2, 1]);
+        quad.add_face([0, 3, 2]);
+
+        let red = Color::new(255, 0, 0, 255);
+        let green = Color::new(0, 255, 0, 255);
+        let lightmap = Texture::new(2, 1, vec![red, green]).with_filter(TextureFilter::Nearest);
+        let mut material = Material::new(Color::white()).with_lightmap(std::sync::Arc::new(lightmap));
+        material.ambient = 1.0;
+        material.diffuse = 0.0;
+        material.specular = 0.0;
+        renderer.render_mesh_shaded(&quad, &Mat4::identity(), &camera, &material, &[]);
+
+        let pixel_at = |renderer: &mut Renderer, x: f64| {
+            let ndc = camera.get_view_projection_matrix().transform_vec3(&Vec3::new(x, 0.0, 0.0));
+            let screen = renderer.to_screen_space(&ndc);
+            renderer.get_buffer()[screen.y as usize * 100 + screen.x as usize]
+        };
+        assert_eq!(pixel_at(&mut renderer, -0.5), red.to_u32());
+        assert_eq!(pixel_at(&mut renderer, 0.5), green.to_u32());
+    }
+
+    #[test]
+    fn test_instanced_rendering() {
+        let camera = Camera::new(800.0, 600.0);
+        let cube = Mesh::create_cube(0.2);
+        let transforms: Vec<Mat4> = (0..100)
+            .map(|i| Mat4::translation((i % 10) as f64 * 0.4 - 1.8, (i / 10) as f64 * 0.4 - 1.8, 0.0))
+            .collect();
+
+        let mut instanced = Renderer::new(800, 600);
+        instanced.clear();
+        instanced.render_instanced(&cube, &transforms, &camera);
+
+        let mut looped = Renderer::new(800, 600);
+        looped.clear();
+        for transform in &transforms {
+            looped.render_mesh(&cube, transform, &camera);
+        }
+
+        assert!(count_drawn_pixels(&mut instanced) > 0);
+        assert_eq!(instanced.get_buffer(), looped.get_buffer());
+    }
+
+    #[test]
+    fn test_frustum_cull_instances() {
+        let renderer = Renderer::new(800, 600);
+        let camera = Camera::new(800.0, 600.0);
+        let cube = Mesh::create_cube(1.0);
+        let transforms = [
+            Mat4::identity(),
+            Mat4::translation(0.0, 0.0, -20.0),
+            Mat4::translation(500.0, 0.0, 0.0),
+            Mat4::translation(0.0, 1.0, 10.0),
+        ];
+
+        assert_eq!(renderer.frustum_cull_instances(&cube, &transforms, &camera), vec![0, 3]);
+    }
+
+    #[test]
+    fn test_render_mesh_culls_offscreen_meshes() {
+        let mut renderer = Renderer::new(200, 200);
+        renderer.clear();
+        let mut camera = Camera::new(200.0, 200.0);
+        camera.set_position(Vec3::zero());
+        camera.look_at(Vec3::new(0.0, 0.0, 1.0));
+        camera.update();
+
+        let cube = Mesh::create_cube(1.0);
+        let bounds = cube.calculate_bounding_box();
+        let behind = Mat4::translation(0.0, 0.0, -1000.0);
+        let ahead = Mat4::translation(0.0, 0.0, 10.0);
+        assert!(!renderer.is_aabb_visible(&bounds, &behind, &camera));
+        assert!(renderer.is_aabb_visible(&bounds, &ahead, &camera));
+
+        renderer.render_mesh(&cube, &behind, &camera);
+        assert_eq!(count_drawn_pixels(&mut renderer), 0);
+        renderer.render_mesh(&cube, &ahead, &camera);
+        assert!(count_drawn_pixels(&mut renderer) > 0);
+    }
+
+    #[test]
+    fn test_axis_gizmo() {
+        let mut renderer = Renderer::new(200, 200);
+        renderer.clear();
+        let mut camera = Camera::new(200.0, 200.0);
+        camera.set_position(Vec3::new(0.0, 0.0, 5.0));
+        camera.look_at(Vec3::zero());
+        camera.update();
+
+        renderer.draw_axis_gizmo(&camera, 50, 150, 30.0);
+
+        let buffer = renderer.get_buffer();
+        let pixels_of = |color: Color| -> Vec<(usize, usize)> {
+            buffer.iter().enumerate()
+                .filter(|(_, &p)| p == color.to_u32())
+                .map(|(i, _)| (i % 200, i / 200))
+                .collect()
+        };
+        let x_axis = pixels_of(Color::new(255, 0, 0, 255));
+        let y_axis = pixels_of(Color::new(0, 255, 0, 255));
+        let z_axis = pixels_of(Color::new(0, 0, 255, 255));
+
+        // X runs right along the anchor row, Y up along the anchor column
+        assert!(x_axis.len() >= 29 && x_axis.iter().all(|&(x, y)| y == 150 && x > 50));
+        assert!(y_axis.len() >= 29 && y_axis.iter().all(|&(x, y)| x == 50 && y < 150));
+        // Z points at the viewer and collapses to a dot
+        assert_eq!(z_axis, vec![(50, 150)]);
+    }
 }
@@ -1,25 +1,101 @@
-use std::collections::HashMap;
-use crate::math::{Vec3, Mat4};
-use crate::geometry::Mesh;
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::Arc;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use crate::animation::Animator;
+use crate::math::{Vec3, Mat4, Quaternion};
+use crate::geometry::{BoundingBox, Mesh};
+use crate::lighting::{Light, Material};
+use serde::{Deserialize, Serialize};
 
-type NodeId = usize;
+pub type NodeId = usize;
+pub type LightId = usize;
 
 #[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    CycleDetected,
+    Json(serde_json::Error),
+    UnknownMesh(String),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io(err) => write!(f, "I/O error: {}", err),
+            SceneError::CycleDetected => write!(f, "node cannot be parented to itself or a descendant"),
+            SceneError::Json(err) => write!(f, "invalid scene JSON: {}", err),
+            SceneError::UnknownMesh(name) => write!(f, "no mesh registered as '{}'", name),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+impl From<io::Error> for SceneError {
+    fn from(err: io::Error) -> Self {
+        SceneError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SceneError {
+    fn from(err: serde_json::Error) -> Self {
+        SceneError::Json(err)
+    }
+}
+
+// Meshes by name, for scene files that refer to meshes instead of embedding them
+#[derive(Debug, Default)]
+pub struct MeshRegistry {
+    meshes: HashMap<String, Arc<Mesh>>,
+}
+
+impl MeshRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Replaces any mesh already registered under the name
+    pub fn register(&mut self, name: &str, mesh: Mesh) -> Arc<Mesh> {
+        let mesh = Arc::new(mesh);
+        self.meshes.insert(name.to_string(), Arc::clone(&mesh));
+        mesh
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<Mesh>> {
+        self.meshes.get(name).cloned()
+    }
+}
+
+// Only the components are serialized; the matrices are rebuilt on load
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transform {
     pub position: Vec3,
     pub rotation: Vec3,
+    // Takes precedence over the Euler `rotation` when set
+    pub orientation: Option<Quaternion>,
     pub scale: Vec3,
+    #[serde(skip, default = "Mat4::identity")]
     pub local_matrix: Mat4,
+    #[serde(skip, default = "Mat4::identity")]
     pub world_matrix: Mat4,  // Make this public
+    #[serde(skip, default = "always_dirty")]
     dirty: bool,
 }
 
+fn always_dirty() -> bool {
+    true
+}
+
 
 impl Transform {
     pub fn new() -> Self {
         Self {
             position: Vec3::zero(),
             rotation: Vec3::zero(),
+            orientation: None,
             scale: Vec3::new(1.0, 1.0, 1.0),
             local_matrix: Mat4::identity(),
             world_matrix: Mat4::identity(),
@@ -34,44 +110,158 @@ impl Transform {
 
     pub fn set_rotation(&mut self, rotation: Vec3) {
         self.rotation = rotation;
+        self.orientation = None;
+        self.dirty = true;
+    }
+
+    pub fn set_orientation(&mut self, orientation: Quaternion) {
+        self.orientation = Some(orientation.normalize());
         self.dirty = true;
     }
 
+    // Current rotation as a quaternion, whichever form it is stored in
+    pub fn get_orientation(&self) -> Quaternion {
+        self.orientation.unwrap_or_else(|| Quaternion::from_euler(self.rotation))
+    }
+
     pub fn set_scale(&mut self, scale: Vec3) {
         self.scale = scale;
         self.dirty = true;
     }
 
+    // Blend from this transform towards `other`, with t clamped to [0, 1].
+    // Position and scale are lerped. Quaternion orientations are slerped;
+    // Euler angles are interpolated per axis along the shorter way round.
+    // The result is only marked dirty if it differs from this transform.
+    pub fn interpolate(&self, other: &Transform, t: f64) -> Transform {
+        let t = t.clamp(0.0, 1.0);
+        let mut result = Transform {
+            position: self.position + (other.position - self.position) * t,
+            rotation: self.rotation,
+            orientation: self.orientation,
+            scale: self.scale + (other.scale - self.scale) * t,
+            local_matrix: self.local_matrix,
+            world_matrix: self.world_matrix,
+            dirty: self.dirty,
+        };
+
+        if self.orientation.is_some() || other.orientation.is_some() {
+            result.orientation = Some(self.get_orientation().slerp(&other.get_orientation(), t));
+        } else {
+            let angle_delta = |from: f64, to: f64| {
+                let delta = (to - from).rem_euclid(2.0 * std::f64::consts::PI);
+                if delta > std::f64::consts::PI { delta - 2.0 * std::f64::consts::PI } else { delta }
+            };
+            let rotation_delta = Vec3::new(
+                angle_delta(self.rotation.x, other.rotation.x),
+                angle_delta(self.rotation.y, other.rotation.y),
+                angle_delta(self.rotation.z, other.rotation.z),
+            );
+            result.rotation += rotation_delta * t;
+        }
+
+        result.dirty |= result.position != self.position
+            || result.rotation != self.rotation
+            || result.orientation != self.orientation
+            || result.scale != self.scale;
+        result
+    }
+
+    // Moves a frame-rate independent fraction of the way towards `target`:
+    // the remaining difference decays by exp(-speed * dt) per call
+    pub fn interpolate_to(&mut self, target: &Transform, speed: f64, dt: f64) {
+        let t = 1.0 - (-speed * dt).exp();
+        *self = self.interpolate(target, t);
+    }
+
+    pub fn snap_to(&mut self, target: &Transform) {
+        self.position = target.position;
+        self.rotation = target.rotation;
+        self.orientation = target.orientation;
+        self.scale = target.scale;
+        self.dirty = true;
+    }
+
+    // Local matrix from the current position, rotation and scale
+    fn compose(&self) -> Mat4 {
+        match self.orientation {
+            Some(orientation) => Mat4::trs_quat(self.position, orientation, self.scale),
+            None => Mat4::trs(self.position, self.rotation, self.scale),
+        }
+    }
+
     fn update_local_matrix(&mut self) {
         if self.dirty {
-            // Create transformation matrices
-            let translation = Mat4::translation(self.position.x, self.position.y, self.position.z);
-            let rotation_x = Mat4::rotation_x(self.rotation.x);
-            let rotation_y = Mat4::rotation_y(self.rotation.y);
-            let rotation_z = Mat4::rotation_z(self.rotation.z);
-            let scale = Mat4::scaling(self.scale.x, self.scale.y, self.scale.z);
-
-            // Combine matrices: T * Rz * Ry * Rx * S
-            self.local_matrix = translation
-                .multiply(&rotation_z)
-                .multiply(&rotation_y)
-                .multiply(&rotation_x)
-                .multiply(&scale);
+            // Combine matrices: T * R * S, R being Rz * Ry * Rx for Euler angles
+            self.local_matrix = self.compose();
 
             self.dirty = false;
         }
     }
+
+    pub fn from_mat4(m: Mat4) -> Self {
+        let mut transform = Self::new();
+        transform.set_from_matrix(&m);
+        transform
+    }
+
+    // Decomposes a TRS matrix back into position, Z * Y * X Euler rotation and
+    // scale. Shear (from non-uniform scale under rotation) cannot be represented.
+    // A mirroring matrix comes back with a negative X scale.
+    fn set_from_matrix(&mut self, matrix: &Mat4) {
+        let m = &matrix.data;
+        let mut scale = Vec3::new(
+            Vec3::new(m[0][0], m[1][0], m[2][0]).length(),
+            Vec3::new(m[0][1], m[1][1], m[2][1]).length(),
+            Vec3::new(m[0][2], m[1][2], m[2][2]).length(),
+        );
+        if matrix.determinant() < 0.0 {
+            scale.x = -scale.x;
+        }
+
+        // Pure rotation part, columns divided by their scale
+        let r = |row: usize, col: usize| {
+            let s = [scale.x, scale.y, scale.z][col];
+            if s != 0.0 { m[row][col] / s } else { 0.0 }
+        };
+
+        let pitch = (-r(2, 0)).clamp(-1.0, 1.0).asin();
+        let rotation = if pitch.cos() > 1e-6 {
+            Vec3::new(r(2, 1).atan2(r(2, 2)), pitch, r(1, 0).atan2(r(0, 0)))
+        } else {
+            // Gimbal lock: fold the whole remaining rotation into X
+            Vec3::new((-r(1, 2)).atan2(r(1, 1)), pitch, 0.0)
+        };
+
+        self.position = Vec3::new(m[0][3], m[1][3], m[2][3]);
+        self.rotation = rotation;
+        self.orientation = None;
+        self.scale = scale;
+        self.dirty = true;
+        self.update_local_matrix();
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct SceneNode {
     pub id: NodeId,
     pub name: String,
     pub transform: Transform,
-    pub mesh: Option<Mesh>,
+    // Shared between instances created with Scene::instantiate_node
+    #[serde(skip)]
+    pub mesh: Option<Arc<Mesh>>,
+    // MeshRegistry key stored in scene files in place of the mesh itself
+    pub mesh_name: Option<String>,
+    pub material: Material,
     pub parent: Option<NodeId>,
     pub children: Vec<NodeId>,
-    pub visible: bool,
+    // Changed through Scene::set_visible, which keeps the visible count
+    visible: bool,
+    pub tags: Vec<String>,
+    // Application state attached to the node. Not saved with the scene and
+    // not copied by instantiate_node.
+    #[serde(skip)]
+    pub user_data: Option<Box<dyn Any + Send + Sync>>,
 }
 
 impl SceneNode {
@@ -81,17 +271,61 @@ impl SceneNode {
             name,
             transform: Transform::new(),
             mesh: None,
+            mesh_name: None,
+            material: Material::default(),
             parent: None,
             children: Vec::new(),
             visible: true,
+            tags: Vec::new(),
+            user_data: None,
+        }
+    }
+
+    // Assigns a registered mesh, remembering its name for serialization
+    pub fn set_named_mesh(&mut self, registry: &MeshRegistry, name: &str) -> Result<(), SceneError> {
+        let mesh = registry.get(name).ok_or_else(|| SceneError::UnknownMesh(name.to_string()))?;
+        self.mesh = Some(mesh);
+        self.mesh_name = Some(name.to_string());
+        Ok(())
+    }
+
+    // Adding a tag the node already has is a no-op
+    pub fn add_tag(&mut self, tag: &str) {
+        if !self.has_tag(tag) {
+            self.tags.push(tag.to_string());
         }
     }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    // Replaces any previous user data, whatever its type
+    pub fn set_user_data<T: 'static + Send + Sync>(&mut self, data: T) {
+        self.user_data = Some(Box::new(data));
+    }
+
+    // None when there is no user data or it has a different type
+    pub fn get_user_data<T: 'static>(&self) -> Option<&T> {
+        self.user_data.as_ref()?.downcast_ref::<T>()
+    }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct Scene {
     nodes: HashMap<NodeId, SceneNode>,
     root_nodes: Vec<NodeId>,
     next_id: NodeId,
+    // Cached for count_visible_nodes, rebuilt when a scene is loaded
+    #[serde(skip)]
+    visible_count: usize,
+    lights: Vec<Light>,
+    #[serde(skip)]
+    animators: Vec<Animator>,
 }
 
 impl Scene {
@@ -100,6 +334,9 @@ impl Scene {
             nodes: HashMap::new(),
             root_nodes: Vec::new(),
             next_id: 0,
+            visible_count: 0,
+            lights: Vec::new(),
+            animators: Vec::new(),
         }
     }
 
@@ -109,6 +346,7 @@ impl Scene {
 
         let node = SceneNode::new(id, name);
         self.nodes.insert(id, node);
+        self.visible_count += 1;
         self.root_nodes.push(id);
         id
     }
@@ -116,12 +354,91 @@ impl Scene {
     pub fn create_mesh_node(&mut self, name: String, mesh: Mesh) -> NodeId {
         let id = self.create_node(name);
         if let Some(node) = self.nodes.get_mut(&id) {
-            node.mesh = Some(mesh);
+            node.mesh = Some(Arc::new(mesh));
         }
         id
     }
 
-    pub fn set_parent(&mut self, child_id: NodeId, parent_id: NodeId) {
+    // Creates a node sharing the source node's mesh, starting from a copy of
+    // its local transform and material. The new node has no children. An
+    // unknown source gives an empty node, an unknown parent a root node.
+    pub fn instantiate_node(&mut self, source_id: NodeId, parent_id: Option<NodeId>) -> NodeId {
+        let name = self.nodes.get(&source_id)
+            .map(|source| source.name.clone())
+            .unwrap_or_default();
+        let id = self.create_node(name);
+
+        if let Some(source) = self.nodes.get(&source_id) {
+            let mesh = source.mesh.clone();
+            let mesh_name = source.mesh_name.clone();
+            let material = source.material.clone();
+            let tags = source.tags.clone();
+            let mut transform = Transform::new();
+            transform.snap_to(&source.transform);
+
+            let node = self.nodes.get_mut(&id).unwrap();
+            node.mesh = mesh;
+            node.mesh_name = mesh_name;
+            node.material = material;
+            node.tags = tags;
+            node.transform = transform;
+        }
+
+        if let Some(parent_id) = parent_id.filter(|parent_id| self.nodes.contains_key(parent_id)) {
+            // A fresh node cannot be an ancestor of anything
+            self.set_parent(id, parent_id).unwrap();
+        }
+        id
+    }
+
+    pub fn add_animator(&mut self, animator: Animator) {
+        self.animators.push(animator);
+    }
+
+    // Advances every animator and writes the results to their nodes. Call
+    // before update_transforms. Non-looping animators are dropped once they
+    // have applied their last keyframe, leaving the node free to move.
+    pub fn update_animators(&mut self, dt: f64) {
+        let mut animators = std::mem::take(&mut self.animators);
+        for animator in &mut animators {
+            animator.update(dt, self);
+        }
+        animators.retain(|animator| !animator.is_finished());
+        self.animators = animators;
+    }
+
+    // Stops every animator driving the node
+    pub fn remove_animators(&mut self, node_id: NodeId) {
+        self.animators.retain(|animator| animator.node_id != node_id);
+    }
+
+    pub fn clear_animators(&mut self) {
+        self.animators.clear();
+    }
+
+    pub fn animator_count(&self) -> usize {
+        self.animators.len()
+    }
+
+    pub fn add_light(&mut self, light: Light) -> LightId {
+        self.lights.push(light);
+        self.lights.len() - 1
+    }
+
+    pub fn lights(&self) -> &[Light] {
+        &self.lights
+    }
+
+    pub fn set_parent(&mut self, child_id: NodeId, parent_id: NodeId) -> Result<(), SceneError> {
+        // Refuse to parent a node under itself or one of its descendants
+        let mut ancestor = Some(parent_id);
+        while let Some(id) = ancestor {
+            if id == child_id {
+                return Err(SceneError::CycleDetected);
+            }
+            ancestor = self.nodes.get(&id).and_then(|node| node.parent);
+        }
+
         // Remove from previous parent or root
         if let Some(node) = self.nodes.get(&child_id) {
             if let Some(old_parent) = node.parent {
@@ -140,6 +457,7 @@ impl Scene {
         if let Some(parent_node) = self.nodes.get_mut(&parent_id) {
             parent_node.children.push(child_id);
         }
+        Ok(())
     }
 
     pub fn update_transforms(&mut self) {
@@ -178,10 +496,114 @@ impl Scene {
         self.nodes.get(&id).map(|node| node.transform.world_matrix.clone())
     }
 
+    // Conversions between world space and a node's local space, using the
+    // world matrix from the last update_transforms call
+    pub fn world_to_local(&self, node_id: NodeId, world_point: Vec3) -> Option<Vec3> {
+        let inverse = self.nodes.get(&node_id)?.transform.world_matrix.inverse()?;
+        Some(inverse.transform_vec3(&world_point))
+    }
+
+    pub fn local_to_world(&self, node_id: NodeId, local_point: Vec3) -> Option<Vec3> {
+        let world = &self.nodes.get(&node_id)?.transform.world_matrix;
+        Some(world.transform_vec3(&local_point))
+    }
+
+    // Directions ignore the node's translation but keep rotation and scale
+    pub fn world_direction_to_local(&self, node_id: NodeId, dir: Vec3) -> Option<Vec3> {
+        let inverse = self.nodes.get(&node_id)?.transform.world_matrix.inverse()?;
+        Some(inverse.transform_direction(&dir))
+    }
+
+    // World-space bounds of a node's mesh and all of its descendants, as of the
+    // last update_transforms call. None if the subtree contains no meshes.
+    pub fn compute_world_bounds(&self, id: NodeId) -> Option<BoundingBox> {
+        let node = self.nodes.get(&id)?;
+
+        let own_bounds = node.mesh.as_ref()
+            .filter(|mesh| !mesh.vertices.is_empty())
+            .map(|mesh| mesh.calculate_bounding_box().transform(&node.transform.world_matrix));
+
+        node.children.iter()
+            .filter_map(|&child_id| self.compute_world_bounds(child_id))
+            .fold(own_bounds, |acc, child_bounds| match acc {
+                Some(bounds) => Some(bounds.union(&child_bounds)),
+                None => Some(child_bounds),
+            })
+    }
+
+    // World-space bounds of every mesh in the scene
+    pub fn compute_scene_bounds(&self) -> Option<BoundingBox> {
+        self.root_nodes.iter()
+            .filter_map(|&root_id| self.compute_world_bounds(root_id))
+            .reduce(|acc, bounds| acc.union(&bounds))
+    }
+
+    // Closest and farthest distance from `camera_position` to the scene
+    // bounds. The near distance is 0 when the camera is inside the bounds.
+    pub fn estimate_clip_planes(&self, camera_position: Vec3) -> Option<(f64, f64)> {
+        let bounds = self.compute_scene_bounds()?;
+
+        let closest = camera_position.clamp(bounds.min, bounds.max);
+        let farthest = Vec3::new(
+            if camera_position.x < (bounds.min.x + bounds.max.x) * 0.5 { bounds.max.x } else { bounds.min.x },
+            if camera_position.y < (bounds.min.y + bounds.max.y) * 0.5 { bounds.max.y } else { bounds.min.y },
+            if camera_position.z < (bounds.min.z + bounds.max.z) * 0.5 { bounds.max.z } else { bounds.min.z },
+        );
+
+        Some(((closest - camera_position).length(), (farthest - camera_position).length()))
+    }
+
     pub fn iter_nodes(&self) -> impl Iterator<Item = &SceneNode> {
         self.nodes.values()
     }
 
+    // Every node, visible or not, parents before their children and each
+    // subtree finished before its next sibling
+    pub fn depth_first_iter(&self) -> impl Iterator<Item = &SceneNode> {
+        let mut stack: Vec<NodeId> = self.root_nodes.iter().rev().copied().collect();
+        std::iter::from_fn(move || {
+            while let Some(id) = stack.pop() {
+                if let Some(node) = self.nodes.get(&id) {
+                    stack.extend(node.children.iter().rev());
+                    return Some(node);
+                }
+            }
+            None
+        })
+    }
+
+    // Every node, visible or not, level by level from the roots
+    pub fn breadth_first_iter(&self) -> impl Iterator<Item = &SceneNode> {
+        let mut queue: VecDeque<NodeId> = self.root_nodes.iter().copied().collect();
+        std::iter::from_fn(move || {
+            while let Some(id) = queue.pop_front() {
+                if let Some(node) = self.nodes.get(&id) {
+                    queue.extend(&node.children);
+                    return Some(node);
+                }
+            }
+            None
+        })
+    }
+
+    // Every node in id (creation) order
+    pub fn iter_nodes_mut(&mut self) -> impl Iterator<Item = &mut SceneNode> {
+        let mut nodes: Vec<&mut SceneNode> = self.nodes.values_mut().collect();
+        nodes.sort_unstable_by_key(|node| node.id);
+        nodes.into_iter()
+    }
+
+    // Calls `f` on every node, visible or not, breadth-first from the roots
+    pub fn apply_to_all<F: FnMut(&mut SceneNode)>(&mut self, mut f: F) {
+        let mut queue: VecDeque<NodeId> = self.root_nodes.iter().copied().collect();
+        while let Some(id) = queue.pop_front() {
+            if let Some(node) = self.nodes.get_mut(&id) {
+                f(node);
+                queue.extend(&node.children);
+            }
+        }
+    }
+
     pub fn traverse_visible<F>(&self, mut callback: F)
     where
         F: FnMut(&SceneNode),
@@ -208,6 +630,10 @@ impl Scene {
     // Scene management utilities
     pub fn remove_node(&mut self, id: NodeId) {
         if let Some(node) = self.nodes.remove(&id) {
+            if node.visible {
+                self.visible_count -= 1;
+            }
+
             // Remove from parent's children
             if let Some(parent_id) = node.parent {
                 if let Some(parent) = self.nodes.get_mut(&parent_id) {
@@ -224,16 +650,210 @@ impl Scene {
         }
     }
 
+    // Removes a single node, handing its children to its own parent (or making
+    // them roots) while keeping their world transforms unchanged
+    pub fn remove_node_keep_children(&mut self, id: NodeId) {
+        let Some(node) = self.nodes.remove(&id) else {
+            return;
+        };
+        if node.visible {
+            self.visible_count -= 1;
+        }
+
+        if let Some(parent_id) = node.parent {
+            if let Some(parent) = self.nodes.get_mut(&parent_id) {
+                parent.children.retain(|&child| child != id);
+            }
+        } else {
+            self.root_nodes.retain(|&root| root != id);
+        }
+
+        let removed_local = node.transform.compose();
+
+        for &child_id in &node.children {
+            if let Some(child) = self.nodes.get_mut(&child_id) {
+                let child_local = child.transform.compose();
+                child.transform.set_from_matrix(&removed_local.multiply(&child_local));
+                child.parent = node.parent;
+            }
+        }
+
+        match node.parent.and_then(|parent_id| self.nodes.get_mut(&parent_id)) {
+            Some(parent) => parent.children.extend(&node.children),
+            None => self.root_nodes.extend(&node.children),
+        }
+    }
+
+    // Turns the node's direct children into root nodes, keeping their world
+    // transforms unchanged, and returns their ids
+    pub fn detach_children(&mut self, parent_id: NodeId) -> Vec<NodeId> {
+        let Some(parent) = self.nodes.get_mut(&parent_id) else {
+            return Vec::new();
+        };
+        let children = std::mem::take(&mut parent.children);
+
+        // Composed from the local transforms so it holds even before the next
+        // update_transforms
+        let mut parent_world = Mat4::identity();
+        let mut ancestor = Some(parent_id);
+        while let Some(node) = ancestor.and_then(|id| self.nodes.get(&id)) {
+            parent_world = node.transform.compose().multiply(&parent_world);
+            ancestor = node.parent;
+        }
+
+        for &child_id in &children {
+            if let Some(child) = self.nodes.get_mut(&child_id) {
+                let child_local = child.transform.compose();
+                child.transform.set_from_matrix(&parent_world.multiply(&child_local));
+                child.parent = None;
+            }
+        }
+        self.root_nodes.extend(&children);
+        children
+    }
+
+    // Removes every descendant of the node but keeps the node itself
+    pub fn remove_all_children(&mut self, parent_id: NodeId) {
+        let children = self.nodes.get(&parent_id)
+            .map(|parent| parent.children.clone())
+            .unwrap_or_default();
+        for child_id in children {
+            self.remove_node(child_id);
+        }
+    }
+
     pub fn find_node_by_name(&self, name: &str) -> Option<NodeId> {
         self.nodes.iter()
             .find(|(_, node)| node.name == name)
             .map(|(&id, _)| id)
     }
+
+    // All nodes with the given name, in creation order
+    pub fn find_all_nodes_by_name(&self, name: &str) -> Vec<NodeId> {
+        self.find_nodes_where(|node| node.name == name)
+    }
+
+    // All nodes carrying the tag, in creation order
+    pub fn find_nodes_by_tag(&self, tag: &str) -> Vec<NodeId> {
+        self.find_nodes_where(|node| node.has_tag(tag))
+    }
+
+    pub fn find_nodes_where<F>(&self, predicate: F) -> Vec<NodeId>
+    where
+        F: Fn(&SceneNode) -> bool,
+    {
+        let mut ids: Vec<NodeId> = self.nodes.values()
+            .filter(|node| predicate(node))
+            .map(|node| node.id)
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    pub fn count_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn count_visible_nodes(&self) -> usize {
+        self.visible_count
+    }
+
+    // Shows or hides a single node; its children keep their own flags
+    pub fn set_visible(&mut self, id: NodeId, visible: bool) {
+        if let Some(node) = self.nodes.get_mut(&id) {
+            if node.visible != visible {
+                node.visible = visible;
+                if visible {
+                    self.visible_count += 1;
+                } else {
+                    self.visible_count -= 1;
+                }
+            }
+        }
+    }
+
+    // Nodes, hierarchy and lights as JSON. Meshes are saved by their
+    // mesh_name only, so nodes whose mesh has no name are loaded without one.
+    pub fn serialize_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("scene contains only JSON-compatible data")
+    }
+
+    // Inverse of serialize_json, looking each node's mesh up in `registry`
+    pub fn deserialize_json(s: &str, registry: &MeshRegistry) -> Result<Scene, SceneError> {
+        let mut scene: Scene = serde_json::from_str(s)?;
+        scene.visible_count = scene.nodes.values().filter(|node| node.visible).count();
+        for node in scene.nodes.values_mut() {
+            if let Some(name) = &node.mesh_name {
+                node.mesh = Some(registry.get(name).ok_or_else(|| SceneError::UnknownMesh(name.clone()))?);
+            }
+        }
+        scene.update_transforms();
+        Ok(scene)
+    }
+
+    // Writes every visible mesh node into one OBJ file, in world space as of
+    // the last update_transforms call. Each node becomes its own group.
+    pub fn export_to_obj(&self, path: &str) -> Result<(), SceneError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let mut result = Ok(());
+        let mut vertex_offset = 0;
+        let mut normal_offset = 0;
+
+        self.traverse_visible(|node| {
+            if result.is_err() {
+                return;
+            }
+            if let Some(mesh) = &node.mesh {
+                result = Self::write_obj_group(&mut writer, node, mesh, vertex_offset, normal_offset);
+                vertex_offset += mesh.vertices.len();
+                normal_offset += mesh.faces.len();
+            }
+        });
+
+        result?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn write_obj_group<W: Write>(
+        writer: &mut W,
+        node: &SceneNode,
+        mesh: &Mesh,
+        vertex_offset: usize,
+        normal_offset: usize,
+    ) -> io::Result<()> {
+        let world = &node.transform.world_matrix;
+        let positions: Vec<Vec3> = mesh.get_transformed_vertices().iter()
+            .map(|v| world.transform_vec3(&v.position))
+            .collect();
+
+        writeln!(writer, "g {}", node.name)?;
+        for p in &positions {
+            writeln!(writer, "v {} {} {}", p.x, p.y, p.z)?;
+        }
+
+        // Face normals are recomputed in world space so non-uniform scale is honoured
+        for face in &mesh.faces {
+            let [a, b, c] = face.vertices;
+            let n = (positions[b] - positions[a]).cross(&(positions[c] - positions[a])).normalize();
+            writeln!(writer, "vn {} {} {}", n.x, n.y, n.z)?;
+        }
+
+        // OBJ indices are 1-based and global across the whole file
+        for (i, face) in mesh.faces.iter().enumerate() {
+            let n = normal_offset + i + 1;
+            let [a, b, c] = face.vertices.map(|v| vertex_offset + v + 1);
+            writeln!(writer, "f {}//{} {}//{} {}//{}", a, n, b, n, c, n)?;
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rasterizer::Color;
 
     #[test]
     fn test_scene_creation() {
@@ -248,7 +868,7 @@ mod tests {
         let parent_id = scene.create_node("parent".to_string());
         let child_id = scene.create_node("child".to_string());
 
-        scene.set_parent(child_id, parent_id);
+        scene.set_parent(child_id, parent_id).unwrap();
 
         let parent = scene.get_node(parent_id).unwrap();
         assert!(parent.children.contains(&child_id));
@@ -267,7 +887,7 @@ mod tests {
             node.transform.set_position(Vec3::new(1.0, 0.0, 0.0));
         }
 
-        scene.set_parent(child_id, parent_id);
+        scene.set_parent(child_id, parent_id).unwrap();
         if let Some(node) = scene.get_node_mut(child_id) {
             node.transform.set_position(Vec3::new(0.0, 1.0, 0.0));
         }
@@ -288,10 +908,494 @@ mod tests {
         let parent_id = scene.create_node("parent".to_string());
         let child_id = scene.create_node("child".to_string());
 
-        scene.set_parent(child_id, parent_id);
+        scene.set_parent(child_id, parent_id).unwrap();
         scene.remove_node(parent_id);
 
         assert!(scene.get_node(parent_id).is_none());
         assert!(scene.get_node(child_id).is_none());
     }
+
+    #[test]
+    fn test_remove_node_keep_children() {
+        let mut scene = Scene::new();
+        let grandparent = scene.create_node("grandparent".to_string());
+        let parent = scene.create_node("parent".to_string());
+        let child = scene.create_node("child".to_string());
+        scene.set_parent(parent, grandparent).unwrap();
+        scene.set_parent(child, parent).unwrap();
+
+        let transforms = [
+            (grandparent, Vec3::new(1.0, 2.0, 3.0), Vec3::new(0.0, 0.5, 0.0), 1.0),
+            (parent, Vec3::new(-2.0, 0.5, 1.0), Vec3::new(0.3, -0.2, 0.7), 2.0),
+            (child, Vec3::new(0.0, 1.0, -4.0), Vec3::new(-0.4, 0.9, 0.1), 0.5),
+        ];
+        for (id, position, rotation, scale) in transforms {
+            let transform = &mut scene.get_node_mut(id).unwrap().transform;
+            transform.set_position(position);
+            transform.set_rotation(rotation);
+            transform.set_scale(Vec3::new(scale, scale, scale));
+        }
+        scene.update_transforms();
+        let before = scene.get_world_transform(child).unwrap();
+
+        scene.remove_node_keep_children(parent);
+        scene.update_transforms();
+
+        assert!(scene.get_node(parent).is_none());
+        assert_eq!(scene.get_node(grandparent).unwrap().children, vec![child]);
+        assert_eq!(scene.get_node(child).unwrap().parent, Some(grandparent));
+
+        let after = scene.get_world_transform(child).unwrap();
+        for i in 0..4 {
+            for j in 0..4 {
+                assert!((before.data[i][j] - after.data[i][j]).abs() < 1e-8);
+            }
+        }
+    }
+
+    #[test]
+    fn test_detach_and_remove_children() {
+        let mut scene = Scene::new();
+        let root = scene.create_node("ui".to_string());
+        let panel = scene.create_node("inventory".to_string());
+        let slots: Vec<NodeId> = (0..3).map(|i| scene.create_node(format!("slot{}", i))).collect();
+        scene.set_parent(panel, root).unwrap();
+        for &slot in &slots {
+            scene.set_parent(slot, panel).unwrap();
+        }
+        let icon = scene.create_node("icon".to_string());
+        scene.set_parent(icon, slots[0]).unwrap();
+
+        scene.get_node_mut(root).unwrap().transform.set_position(Vec3::new(10.0, 0.0, 0.0));
+        scene.get_node_mut(panel).unwrap().transform.set_scale(Vec3::new(2.0, 2.0, 2.0));
+        scene.get_node_mut(slots[1]).unwrap().transform.set_position(Vec3::new(1.0, 1.0, 0.0));
+        scene.update_transforms();
+        let before = scene.get_world_transform(slots[1]).unwrap();
+
+        assert_eq!(scene.detach_children(panel), slots);
+        scene.update_transforms();
+        assert!(scene.get_node(panel).unwrap().children.is_empty());
+        assert_eq!(scene.get_node(slots[1]).unwrap().parent, None);
+        assert_eq!(scene.get_node(icon).unwrap().parent, Some(slots[0]));
+        let after = scene.get_world_transform(slots[1]).unwrap();
+        assert!((after.transform_vec3(&Vec3::zero()) - before.transform_vec3(&Vec3::zero())).length() < 1e-9);
+        assert_eq!(after.transform_vec3(&Vec3::zero()), Vec3::new(12.0, 2.0, 0.0));
+
+        for &slot in &slots {
+            scene.set_parent(slot, panel).unwrap();
+        }
+        scene.remove_all_children(panel);
+        assert!(scene.get_node(panel).is_some());
+        assert!(scene.get_node(panel).unwrap().children.is_empty());
+        assert_eq!(scene.count_nodes(), 2);
+    }
+
+    #[test]
+    fn test_find_nodes() {
+        let mut scene = Scene::new();
+        let enemies: Vec<NodeId> = (0..5).map(|_| scene.create_node("enemy".to_string())).collect();
+        for _ in 0..3 {
+            scene.create_node("player".to_string());
+        }
+
+        assert_eq!(scene.find_all_nodes_by_name("enemy"), enemies);
+        assert_eq!(scene.find_nodes_where(|n| n.name.starts_with('e')), enemies);
+        assert!(scene.find_all_nodes_by_name("boss").is_empty());
+
+        scene.set_visible(enemies[0], false);
+        scene.remove_node(enemies[1]);
+        assert_eq!(scene.count_nodes(), 7);
+        assert_eq!(scene.count_visible_nodes(), 6);
+    }
+
+    #[test]
+    fn test_user_data() {
+        let mut scene = Scene::new();
+        let id = scene.create_node("enemy".to_string());
+        let node = scene.get_node_mut(id).unwrap();
+        assert!(node.get_user_data::<u32>().is_none());
+
+        node.set_user_data(42u32);
+        assert_eq!(node.get_user_data::<u32>(), Some(&42));
+        assert!(node.get_user_data::<i64>().is_none());
+
+        node.set_user_data("boss".to_string());
+        assert_eq!(node.get_user_data::<String>().map(String::as_str), Some("boss"));
+        assert!(node.get_user_data::<u32>().is_none());
+    }
+
+    #[test]
+    fn test_cached_node_counts() {
+        let mut scene = Scene::new();
+        let [root, a, b, c] = ["root", "a", "b", "c"].map(|name| scene.create_node(name.to_string()));
+        scene.set_parent(a, root).unwrap();
+        scene.set_parent(b, a).unwrap();
+        assert_eq!((scene.count_nodes(), scene.count_visible_nodes()), (4, 4));
+
+        // Hiding twice only counts once
+        scene.set_visible(b, false);
+        scene.set_visible(b, false);
+        assert_eq!(scene.count_visible_nodes(), 3);
+        assert!(!scene.get_node(b).unwrap().is_visible());
+
+        let restored = Scene::deserialize_json(&scene.serialize_json(), &MeshRegistry::new()).unwrap();
+        assert_eq!(restored.count_visible_nodes(), 3);
+
+        scene.remove_node_keep_children(a);
+        assert_eq!((scene.count_nodes(), scene.count_visible_nodes()), (3, 2));
+        scene.remove_node(root);
+        assert_eq!((scene.count_nodes(), scene.count_visible_nodes()), (1, 1));
+
+        scene.set_visible(c, false);
+        scene.set_visible(c, true);
+        scene.set_visible(99, false);
+        assert_eq!(scene.count_visible_nodes(), 1);
+    }
+
+    #[test]
+    fn test_find_nodes_by_tag() {
+        let mut scene = Scene::new();
+        let crate_id = scene.create_node("crate".to_string());
+        let enemy = scene.create_node("enemy".to_string());
+        let hud = scene.create_node("hud".to_string());
+
+        scene.get_node_mut(crate_id).unwrap().add_tag("collidable");
+        let node = scene.get_node_mut(enemy).unwrap();
+        node.add_tag("enemy");
+        node.add_tag("collidable");
+        node.add_tag("enemy");
+        assert_eq!(node.tags.len(), 2);
+        scene.get_node_mut(hud).unwrap().add_tag("ui");
+
+        assert_eq!(scene.find_nodes_by_tag("collidable"), vec![crate_id, enemy]);
+        assert_eq!(scene.find_nodes_by_tag("enemy"), vec![enemy]);
+        assert!(scene.find_nodes_by_tag("player").is_empty());
+        assert!(scene.get_node(hud).unwrap().has_tag("ui"));
+
+        // Instances keep the source's tags
+        let copy = scene.instantiate_node(enemy, None);
+        assert_eq!(scene.find_nodes_by_tag("enemy"), vec![enemy, copy]);
+    }
+
+    #[test]
+    fn test_instantiate_node() {
+        let mut scene = Scene::new();
+        let group = scene.create_node("group".to_string());
+        let source = scene.create_mesh_node("rock".to_string(), Mesh::create_cube(1.0));
+        scene.get_node_mut(source).unwrap().transform.set_position(Vec3::new(0.0, 2.0, 0.0));
+
+        let instances: Vec<NodeId> = (0..5).map(|_| scene.instantiate_node(source, Some(group))).collect();
+        scene.get_node_mut(instances[0]).unwrap().transform.set_position(Vec3::new(5.0, 0.0, 0.0));
+        scene.update_transforms();
+
+        let source_mesh = scene.get_node(source).unwrap().mesh.clone().unwrap();
+        for &id in &instances {
+            let node = scene.get_node(id).unwrap();
+            assert!(Arc::ptr_eq(node.mesh.as_ref().unwrap(), &source_mesh));
+            assert_eq!(node.parent, Some(group));
+        }
+        assert_eq!(Arc::strong_count(&source_mesh), 7);
+
+        let world_x = |id: NodeId| scene.get_world_transform(id).unwrap().data[0][3];
+        assert_eq!(world_x(instances[0]), 5.0);
+        for &id in &instances[1..] {
+            assert_eq!(world_x(id), 0.0);
+            assert_eq!(scene.get_node(id).unwrap().transform.position.y, 2.0);
+        }
+    }
+
+    #[test]
+    fn test_scene_lights() {
+        let mut scene = Scene::new();
+        assert!(scene.lights().is_empty());
+        let sun = scene.add_light(Light::directional(Vec3::new(0.0, -1.0, 0.0), Color::white(), 1.0));
+        let lamp = scene.add_light(Light::point(Vec3::zero(), Color::white(), 1.0, (1.0, 0.0, 0.0)));
+        assert_eq!((sun, lamp), (0, 1));
+        assert!(matches!(scene.lights()[lamp], Light::Point(_)));
+    }
+
+    #[test]
+    fn test_traversal_iterators() {
+        // root
+        // |- a (hidden)
+        // |  |- a1
+        // |  `- a2
+        // `- b
+        //    `- b1
+        let mut scene = Scene::new();
+        let [root, a, b, a1, a2, b1] = ["root", "a", "b", "a1", "a2", "b1"].map(|name| scene.create_node(name.to_string()));
+        for (child, parent) in [(a, root), (b, root), (a1, a), (a2, a), (b1, b)] {
+            scene.set_parent(child, parent).unwrap();
+        }
+        scene.set_visible(a, false);
+
+        let names = |nodes: Vec<&SceneNode>| nodes.iter().map(|node| node.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(scene.depth_first_iter().collect()), ["root", "a", "a1", "a2", "b", "b1"]);
+        assert_eq!(names(scene.breadth_first_iter().collect()), ["root", "a", "b", "a1", "a2", "b1"]);
+
+        let leaves: Vec<NodeId> = scene.breadth_first_iter()
+            .filter(|node| node.children.is_empty())
+            .map(|node| node.id)
+            .collect();
+        assert_eq!(leaves, vec![a1, a2, b1]);
+    }
+
+    #[test]
+    fn test_mutable_iteration() {
+        let mut scene = Scene::new();
+        let root = scene.create_node("root".to_string());
+        let child = scene.create_node("child".to_string());
+        let other_root = scene.create_node("other".to_string());
+        let grandchild = scene.create_node("grandchild".to_string());
+        scene.set_parent(child, root).unwrap();
+        scene.set_parent(grandchild, child).unwrap();
+        scene.set_visible(child, false);
+
+        let ids: Vec<NodeId> = scene.iter_nodes_mut().map(|node| node.id).collect();
+        assert_eq!(ids, vec![root, child, other_root, grandchild]);
+
+        // Breadth-first, including the hidden subtree
+        let mut order = Vec::new();
+        scene.apply_to_all(|node| {
+            order.push(node.id);
+            node.transform.set_position(Vec3::new(1.0, 0.0, 0.0));
+        });
+        assert_eq!(order, vec![root, other_root, child, grandchild]);
+        assert!(scene.iter_nodes().all(|node| node.transform.position.x == 1.0));
+    }
+
+    #[test]
+    fn test_estimate_clip_planes() {
+        let mut scene = Scene::new();
+        assert!(scene.estimate_clip_planes(Vec3::zero()).is_none());
+
+        scene.create_mesh_node("cube".to_string(), Mesh::create_cube(2.0));
+        scene.update_transforms();
+
+        let (near, far) = scene.estimate_clip_planes(Vec3::new(0.0, 0.0, -5.0)).unwrap();
+        assert!((near - 4.0).abs() < 1e-10);
+        assert!((far - 38.0_f64.sqrt()).abs() < 1e-10);
+
+        let (near, _) = scene.estimate_clip_planes(Vec3::new(0.5, 0.0, 0.0)).unwrap();
+        assert_eq!(near, 0.0);
+    }
+
+    #[test]
+    fn test_transform_interpolate_midpoint() {
+        let mut from = Transform::new();
+        from.set_position(Vec3::new(0.0, 0.0, 0.0));
+        from.set_rotation(Vec3::new(0.0, 0.2, 0.0));
+        from.update_local_matrix();
+        let mut to = Transform::new();
+        to.set_position(Vec3::new(4.0, -2.0, 10.0));
+        to.set_rotation(Vec3::new(0.0, 0.6, 0.0));
+        to.set_scale(Vec3::new(3.0, 3.0, 3.0));
+
+        let mid = from.interpolate(&to, 0.5);
+        assert_eq!(mid.position, Vec3::new(2.0, -1.0, 5.0));
+        assert_eq!(mid.scale, Vec3::new(2.0, 2.0, 2.0));
+        assert!((mid.rotation.y - 0.4).abs() < 1e-12);
+        assert!(mid.dirty);
+
+        // Nothing moves, so the cached matrix stays valid
+        assert!(!from.interpolate(&to, 0.0).dirty);
+        assert_eq!(from.interpolate(&to, 7.0).position, to.position);
+    }
+
+    #[test]
+    fn test_transform_interpolation() {
+        let mut current = Transform::new();
+        let mut target = Transform::new();
+        target.set_position(Vec3::new(10.0, 0.0, 0.0));
+        target.set_rotation(Vec3::new(0.0, 0.0, -3.0));
+        target.set_scale(Vec3::new(2.0, 2.0, 2.0));
+
+        let initial = (target.position - current.position).length();
+        for _ in 0..10 {
+            current.interpolate_to(&target, 5.0, 0.016);
+        }
+        let remaining = (target.position - current.position).length();
+        assert!((remaining / initial - (-5.0 * 0.016 * 10.0_f64).exp()).abs() < 1e-10);
+        assert!(current.scale.x > 1.0 && current.scale.x < 2.0);
+        // -3 rad is reached by turning the short way, through negative angles
+        assert!(current.rotation.z < 0.0);
+
+        current.snap_to(&target);
+        current.update_local_matrix();
+        assert_eq!(current.position, target.position);
+        assert_eq!(current.local_matrix.data, Mat4::trs(target.position, target.rotation, target.scale).data);
+    }
+
+    #[test]
+    fn test_space_conversions() {
+        let mut scene = Scene::new();
+        let parent = scene.create_node("parent".to_string());
+        let node = scene.create_node("node".to_string());
+        scene.set_parent(node, parent).unwrap();
+        scene.get_node_mut(parent).unwrap().transform.set_position(Vec3::new(3.0, 0.0, 0.0));
+        scene.get_node_mut(node).unwrap().transform.set_scale(Vec3::new(2.0, 2.0, 2.0));
+        scene.update_transforms();
+
+        let local = scene.world_to_local(node, Vec3::new(5.0, 0.0, 0.0)).unwrap();
+        assert!((local - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-10);
+        let world = scene.local_to_world(node, local).unwrap();
+        assert!((world - Vec3::new(5.0, 0.0, 0.0)).length() < 1e-10);
+
+        let direction = scene.world_direction_to_local(node, Vec3::new(0.0, 4.0, 0.0)).unwrap();
+        assert!((direction - Vec3::new(0.0, 2.0, 0.0)).length() < 1e-10);
+        assert!(scene.world_to_local(99, Vec3::zero()).is_none());
+    }
+
+    #[test]
+    fn test_set_parent_rejects_cycles() {
+        let mut scene = Scene::new();
+        let a = scene.create_node("a".to_string());
+        let b = scene.create_node("b".to_string());
+        let c = scene.create_node("c".to_string());
+        scene.set_parent(b, a).unwrap();
+        scene.set_parent(c, b).unwrap();
+
+        assert!(matches!(scene.set_parent(a, c), Err(SceneError::CycleDetected)));
+        assert!(matches!(scene.set_parent(b, b), Err(SceneError::CycleDetected)));
+        assert_eq!(scene.get_node(a).unwrap().parent, None);
+        assert_eq!(scene.get_node(a).unwrap().children, vec![b]);
+        assert_eq!(scene.get_node(c).unwrap().children, Vec::<NodeId>::new());
+        assert_eq!(scene.root_nodes, vec![a]);
+
+        // Moving c up next to b is fine
+        scene.set_parent(c, a).unwrap();
+        assert_eq!(scene.get_node(a).unwrap().children, vec![b, c]);
+        assert!(scene.get_node(b).unwrap().children.is_empty());
+    }
+
+    #[test]
+    fn test_transform_from_mat4() {
+        let assert_matrices_close = |a: &Mat4, b: &Mat4| {
+            for row in 0..4 {
+                for col in 0..4 {
+                    assert!((a.data[row][col] - b.data[row][col]).abs() < 1e-9);
+                }
+            }
+        };
+
+        let matrix = Mat4::trs(Vec3::new(1.0, -2.0, 3.0), Vec3::new(0.3, -0.7, 1.2), Vec3::new(2.0, 0.5, 1.5));
+        let transform = Transform::from_mat4(matrix);
+        assert_matrices_close(&transform.local_matrix, &matrix);
+        assert!((transform.rotation - Vec3::new(0.3, -0.7, 1.2)).length() < 1e-9);
+
+        // Mirrored matrices survive the round trip
+        let mirrored = Mat4::trs(Vec3::zero(), Vec3::new(0.0, 0.4, 0.0), Vec3::new(1.0, -1.0, 1.0));
+        let transform = Transform::from_mat4(mirrored);
+        assert!(transform.scale.x < 0.0);
+        assert_matrices_close(&transform.local_matrix, &mirrored);
+    }
+
+    #[test]
+    fn test_quaternion_orientation() {
+        let mut transform = Transform::new();
+        transform.set_rotation(Vec3::new(0.0, 1.0, 0.0));
+        let quarter_turn = Quaternion::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), std::f64::consts::PI / 2.0);
+        transform.set_orientation(quarter_turn);
+        transform.update_local_matrix();
+        let p = transform.local_matrix.transform_vec3(&Vec3::new(1.0, 0.0, 0.0));
+        assert!((p - Vec3::new(0.0, 0.0, -1.0)).length() < 1e-10);
+
+        // Interpolating towards an orientation slerps halfway in one step
+        let mut current = Transform::new();
+        let mut target = Transform::new();
+        target.set_orientation(quarter_turn);
+        current.interpolate_to(&target, 2.0_f64.ln(), 1.0);
+        let halfway = Quaternion::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), std::f64::consts::PI / 4.0);
+        assert!((current.get_orientation().dot(&halfway) - 1.0).abs() < 1e-10);
+
+        // Euler rotation takes over again once set
+        current.set_rotation(Vec3::zero());
+        assert!(current.orientation.is_none());
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut registry = MeshRegistry::new();
+        registry.register("cube", Mesh::create_cube(1.0));
+
+        let mut scene = Scene::new();
+        let parent = scene.create_node("parent".to_string());
+        let child = scene.create_node("child".to_string());
+        scene.set_parent(child, parent).unwrap();
+        let node = scene.get_node_mut(child).unwrap();
+        node.set_named_mesh(&registry, "cube").unwrap();
+        node.transform.set_position(Vec3::new(1.0, 2.0, 3.0));
+        node.add_tag("collidable");
+        scene.add_light(Light::directional(Vec3::new(0.0, -1.0, 0.0), Color::white(), 0.5));
+
+        let loaded = Scene::deserialize_json(&scene.serialize_json(), &registry).unwrap();
+        assert_eq!(loaded.get_node(parent).unwrap().name, "parent");
+        let loaded_child = loaded.get_node(child).unwrap();
+        assert_eq!(loaded_child.name, "child");
+        assert_eq!(loaded_child.parent, Some(parent));
+        assert_eq!(loaded.get_node(parent).unwrap().children, vec![child]);
+        assert!(loaded_child.has_tag("collidable"));
+        assert_eq!(loaded_child.mesh.as_ref().unwrap().vertices.len(), 8);
+        assert_eq!(loaded_child.transform.world_matrix.data[1][3], 2.0);
+        assert_eq!(loaded.lights().len(), 1);
+
+        // New nodes don't reuse loaded ids
+        let mut loaded = loaded;
+        assert!(loaded.create_node("extra".to_string()) > child);
+
+        let missing = Scene::deserialize_json(&scene.serialize_json(), &MeshRegistry::new());
+        assert!(matches!(missing, Err(SceneError::UnknownMesh(name)) if name == "cube"));
+        assert!(matches!(Scene::deserialize_json("{", &registry), Err(SceneError::Json(_))));
+    }
+
+    #[test]
+    fn test_export_to_obj() {
+        let mut scene = Scene::new();
+        let left = scene.create_mesh_node("left".to_string(), Mesh::create_cube(2.0));
+        let right = scene.create_mesh_node("right".to_string(), Mesh::create_cube(2.0));
+        scene.get_node_mut(left).unwrap().transform.set_position(Vec3::new(-3.0, 0.0, 0.0));
+        scene.get_node_mut(right).unwrap().transform.set_position(Vec3::new(3.0, 0.0, 0.0));
+        scene.update_transforms();
+
+        let path = std::env::temp_dir().join("ironsight_export_test.obj");
+        scene.export_to_obj(path.to_str().unwrap()).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let positions: Vec<Vec3> = contents.lines()
+            .filter_map(|line| line.strip_prefix("v "))
+            .map(|rest| {
+                let c: Vec<f64> = rest.split_whitespace().map(|t| t.parse().unwrap()).collect();
+                Vec3::new(c[0], c[1], c[2])
+            })
+            .collect();
+        assert_eq!(positions.len(), 16);
+        assert_eq!(contents.lines().filter(|l| l.starts_with("f ")).count(), 24);
+        assert!(contents.contains("g left") && contents.contains("g right"));
+
+        let min_x = positions.iter().map(|p| p.x).fold(f64::INFINITY, f64::min);
+        let max_x = positions.iter().map(|p| p.x).fold(f64::NEG_INFINITY, f64::max);
+        assert!((min_x + 4.0).abs() < 1e-10);
+        assert!((max_x - 4.0).abs() < 1e-10);
+
+        // The second group's faces must point at its own vertices
+        assert!(contents.contains("f 9//13"));
+    }
+
+    #[test]
+    fn test_compute_world_bounds() {
+        let mut scene = Scene::new();
+        let parent_id = scene.create_mesh_node("parent".to_string(), Mesh::create_cube(2.0));
+        let child_id = scene.create_mesh_node("child".to_string(), Mesh::create_cube(2.0));
+        scene.set_parent(child_id, parent_id).unwrap();
+        scene.get_node_mut(child_id).unwrap().transform.set_position(Vec3::new(3.0, 0.0, 0.0));
+        scene.update_transforms();
+
+        let bounds = scene.compute_world_bounds(parent_id).unwrap();
+        assert!((bounds.min - Vec3::new(-1.0, -1.0, -1.0)).length() < 1e-10);
+        assert!((bounds.max - Vec3::new(4.0, 1.0, 1.0)).length() < 1e-10);
+
+        let empty_id = scene.create_node("empty".to_string());
+        assert!(scene.compute_world_bounds(empty_id).is_none());
+    }
 }
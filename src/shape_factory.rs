@@ -1,10 +1,286 @@
-use crate::geometry::Mesh;
+use crate::geometry::{Mesh, Vertex};
+use crate::math::{Mat4, Vec2, Vec3};
+use crate::rasterizer::Color;
+use std::f64::consts::PI;
 
 pub struct ShapeFactory;
 
 
 impl ShapeFactory {
+    // Corners are colored like an RGB cube: red along +X, green along +Y
+    // and blue along +Z, blending across the faces
     pub fn create_cube(size: f64) -> Mesh {
-        Mesh::create_cube(size)
+        let mut mesh = Mesh::create_cube(size);
+        for vertex in &mut mesh.vertices {
+            let channel = |v: f64| if v > 0.0 { 255 } else { 0 };
+            let p = vertex.position;
+            vertex.color = Color::new(channel(p.x), channel(p.y), channel(p.z), 255);
+        }
+        mesh
+    }
+
+    // UV sphere centered on the origin, with the poles on the Y axis.
+    // Rings and sectors are clamped to 3..=256.
+    pub fn create_sphere(radius: f64, rings: u32, sectors: u32) -> Mesh {
+        let rings = rings.clamp(3, 256) as usize;
+        let sectors = sectors.clamp(3, 256) as usize;
+        let mut mesh = Mesh::with_capacity((rings + 1) * (sectors + 1), 2 * sectors * (rings - 1));
+
+        // The seam column is duplicated so texture coordinates can reach 1
+        for ring in 0..=rings {
+            let phi = PI * ring as f64 / rings as f64;
+            for sector in 0..=sectors {
+                let theta = 2.0 * PI * sector as f64 / sectors as f64;
+                let normal = Vec3::new(phi.sin() * theta.cos(), phi.cos(), phi.sin() * theta.sin());
+                mesh.add_vertex(Vertex::new(
+                    normal * radius,
+                    normal,
+                    Vec2::new(sector as f64 / sectors as f64, ring as f64 / rings as f64),
+                ));
+            }
+        }
+
+        let row = sectors + 1;
+        for ring in 0..rings {
+            for sector in 0..sectors {
+                let a = ring * row + sector;
+                let b = a + row;
+                let c = b + 1;
+                let d = a + 1;
+                // Skip the triangles that collapse onto a pole
+                if ring != 0 {
+                    mesh.add_face([a, d, c]);
+                }
+                if ring != rings - 1 {
+                    mesh.add_face([a, c, b]);
+                }
+            }
+        }
+
+        mesh.generate_vertex_normals();
+        mesh
+    }
+
+    // Flat grid in the XZ plane centered on the origin, facing +Y. Each side
+    // is split into `subdivisions` cells, clamped to 1..=256.
+    pub fn create_plane(width: f64, depth: f64, subdivisions: u32) -> Mesh {
+        let n = subdivisions.clamp(1, 256) as usize;
+        let row = n + 1;
+        let mut mesh = Mesh::with_capacity(row * row, 2 * n * n);
+
+        for j in 0..=n {
+            let v = j as f64 / n as f64;
+            for i in 0..=n {
+                let u = i as f64 / n as f64;
+                mesh.add_vertex(Vertex::new(
+                    Vec3::new((u - 0.5) * width, 0.0, (v - 0.5) * depth),
+                    Vec3::new(0.0, 1.0, 0.0),
+                    Vec2::new(u, v),
+                ));
+            }
+        }
+
+        for j in 0..n {
+            for i in 0..n {
+                let a = j * row + i;
+                let b = a + 1;
+                let c = a + row + 1;
+                let d = a + row;
+                mesh.add_face([a, d, c]);
+                mesh.add_face([a, c, b]);
+            }
+        }
+        mesh
+    }
+
+    // Capped cylinder around the Y axis, centered on the origin. The caps have
+    // their own vertices so the rim edges stay sharp.
+    pub fn create_cylinder(radius: f64, height: f64, segments: u32) -> Mesh {
+        let n = segments.clamp(3, 256) as usize;
+        let half = height / 2.0;
+        let mut mesh = Mesh::with_capacity(4 * n + 2, 4 * n);
+
+        let bottom = Self::add_ring(&mut mesh, radius, -half, n);
+        let top = Self::add_ring(&mut mesh, radius, half, n);
+        for i in 0..n {
+            let next = (i + 1) % n;
+            mesh.add_face([bottom + i, top + i, top + next]);
+            mesh.add_face([bottom + i, top + next, bottom + next]);
+        }
+
+        let bottom_cap = Self::add_ring(&mut mesh, radius, -half, n);
+        let bottom_center = Self::add_axis_point(&mut mesh, -half);
+        let top_cap = Self::add_ring(&mut mesh, radius, half, n);
+        let top_center = Self::add_axis_point(&mut mesh, half);
+        for i in 0..n {
+            let next = (i + 1) % n;
+            mesh.add_face([bottom_center, bottom_cap + i, bottom_cap + next]);
+            mesh.add_face([top_center, top_cap + next, top_cap + i]);
+        }
+
+        mesh.generate_vertex_normals();
+        mesh
+    }
+
+    // Arrow pointing along +Y from the origin: a capped cylinder for the shaft
+    // with a cone on top. Each part gets its own vertices so edges stay sharp.
+    // `segments` is clamped to 3..=256.
+    pub fn create_arrow(
+        shaft_radius: f64,
+        shaft_length: f64,
+        head_radius: f64,
+        head_length: f64,
+        segments: u32,
+    ) -> Mesh {
+        let n = segments.clamp(3, 256) as usize;
+        let mut mesh = Mesh::with_capacity(4 * n + 3, 5 * n);
+
+        // Shaft sides and bottom cap
+        let bottom = Self::add_ring(&mut mesh, shaft_radius, 0.0, n);
+        let top = Self::add_ring(&mut mesh, shaft_radius, shaft_length, n);
+        let bottom_center = Self::add_axis_point(&mut mesh, 0.0);
+        for i in 0..n {
+            let next = (i + 1) % n;
+            mesh.add_face([bottom + i, top + i, top + next]);
+            mesh.add_face([bottom + i, top + next, bottom + next]);
+            mesh.add_face([bottom_center, bottom + i, bottom + next]);
+        }
+
+        // Cone base, covering the top of the shaft, and the cone itself
+        let base = Self::add_ring(&mut mesh, head_radius, shaft_length, n);
+        let base_center = Self::add_axis_point(&mut mesh, shaft_length);
+        let apex = Self::add_axis_point(&mut mesh, shaft_length + head_length);
+        for i in 0..n {
+            let next = (i + 1) % n;
+            mesh.add_face([base_center, base + i, base + next]);
+            mesh.add_face([base + i, apex, base + next]);
+        }
+
+        mesh.generate_vertex_normals();
+        mesh
+    }
+
+    // Circle of `segments` vertices around the Y axis at height `y`;
+    // returns the index of the first one
+    fn add_ring(mesh: &mut Mesh, radius: f64, y: f64, segments: usize) -> usize {
+        let first = mesh.vertices.len();
+        for i in 0..segments {
+            let angle = 2.0 * PI * i as f64 / segments as f64;
+            mesh.add_vertex(Vertex::new(
+                Vec3::new(radius * angle.cos(), y, radius * angle.sin()),
+                Vec3::zero(),
+                Vec2::new(i as f64 / segments as f64, y),
+            ));
+        }
+        first
+    }
+
+    fn add_axis_point(mesh: &mut Mesh, y: f64) -> usize {
+        mesh.add_vertex(Vertex::new(Vec3::new(0.0, y, 0.0), Vec3::zero(), Vec2::zero()))
+    }
+
+    // Red, green and blue arrows of the given length along +X, +Y and +Z
+    pub fn create_coordinate_axes(length: f64) -> Vec<(Mesh, Color)> {
+        let axes = [
+            (Mat4::rotation_z(-PI / 2.0), Color::new(255, 0, 0, 255)),
+            (Mat4::identity(), Color::new(0, 255, 0, 255)),
+            (Mat4::rotation_x(PI / 2.0), Color::new(0, 0, 255, 255)),
+        ];
+
+        axes.into_iter()
+            .map(|(rotation, color)| {
+                let mut arrow = Self::create_arrow(
+                    length * 0.02,
+                    length * 0.8,
+                    length * 0.05,
+                    length * 0.2,
+                    12,
+                );
+                arrow.transform(rotation);
+                arrow.apply_transform();
+                (arrow, color)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cube_colors() {
+        let mut cube = ShapeFactory::create_cube(1.0);
+        let corners: std::collections::HashSet<u32> = cube.vertices.iter().map(|v| v.color.to_u32()).collect();
+        assert_eq!(corners.len(), 8);
+        let top_right_front = cube.vertices.iter()
+            .find(|v| v.position == Vec3::new(0.5, 0.5, 0.5))
+            .unwrap();
+        assert_eq!(top_right_front.color.to_u32(), Color::white().to_u32());
+
+        cube.set_all_face_colors(Color::white());
+        assert!(cube.faces.iter().all(|face| face.color.unwrap().to_u32() == Color::white().to_u32()));
+    }
+
+    #[test]
+    fn test_sphere_and_cylinder() {
+        let sphere = ShapeFactory::create_sphere(2.0, 8, 12);
+        assert_eq!(sphere.vertices.len(), 9 * 13);
+        assert_eq!(sphere.faces.len(), 2 * 12 * 7);
+        assert!(sphere.vertices.iter().all(|v| (v.position.length() - 2.0).abs() < 1e-10));
+        assert!(sphere.faces.iter().all(|f| {
+            f.normal.dot(&sphere.vertices[f.vertices[0]].position) > 0.0
+        }));
+        assert!(sphere.vertices.iter().all(|v| v.normal.dot(&v.position) > 0.0));
+
+        // Degenerate requests are clamped to a usable minimum
+        assert_eq!(ShapeFactory::create_sphere(1.0, 1, 0).faces.len(), 2 * 3 * 2);
+
+        let cylinder = ShapeFactory::create_cylinder(0.5, 3.0, 16);
+        assert_eq!(cylinder.faces.len(), 4 * 16);
+        let bbox = cylinder.calculate_bounding_box();
+        assert!((bbox.min - Vec3::new(-0.5, -1.5, -0.5)).length() < 1e-10);
+        assert!((bbox.max - Vec3::new(0.5, 1.5, 0.5)).length() < 1e-10);
+        assert!(cylinder.faces.iter().all(|f| {
+            let centroid = f.vertices.iter()
+                .fold(Vec3::zero(), |sum, &i| sum + cylinder.vertices[i].position) / 3.0;
+            f.normal.dot(&centroid) > 0.0
+        }));
+    }
+
+    #[test]
+    fn test_plane() {
+        let plane = ShapeFactory::create_plane(4.0, 2.0, 3);
+        assert_eq!(plane.vertices.len(), 16);
+        assert_eq!(plane.faces.len(), 18);
+
+        let bbox = plane.calculate_bounding_box();
+        assert!((bbox.min - Vec3::new(-2.0, 0.0, -1.0)).length() < 1e-10);
+        assert!((bbox.max - Vec3::new(2.0, 0.0, 1.0)).length() < 1e-10);
+        assert!(plane.vertices.iter().all(|v| v.normal == Vec3::new(0.0, 1.0, 0.0)));
+        assert!(plane.faces.iter().all(|f| f.normal.y > 0.99));
+        assert_eq!(plane.vertices[15].uv, Vec2::new(1.0, 1.0));
+    }
+
+    #[test]
+    fn test_arrow_and_axes() {
+        let arrow = ShapeFactory::create_arrow(0.1, 2.0, 0.3, 0.5, 16);
+        let bbox = arrow.calculate_bounding_box();
+        assert!((bbox.max.y - bbox.min.y - 2.5).abs() < 1e-10);
+        assert!((bbox.max.x - bbox.min.x - 0.6).abs() < 1e-10);
+        assert!((bbox.max.z - bbox.min.z - 0.6).abs() < 1e-10);
+        assert_eq!(ShapeFactory::create_arrow(0.1, 2.0, 0.3, 0.5, 10_000).faces.len(), 5 * 256);
+
+        let axes = ShapeFactory::create_coordinate_axes(1.0);
+        let directions = [Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0)];
+        for ((mesh, _), direction) in axes.iter().zip(directions) {
+            // The tip is the vertex furthest along the axis, at full length
+            let tip = mesh.vertices.iter()
+                .map(|v| v.position)
+                .max_by(|a, b| a.dot(&direction).total_cmp(&b.dot(&direction)))
+                .unwrap();
+            assert!((tip - direction).length() < 1e-10);
+        }
+        assert_eq!(axes[0].1.to_u32(), Color::new(255, 0, 0, 255).to_u32());
     }
 }
@@ -0,0 +1,106 @@
+use crate::rasterizer::Color;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFilter {
+    Nearest,
+    Bilinear,
+}
+
+// Row-major RGBA image. Texture coordinates wrap, with (0, 0) at the top-left
+// corner of the first row.
+#[derive(Debug, Clone)]
+pub struct Texture {
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<Color>,
+    pub filter: TextureFilter,
+}
+
+impl Texture {
+    // Panics on an empty texture, which would have no texel to wrap onto
+    pub fn new(width: usize, height: usize, data: Vec<Color>) -> Self {
+        assert!(width > 0 && height > 0, "texture must be at least 1x1");
+        assert_eq!(data.len(), width * height, "texture data does not match its size");
+        Self {
+            width,
+            height,
+            data,
+            filter: TextureFilter::Bilinear,
+        }
+    }
+
+    pub fn from_color(width: usize, height: usize, color: Color) -> Self {
+        Self::new(width, height, vec![color; width * height])
+    }
+
+    pub fn with_filter(mut self, filter: TextureFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn get_pixel(&self, x: usize, y: usize) -> Color {
+        self.data[y * self.width + x]
+    }
+
+    fn texel(&self, x: i64, y: i64) -> Color {
+        let x = x.rem_euclid(self.width as i64) as usize;
+        let y = y.rem_euclid(self.height as i64) as usize;
+        self.get_pixel(x, y)
+    }
+
+    pub fn sample(&self, u: f64, v: f64) -> Color {
+        // Texel centers sit at half-integer coordinates
+        let x = u * self.width as f64 - 0.5;
+        let y = v * self.height as f64 - 0.5;
+
+        match self.filter {
+            TextureFilter::Nearest => self.texel(x.round() as i64, y.round() as i64),
+            TextureFilter::Bilinear => {
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+
+                let texels = [
+                    (self.texel(x0, y0), (1.0 - fx) * (1.0 - fy)),
+                    (self.texel(x0 + 1, y0), fx * (1.0 - fy)),
+                    (self.texel(x0, y0 + 1), (1.0 - fx) * fy),
+                    (self.texel(x0 + 1, y0 + 1), fx * fy),
+                ];
+                let channel = |get: fn(&Color) -> u8| {
+                    let value: f64 = texels.iter().map(|(c, w)| get(c) as f64 * w).sum();
+                    value.round().clamp(0.0, 255.0) as u8
+                };
+                Color::new(channel(|c| c.r), channel(|c| c.g), channel(|c| c.b), channel(|c| c.a))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_texture_sampling() {
+        let black = Color::new(0, 0, 0, 255);
+        let white = Color::new(255, 255, 255, 255);
+        let texture = Texture::new(2, 1, vec![black, white]);
+
+        // Texel centers return the texel itself, halfway between blends
+        assert_eq!(texture.sample(0.25, 0.5).to_u32(), black.to_u32());
+        assert_eq!(texture.sample(0.75, 0.5).to_u32(), white.to_u32());
+        assert_eq!(texture.sample(0.5, 0.5).r, 128);
+
+        let nearest = texture.clone().with_filter(TextureFilter::Nearest);
+        assert_eq!(nearest.sample(0.45, 0.5).to_u32(), black.to_u32());
+        assert_eq!(nearest.sample(0.55, 0.5).to_u32(), white.to_u32());
+        // Coordinates wrap around
+        assert_eq!(nearest.sample(1.25, -3.0).to_u32(), black.to_u32());
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 1x1")]
+    fn test_empty_texture_is_rejected() {
+        Texture::from_color(4, 0, Color::white());
+    }
+}
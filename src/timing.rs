@@ -0,0 +1,227 @@
+use std::collections::VecDeque;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Weight of the newest frame in the smoothed frame rate
+const FPS_SMOOTHING: f64 = 0.1;
+
+// Limits on FrameHistogram buckets, so bad arguments can't allocate without bound
+const MIN_BUCKET_SIZE_MS: f64 = 0.01;
+const MAX_HISTOGRAM_BUCKETS: usize = 100_000;
+
+// Measures frame times and optionally sleeps to hold a target frame rate
+#[derive(Debug, Clone)]
+pub struct FrameTimer {
+    start: Instant,
+    last_tick: Instant,
+    target_period: Option<Duration>,
+    fps: f64,
+    frame_count: u64,
+}
+
+impl FrameTimer {
+    // A target of 0 leaves the frame rate uncapped
+    pub fn new(target_fps: u32) -> Self {
+        let now = Instant::now();
+        Self {
+            start: now,
+            last_tick: now,
+            target_period: (target_fps > 0).then(|| Duration::from_secs_f64(1.0 / target_fps as f64)),
+            fps: 0.0,
+            frame_count: 0,
+        }
+    }
+
+    // Call once per frame. Sleeps off whatever remains of the target frame
+    // period, then returns the seconds since the previous tick.
+    pub fn tick(&mut self) -> f64 {
+        let mut now = Instant::now();
+        if let Some(period) = self.target_period {
+            let frame_time = now - self.last_tick;
+            if frame_time < period {
+                thread::sleep(period - frame_time);
+                now = Instant::now();
+            }
+        }
+
+        let delta_time = (now - self.last_tick).as_secs_f64();
+        self.last_tick = now;
+        self.frame_count += 1;
+
+        if delta_time > 0.0 {
+            let current_fps = 1.0 / delta_time;
+            self.fps = if self.frame_count == 1 {
+                current_fps
+            } else {
+                self.fps + (current_fps - self.fps) * FPS_SMOOTHING
+            };
+        }
+        delta_time
+    }
+
+    // Exponential moving average of the frame rate
+    pub fn fps(&self) -> f64 {
+        self.fps
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    pub fn elapsed_total(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+// Distribution of the most recent frame times, in fixed-size buckets covering
+// [min_ms, max_ms). Times outside the range land in the first or last bucket.
+// The bucket size is raised to at least MIN_BUCKET_SIZE_MS, or further if the
+// range would need more than MAX_HISTOGRAM_BUCKETS; an empty or inverted range
+// becomes a single bucket.
+#[derive(Debug, Clone)]
+pub struct FrameHistogram {
+    pub buckets: Vec<u64>,
+    pub bucket_size_ms: f64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    history: VecDeque<f64>,
+    window: usize,
+}
+
+impl FrameHistogram {
+    pub fn new(bucket_size_ms: f64, min_ms: f64, max_ms: f64, window: usize) -> Self {
+        let mut bucket_size_ms = bucket_size_ms.max(MIN_BUCKET_SIZE_MS);
+        let max_ms = max_ms.max(min_ms + bucket_size_ms);
+        let range = max_ms - min_ms;
+        if range / bucket_size_ms > MAX_HISTOGRAM_BUCKETS as f64 {
+            bucket_size_ms = range / MAX_HISTOGRAM_BUCKETS as f64;
+        }
+        let bucket_count = ((range / bucket_size_ms).ceil() as usize).clamp(1, MAX_HISTOGRAM_BUCKETS);
+        Self {
+            buckets: vec![0; bucket_count],
+            bucket_size_ms,
+            min_ms,
+            max_ms,
+            history: VecDeque::with_capacity(window),
+            window: window.max(1),
+        }
+    }
+
+    fn bucket_index(&self, frame_time_ms: f64) -> usize {
+        let index = ((frame_time_ms - self.min_ms) / self.bucket_size_ms).floor();
+        (index.max(0.0) as usize).min(self.buckets.len() - 1)
+    }
+
+    pub fn record(&mut self, frame_time_ms: f64) {
+        if self.history.len() == self.window {
+            if let Some(oldest) = self.history.pop_front() {
+                let index = self.bucket_index(oldest);
+                self.buckets[index] -= 1;
+            }
+        }
+        let index = self.bucket_index(frame_time_ms);
+        self.buckets[index] += 1;
+        self.history.push_back(frame_time_ms);
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.history.len()
+    }
+
+    // Frame time below which a fraction `p` of the recorded frames fall,
+    // resolved to the middle of its bucket. 0 when nothing was recorded.
+    pub fn percentile(&self, p: f64) -> f64 {
+        let total = self.history.len() as u64;
+        if total == 0 {
+            return 0.0;
+        }
+
+        let rank = ((p.clamp(0.0, 1.0) * total as f64).ceil() as u64).max(1);
+        let mut cumulative = 0;
+        for (index, &count) in self.buckets.iter().enumerate() {
+            cumulative += count;
+            if cumulative >= rank {
+                return self.min_ms + (index as f64 + 0.5) * self.bucket_size_ms;
+            }
+        }
+        self.max_ms
+    }
+
+    pub fn reset(&mut self) {
+        self.buckets.fill(0);
+        self.history.clear();
+    }
+
+    pub fn print_report(&self) {
+        println!("Frame times over the last {} frames:", self.frame_count());
+        for p in [0.5, 0.9, 0.99] {
+            println!("  p{:<4} {:>8.2} ms", p * 100.0, self.percentile(p));
+        }
+    }
+}
+
+impl Default for FrameHistogram {
+    // 0.5ms buckets up to 250ms over the last 1000 frames
+    fn default() -> Self {
+        Self::new(0.5, 0.0, 250.0, 1000)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_timer_caps_frame_rate() {
+        let mut timer = FrameTimer::new(100);
+        for _ in 0..3 {
+            let delta_time = timer.tick();
+            assert!(delta_time >= 0.0099);
+        }
+        assert_eq!(timer.frame_count(), 3);
+        assert!(timer.fps() > 0.0 && timer.fps() <= 101.0);
+        assert!(timer.elapsed_total() >= Duration::from_millis(29));
+    }
+
+    #[test]
+    fn test_frame_histogram_rejects_bad_buckets() {
+        for bucket_size in [0.0, -1.0, f64::NAN] {
+            let mut histogram = FrameHistogram::new(bucket_size, 0.0, 50.0, 10);
+            assert_eq!(histogram.bucket_size_ms, MIN_BUCKET_SIZE_MS);
+            assert_eq!(histogram.buckets.len(), 5000);
+            histogram.record(20.0);
+            histogram.record(1e9);
+            assert_eq!(histogram.frame_count(), 2);
+        }
+
+        // Inverted range: one bucket, everything lands in it
+        let mut histogram = FrameHistogram::new(1.0, 50.0, 10.0, 10);
+        assert_eq!(histogram.buckets.len(), 1);
+        histogram.record(30.0);
+        assert_eq!(histogram.buckets[0], 1);
+
+        let histogram = FrameHistogram::new(1e-9, 0.0, 1e12, 10);
+        assert_eq!(histogram.buckets.len(), MAX_HISTOGRAM_BUCKETS);
+    }
+
+    #[test]
+    fn test_frame_histogram_percentiles() {
+        let mut histogram = FrameHistogram::new(1.0, 0.0, 200.0, 100);
+        for i in 0..100 {
+            histogram.record(if i % 10 == 9 { 100.0 } else { 16.0 });
+        }
+
+        assert!((histogram.percentile(0.90) - 16.0).abs() <= 1.0);
+        assert!((histogram.percentile(0.99) - 100.0).abs() <= 1.0);
+
+        // Only the last 100 frames count
+        for _ in 0..100 {
+            histogram.record(8.0);
+        }
+        assert_eq!(histogram.frame_count(), 100);
+        assert!((histogram.percentile(0.99) - 8.0).abs() <= 1.0);
+
+        histogram.reset();
+        assert_eq!(histogram.percentile(0.5), 0.0);
+    }
+}